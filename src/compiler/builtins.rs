@@ -0,0 +1,474 @@
+//! Methods on the built-in types which are implemented with native instructions, like
+//! `length("héllo")`, `name[0]`, `slice(name, 1, 3)` or `append(list, 4)`.
+//!
+//! Strings are treated as sequences of grapheme clusters, which is what a reader would
+//! consider characters. A string is converted into the list of its characters before it's
+//! measured, indexed or sliced, so `length("héllo")` is `5` no matter how the `é` is encoded.
+
+use crate::compiler::Compiler;
+use crate::type_system::Type;
+use crate::types::{CompilerResult, Expression, Method, PairPattern, Pattern, VariablePattern};
+
+use strontium::machine::instruction::Instruction;
+use strontium::machine::register::RegisterValue;
+
+/// A variant of a multimethod which is built into the language.
+pub struct Builtin {
+    pub name: &'static str,
+    type_parameters: &'static [&'static str],
+    /// The names and types of the parameters.
+    pub parameters: Vec<(&'static str, Type)>,
+    pub result: Type,
+    /// Generate the body of the variant, which finds its arguments in `args` and returns
+    /// its result in `ret`.
+    body: fn(&mut Compiler) -> Vec<Instruction>,
+}
+
+impl Builtin {
+    /// Get all built-in variants.
+    pub fn all() -> Vec<Builtin> {
+        vec![
+            Builtin {
+                name: "length",
+                type_parameters: &[],
+                parameters: vec![("value", Type::String)],
+                result: Type::Int,
+                body: Self::string_length,
+            },
+            Builtin {
+                name: "chars",
+                type_parameters: &[],
+                parameters: vec![("value", Type::String)],
+                result: Type::list_of(&[Type::Char]),
+                body: Self::string_characters,
+            },
+            Builtin {
+                name: "[]",
+                type_parameters: &[],
+                parameters: vec![("value", Type::String), ("index", Type::Int)],
+                result: Type::Char,
+                body: Self::string_index,
+            },
+            Builtin {
+                name: "slice",
+                type_parameters: &[],
+                parameters: vec![("value", Type::String), ("start", Type::Int)],
+                result: Type::String,
+                body: Self::string_slice,
+            },
+            Builtin {
+                name: "slice",
+                type_parameters: &[],
+                parameters: vec![
+                    ("value", Type::String),
+                    ("start", Type::Int),
+                    ("end", Type::Int),
+                ],
+                result: Type::String,
+                body: Self::string_slice_range,
+            },
+            Builtin {
+                name: "length",
+                type_parameters: &["T"],
+                parameters: vec![("list", Self::list())],
+                result: Type::Int,
+                body: Self::list_length,
+            },
+            Builtin {
+                name: "[]",
+                type_parameters: &["T"],
+                parameters: vec![("list", Self::list()), ("index", Type::Int)],
+                result: Type::Parameter(String::from("T")),
+                body: Self::list_index,
+            },
+            Builtin {
+                name: "append",
+                type_parameters: &["T"],
+                parameters: vec![
+                    ("list", Self::list()),
+                    ("element", Type::Parameter(String::from("T"))),
+                ],
+                result: Self::list(),
+                body: Self::list_append,
+            },
+        ]
+    }
+
+    /// Get the type parameters of the variant, like `T` in `[](list List[T], index Int) T`.
+    pub fn type_parameters(&self) -> Vec<String> {
+        self.type_parameters
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// Get the signature of the variant, like `(value String, index Int)`.
+    pub fn signature(&self) -> Option<Pattern> {
+        self.parameters
+            .iter()
+            .map(|(name, parameter_type)| {
+                Pattern::Variable(VariablePattern {
+                    name: Some(name.to_string()),
+                    type_id: parameter_type.annotation(),
+                })
+            })
+            .reduce(|left, right| {
+                Pattern::Pair(PairPattern {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                })
+            })
+    }
+
+    /// Define the built-in variants of the multimethod with the given name which haven't been
+    /// defined yet, so only those which a program calls are compiled into it.
+    pub fn define(compiler: &mut Compiler, name: &str, call: &Expression) -> CompilerResult<()> {
+        for builtin in Self::all().into_iter().filter(|b| b.name == name) {
+            let mut method = Method {
+                name: name.to_string(),
+                qualifier: None,
+                type_parameters: builtin.type_parameters(),
+                signature: builtin.signature(),
+                return_type: None,
+                guard: None,
+                body: Box::new(call.clone()),
+            };
+            method.erase_static_types();
+
+            if compiler
+                .compiled_methods
+                .contains_key(&Compiler::generate_method_id(&method))
+            {
+                continue;
+            }
+
+            let instructions = (builtin.body)(compiler);
+
+            compiler.define_native_method(method, instructions, call.start_pos, call.end_pos)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the argument at the given index into a new register.
+    fn argument(compiler: &mut Compiler, index: i64) -> (String, Vec<Instruction>) {
+        let index_register = compiler.registers.allocate_register();
+        let argument_register = compiler.registers.allocate_register();
+
+        (
+            argument_register.clone(),
+            vec![
+                Instruction::Load {
+                    value: RegisterValue::Int64(index),
+                    register: index_register.clone(),
+                },
+                Instruction::Index {
+                    source: "args".to_string(),
+                    index: index_register,
+                    destination: argument_register,
+                },
+            ],
+        )
+    }
+
+    /// The type `List[T]` of the lists the generic variants accept.
+    fn list() -> Type {
+        Type::Generic {
+            name: String::from("List"),
+            arguments: vec![Type::Parameter(String::from("T"))],
+        }
+    }
+
+    /// Convert the string in `arg` into the list of its characters.
+    fn characters(destination: String) -> Instruction {
+        Instruction::Convert {
+            source: "arg".to_string(),
+            type_name: "List".to_string(),
+            destination,
+        }
+    }
+
+    fn string_length(compiler: &mut Compiler) -> Vec<Instruction> {
+        let characters_register = compiler.registers.allocate_register();
+
+        vec![
+            Self::characters(characters_register.clone()),
+            Instruction::Length {
+                source: characters_register,
+                destination: "ret".to_string(),
+            },
+            Instruction::Return,
+        ]
+    }
+
+    fn string_characters(_compiler: &mut Compiler) -> Vec<Instruction> {
+        vec![Self::characters("ret".to_string()), Instruction::Return]
+    }
+
+    fn string_index(compiler: &mut Compiler) -> Vec<Instruction> {
+        let characters_register = compiler.registers.allocate_register();
+        let (index_register, mut instructions) = Self::argument(compiler, 1);
+
+        instructions.push(Self::characters(characters_register.clone()));
+        instructions.push(Instruction::Index {
+            source: characters_register,
+            index: index_register,
+            destination: "ret".to_string(),
+        });
+        instructions.push(Instruction::Return);
+
+        instructions
+    }
+
+    fn string_slice(compiler: &mut Compiler) -> Vec<Instruction> {
+        Self::slice_characters(compiler, None)
+    }
+
+    fn string_slice_range(compiler: &mut Compiler) -> Vec<Instruction> {
+        Self::slice_characters(compiler, Some(2))
+    }
+
+    /// Slice the characters of the string in `arg` from the start given as the second
+    /// argument up to the end given as the argument at the given index, or to the end of
+    /// the string.
+    fn slice_characters(compiler: &mut Compiler, end: Option<i64>) -> Vec<Instruction> {
+        let characters_register = compiler.registers.allocate_register();
+        let (start_register, mut instructions) = Self::argument(compiler, 1);
+
+        instructions.push(Self::characters(characters_register.clone()));
+
+        let end_register = match end {
+            Some(index) => {
+                let (end_register, mut end_instructions) = Self::argument(compiler, index);
+                instructions.append(&mut end_instructions);
+
+                end_register
+            }
+            None => {
+                let length_register = compiler.registers.allocate_register();
+                instructions.push(Instruction::Length {
+                    source: characters_register.clone(),
+                    destination: length_register.clone(),
+                });
+
+                length_register
+            }
+        };
+
+        instructions.push(Instruction::Slice {
+            source: characters_register.clone(),
+            start: start_register,
+            end: end_register,
+            destination: characters_register.clone(),
+        });
+        instructions.push(Instruction::Convert {
+            source: characters_register,
+            type_name: "String".to_string(),
+            destination: "ret".to_string(),
+        });
+        instructions.push(Instruction::Return);
+
+        instructions
+    }
+
+    fn list_length(_compiler: &mut Compiler) -> Vec<Instruction> {
+        vec![
+            Instruction::Length {
+                source: "arg".to_string(),
+                destination: "ret".to_string(),
+            },
+            Instruction::Return,
+        ]
+    }
+
+    fn list_index(compiler: &mut Compiler) -> Vec<Instruction> {
+        let (index_register, mut instructions) = Self::argument(compiler, 1);
+
+        instructions.push(Instruction::Index {
+            source: "arg".to_string(),
+            index: index_register,
+            destination: "ret".to_string(),
+        });
+        instructions.push(Instruction::Return);
+
+        instructions
+    }
+
+    /// Return a copy of the list with the element added to its end, leaving the list
+    /// itself unchanged.
+    fn list_append(compiler: &mut Compiler) -> Vec<Instruction> {
+        let (element_register, mut instructions) = Self::argument(compiler, 1);
+
+        instructions.push(Instruction::Copy {
+            source: "arg".to_string(),
+            destination: "ret".to_string(),
+        });
+        instructions.push(Instruction::Append {
+            source: element_register,
+            destination: "ret".to_string(),
+        });
+        instructions.push(Instruction::Return);
+
+        instructions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::types::{CompilerError, CompilerResult};
+
+    use strontium::machine::instruction::Instruction;
+    use strontium::machine::register::RegisterValue;
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    /// Find the instruction which starts at the given byte address of linked bytecode.
+    fn instruction_at(bytecode: &[Instruction], address: usize) -> Option<&Instruction> {
+        let mut offset = 0;
+
+        for instruction in bytecode {
+            if offset == address {
+                return Some(instruction);
+            }
+
+            let bytes: Vec<u8> = instruction.clone().into();
+            offset += bytes.len();
+        }
+
+        None
+    }
+
+    /// Find the first instruction of each routine entered by a CALL in linked bytecode.
+    fn call_targets(bytecode: &[Instruction]) -> Vec<&Instruction> {
+        bytecode
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => instruction_at(bytecode, *address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A word whose `é` is written as an `e` followed by a combining accent.
+    const WORD: &str = "he\u{301}llo";
+
+    /// Check whether the bytecode converts the string argument into its characters.
+    fn converts_to_characters(bytecode: &[Instruction]) -> bool {
+        bytecode.iter().any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::Convert { source, type_name, .. }
+                    if source == "arg" && type_name == "List"
+            )
+        })
+    }
+
+    #[test]
+    fn string_methods_work_on_characters() {
+        for call in [
+            format!("length(\"{}\")", WORD),
+            format!("\"{}\"[1]", WORD),
+            format!("chars(\"{}\")", WORD),
+            format!("slice(\"{}\", 1)", WORD),
+            format!("slice(\"{}\", 1, 3)", WORD),
+        ] {
+            let bytecode = compile(&call).unwrap();
+
+            assert!(converts_to_characters(&bytecode), "{}", call);
+            assert!(bytecode.iter().any(|instruction| matches!(
+                instruction,
+                Instruction::Load {
+                    value: RegisterValue::String(value),
+                    ..
+                } if value == WORD
+            )));
+        }
+    }
+
+    #[test]
+    fn character_literals_keep_combining_marks() {
+        let bytecode = compile(&format!("'{}'", "e\u{301}")).unwrap();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Load {
+                value: RegisterValue::Char(value),
+                ..
+            } if value == "e\u{301}"
+        )));
+    }
+
+    #[test]
+    fn slice_ends_at_the_given_character() {
+        let bytecode = compile(&format!("slice(\"{}\", 1, 3)", WORD)).unwrap();
+
+        // The end is the third argument, rather than the length of the string.
+        let end_registers = bytecode
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Instruction::Load {
+                    value: RegisterValue::Int64(2),
+                    register,
+                }, Instruction::Index {
+                    source,
+                    index,
+                    destination,
+                }] if source == "args" && index == register => Some(destination.clone()),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Slice { end, .. } if end_registers.contains(end)
+        )));
+    }
+
+    #[test]
+    fn slice_bounds_need_to_be_integers() {
+        assert!(matches!(
+            compile(&format!("slice(\"{}\", 1, \"3\")", WORD)),
+            Err(CompilerError::TypeErrors(_))
+        ));
+    }
+
+    #[test]
+    fn list_length_is_linked_statically() {
+        let bytecode = compile("length([1, 2])").unwrap();
+
+        assert!(call_targets(&bytecode).iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Length { source, destination } if source == "arg" && destination == "ret"
+        )));
+    }
+
+    #[test]
+    fn list_index_reads_the_element_of_the_list() {
+        let bytecode = compile("[1, 2][0]").unwrap();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Index { source, destination, .. }
+                if source == "arg" && destination == "ret"
+        )));
+    }
+
+    #[test]
+    fn append_copies_the_list_before_appending() {
+        let bytecode = compile("append([1], 2)").unwrap();
+
+        assert!(bytecode.windows(2).any(|pair| matches!(
+            pair,
+            [Instruction::Copy {
+                source,
+                destination,
+            }, Instruction::Append {
+                destination: appended,
+                ..
+            }] if source == "arg" && destination == "ret" && appended == "ret"
+        )));
+    }
+}
@@ -1,6 +1,10 @@
-use crate::compiler::{Compilelet, Compiler};
+use crate::compiler::{
+    Builtin, Compilelet, Compiler, PendingCall, PendingTypeTest, METHOD_ARGUMENTS,
+};
+use crate::type_system::{Type, Typed};
 use crate::types::{
-    CompilerError, CompilerResult, Expression, ExpressionKind, Pattern, ValuePattern,
+    CompilerError, CompilerResult, CompilerWarning, Constant, Expression, ExpressionKind, Pattern,
+    TypeExpression, ValuePattern,
 };
 use strontium::machine::instruction::{
     CalculationMethod, ComparisonMethod, Instruction, Interrupt, InterruptKind,
@@ -18,10 +22,20 @@ impl Compilelet for CallCompilelet {
     ) -> CompilerResult<Vec<Instruction>> {
         let mut instructions = Vec::new();
 
-        if let ExpressionKind::Call(call) = expression.kind {
+        if let ExpressionKind::Call(call) = expression.kind.clone() {
             let method_name = call.name;
             let signature = call.signature.clone();
 
+            // A type test like `x is Int` compares the name of the value's type with the
+            // types it may have, which are only known once all types are defined.
+            if method_name == "is" {
+                return self.compile_type_test(compiler, signature, target_register);
+            }
+
+            // Methods on the built-in types, like `length` on a string, are only defined
+            // once they're called.
+            Builtin::define(compiler, &method_name, &expression)?;
+
             match method_name.as_str() {
                 "print" => {
                     let value_register = compiler.registers.allocate_register();
@@ -55,63 +69,42 @@ impl Compilelet for CallCompilelet {
                     });
                 }
 
-                // Built-in arithmetic operators
+                // The null-coalescing operator, which only evaluates its default if the
+                // value is `nil`.
+                "??" => {
+                    instructions.append(&mut self.compile_coalescing(
+                        compiler,
+                        signature,
+                        target_register,
+                    )?);
+                }
+
+                // Built-in arithmetic operators, which raise a runtime error if an operation
+                // on two integers overflows or divides by zero.
                 "+" | "-" | "*" | "/" | "^" | "%" => {
                     let method = match method_name.as_str() {
-                        "+" => CalculationMethod::ADD,
-                        "-" => CalculationMethod::SUBTRACT,
-                        "*" => CalculationMethod::MULTIPLY,
-                        "/" => CalculationMethod::DIVIDE,
-                        "^" => CalculationMethod::POWER,
-                        "%" => CalculationMethod::MODULO,
+                        "+" => CalculationMethod::CHECKED_ADD,
+                        "-" => CalculationMethod::CHECKED_SUBTRACT,
+                        "*" => CalculationMethod::CHECKED_MULTIPLY,
+                        "/" => CalculationMethod::CHECKED_DIVIDE,
+                        "^" => CalculationMethod::CHECKED_POWER,
+                        "%" => CalculationMethod::CHECKED_MODULO,
                         _ => unreachable!(),
                     };
 
-                    if let Some(Pattern::Pair(pair)) = signature {
-                        let left_expr =
-                            if let Pattern::Value(ValuePattern { expression }) = *pair.left {
-                                *expression
-                            } else {
-                                unreachable!()
-                            };
-
-                        let right_expr =
-                            if let Pattern::Value(ValuePattern { expression }) = *pair.right {
-                                *expression
-                            } else {
-                                unreachable!()
-                            };
-
-                        let left_register = compiler.registers.allocate_register();
-                        instructions.append(
-                            &mut compiler
-                                .compile_expression(left_expr, Some(left_register.clone()))?,
-                        );
-
-                        let right_register = compiler.registers.allocate_register();
-                        instructions.append(
-                            &mut compiler
-                                .compile_expression(right_expr, Some(right_register.clone()))?,
-                        );
-
-                        let destination_register = target_register
-                            .unwrap_or_else(|| compiler.registers.allocate_register());
-                        instructions.push(Instruction::Calculate {
+                    instructions.append(&mut self.compile_operator(
+                        compiler,
+                        &expression,
+                        signature,
+                        target_register,
+                        Some(&method_name),
+                        |operand1, operand2, destination| Instruction::Calculate {
                             method,
-                            operand1: left_register,
-                            operand2: right_register,
-                            destination: destination_register.clone(),
-                        });
-
-                        if compiler.context.recursion_depth == 1 {
-                            instructions.push(Instruction::Interrupt {
-                                interrupt: Interrupt {
-                                    address: destination_register,
-                                    kind: InterruptKind::Print,
-                                },
-                            });
-                        }
-                    }
+                            operand1,
+                            operand2,
+                            destination,
+                        },
+                    )?);
                 }
 
                 // Built-in comparison operators
@@ -126,85 +119,97 @@ impl Compilelet for CallCompilelet {
                         _ => unreachable!(),
                     };
 
-                    if let Some(Pattern::Pair(pair)) = signature {
-                        let left_expr =
-                            if let Pattern::Value(ValuePattern { expression }) = *pair.left {
-                                *expression
-                            } else {
-                                unreachable!()
-                            };
-
-                        let right_expr =
-                            if let Pattern::Value(ValuePattern { expression }) = *pair.right {
-                                *expression
-                            } else {
-                                unreachable!()
-                            };
-
-                        let left_register = compiler.registers.allocate_register();
-                        instructions.append(
-                            &mut compiler
-                                .compile_expression(left_expr, Some(left_register.clone()))?,
-                        );
-
-                        let right_register = compiler.registers.allocate_register();
-                        instructions.append(
-                            &mut compiler
-                                .compile_expression(right_expr, Some(right_register.clone()))?,
-                        );
-
-                        let destination_register = target_register
-                            .unwrap_or_else(|| compiler.registers.allocate_register());
-                        instructions.push(Instruction::Compare {
+                    instructions.append(&mut self.compile_operator(
+                        compiler,
+                        &expression,
+                        signature,
+                        target_register,
+                        None,
+                        |operand1, operand2, destination| Instruction::Compare {
                             method,
-                            operand1: left_register,
-                            operand2: right_register,
-                            destination: destination_register.clone(),
-                        });
+                            operand1,
+                            operand2,
+                            destination,
+                        },
+                    )?);
+                }
 
-                        if compiler.context.recursion_depth == 1 {
-                            instructions.push(Instruction::Interrupt {
-                                interrupt: Interrupt {
-                                    address: destination_register,
-                                    kind: InterruptKind::Print,
-                                },
-                            });
-                        }
+                // Conversions between numbers and strings
+                "as" => {
+                    let (value, type_id) = self.type_operands(signature)?;
+
+                    let source_register = compiler.registers.allocate_register();
+                    instructions.append(&mut compiler.compile_expression(
+                        value.into_expression(),
+                        Some(source_register.clone()),
+                    )?);
+
+                    let destination_register =
+                        target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+                    instructions.push(Instruction::Convert {
+                        source: source_register,
+                        type_name: type_id.canonical().name(),
+                        destination: destination_register.clone(),
+                    });
+
+                    if compiler.context.recursion_depth == 1 {
+                        instructions.push(Instruction::Interrupt {
+                            interrupt: Interrupt {
+                                address: destination_register,
+                                kind: InterruptKind::Print,
+                            },
+                        });
                     }
                 }
 
+                // Delegate to the next most specific variant of the current method
+                "next_method" => {
+                    instructions.append(&mut self.compile_next_method(compiler, signature)?);
+
+                    let destination_register =
+                        target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+
+                    instructions.push(Instruction::Copy {
+                        source: "ret".to_string(),
+                        destination: destination_register,
+                    });
+                }
+
                 // Any other method calls (user-defined multimethods)
                 _ => {
                     // Verify the multimethod exists
-                    if !compiler.multimethods.contains_key(&method_name) {
-                        return Err(CompilerError::MethodNotFound(method_name.clone()));
-                    }
+                    let multimethod = match compiler.multimethods.get(&method_name) {
+                        Some(multimethod) => multimethod,
+                        None => return Err(CompilerError::MethodNotFound(method_name.clone())),
+                    };
 
-                    // Compile the argument expression into the 'arg' register
-                    // The argument is what will be matched against patterns at runtime
-                    if let Some(call_sig) = signature {
-                        // Extract the value from the pattern and compile it
-                        match call_sig {
-                            Pattern::Value(ValuePattern { expression }) => {
-                                instructions.append(
-                                    &mut compiler
-                                        .compile_expression(*expression, Some("arg".to_string()))?,
-                                );
-                            }
-                            _ => {
-                                // For other patterns, try to compile them directly
-                                // This handles things like tuple arguments
-                                return Err(CompilerError::Generic(
-                                    "Only value patterns supported in calls currently".to_string(),
-                                ));
-                            }
-                        }
+                    // Warn about calls which are guaranteed to fail at runtime
+                    if !multimethod.may_accept(&compiler.parser, &compiler.types, &signature) {
+                        compiler.warnings.push(CompilerWarning::NonExhaustiveCall {
+                            method_name: method_name.clone(),
+                            arguments: signature.clone(),
+                            start_pos: expression.start_pos,
+                            end_pos: expression.end_pos,
+                        });
                     }
 
-                    // Generate DISPATCH instruction - runtime will match arg against patterns
-                    instructions.push(Instruction::Dispatch {
+                    // Evaluate the arguments into the 'arg' and 'args' registers.
+                    // The first argument is what will be matched against patterns at runtime
+                    let arguments = signature
+                        .clone()
+                        .map(|pattern| pattern.flatten_pairs())
+                        .unwrap_or_default();
+
+                    instructions.append(&mut self.compile_arguments(compiler, arguments)?);
+
+                    // Generate a CALL which the linker either resolves to the variant selected
+                    // for these arguments, or turns into a DISPATCH if it can only be known at runtime
+                    let address = compiler.add_pending_call(PendingCall {
                         method_name: method_name.clone(),
+                        arguments: signature,
+                        next_after: None,
                     });
+                    instructions.push(Instruction::Call { address });
 
                     // Copy the return value to the target register
                     let destination_register =
@@ -233,6 +238,482 @@ impl Compilelet for CallCompilelet {
 }
 
 impl CallCompilelet {
+    /// Compile `value ?? default`, which evaluates to the value unless its type is `Nil`,
+    /// in which case the default is evaluated instead.
+    fn compile_coalescing(
+        &self,
+        compiler: &mut Compiler,
+        signature: Option<Pattern>,
+        target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let (value, default) = match signature {
+            Some(Pattern::Pair(pair)) => {
+                (pair.left.into_expression(), pair.right.into_expression())
+            }
+            _ => {
+                return Err(CompilerError::Generic(
+                    "`??` expects a value and a default".to_string(),
+                ))
+            }
+        };
+
+        let destination_register =
+            target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+        let type_register = compiler.registers.allocate_register();
+        let nil_register = compiler.registers.allocate_register();
+        let is_value_register = compiler.registers.allocate_register();
+        let end = compiler.allocate_label();
+
+        let mut instructions =
+            compiler.compile_expression(value, Some(destination_register.clone()))?;
+
+        instructions.extend([
+            Instruction::TypeOf {
+                source: destination_register.clone(),
+                destination: type_register.clone(),
+            },
+            Instruction::Load {
+                value: RegisterValue::String("Nil".to_string()),
+                register: nil_register.clone(),
+            },
+            Instruction::Compare {
+                method: ComparisonMethod::NEQ,
+                operand1: type_register,
+                operand2: nil_register,
+                destination: is_value_register.clone(),
+            },
+            Instruction::JumpCToLabel {
+                id: end,
+                conditional_address: is_value_register,
+            },
+        ]);
+        instructions
+            .append(&mut compiler.compile_expression(default, Some(destination_register.clone()))?);
+        instructions.push(Instruction::LabelTarget { id: end });
+
+        if compiler.context.recursion_depth == 1 {
+            instructions.push(Instruction::Interrupt {
+                interrupt: Interrupt {
+                    address: destination_register,
+                    kind: InterruptKind::Print,
+                },
+            });
+        }
+
+        Ok(instructions)
+    }
+
+    /// Compile a built-in operator applied to two operands, which is folded into its result
+    /// if both of them are constant.
+    ///
+    /// Otherwise, both operands are evaluated before the instruction for the operator is
+    /// applied to them. The operands of an arithmetic operator are promoted first, so an
+    /// integer is converted to a float if the other operand is a float.
+    fn compile_operator(
+        &self,
+        compiler: &mut Compiler,
+        expression: &Expression,
+        signature: Option<Pattern>,
+        target_register: Option<String>,
+        arithmetic_operator: Option<&str>,
+        operation: impl FnOnce(String, String, String) -> Instruction,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let mut instructions = vec![];
+        let destination_register =
+            target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+
+        match Constant::fold(&compiler.parser, expression) {
+            Ok(Some(value)) => instructions.push(Instruction::Load {
+                value: Compiler::constant_value(&value)?,
+                register: destination_register.clone(),
+            }),
+
+            Ok(None) => {
+                let (left_expr, right_expr) = match signature.clone() {
+                    Some(Pattern::Pair(pair)) => {
+                        (pair.left.into_expression(), pair.right.into_expression())
+                    }
+                    _ => {
+                        return Err(CompilerError::Generic(
+                            "built-in operators expect two operands".to_string(),
+                        ))
+                    }
+                };
+                let operand_types = (left_expr.get_type(), right_expr.get_type());
+
+                let left_register = compiler.registers.allocate_register();
+                instructions.append(
+                    &mut compiler.compile_expression(left_expr, Some(left_register.clone()))?,
+                );
+
+                let right_register = compiler.registers.allocate_register();
+                instructions.append(
+                    &mut compiler.compile_expression(right_expr, Some(right_register.clone()))?,
+                );
+
+                if let Some(operator) = arithmetic_operator {
+                    instructions.append(&mut self.promote_operands(
+                        compiler,
+                        operator,
+                        operand_types,
+                        (&left_register, &right_register),
+                    ));
+                }
+
+                instructions.push(operation(
+                    left_register,
+                    right_register,
+                    destination_register.clone(),
+                ));
+            }
+
+            Err(error) => {
+                return Err(CompilerError::InvalidArithmetic {
+                    error,
+                    start_pos: expression.start_pos,
+                    end_pos: expression.end_pos,
+                })
+            }
+        }
+
+        if compiler.context.recursion_depth == 1 {
+            instructions.push(Instruction::Interrupt {
+                interrupt: Interrupt {
+                    address: destination_register,
+                    kind: InterruptKind::Print,
+                },
+            });
+        }
+
+        Ok(instructions)
+    }
+
+    /// Promote an operand to the type of the other one if it's further up the numeric tower,
+    /// like an integer combined with a float.
+    ///
+    /// If the type of an operand is only known at runtime, the types of both operands are
+    /// compared with each pair of types which are promoted. Operands of different types
+    /// which can't be promoted, like a sized number and an `Int`, raise a runtime error.
+    fn promote_operands(
+        &self,
+        compiler: &mut Compiler,
+        operator: &str,
+        (left_type, right_type): (Type, Type),
+        (left_register, right_register): (&String, &String),
+    ) -> Vec<Instruction> {
+        let mut instructions = vec![];
+
+        if left_type.is_known() && right_type.is_known() {
+            let target = Type::promote(&left_type, &right_type);
+
+            for (operand_type, register) in
+                [(left_type, left_register), (right_type, right_register)]
+            {
+                if let Some(type_name) = target.name().filter(|_| operand_type != target) {
+                    instructions.push(Instruction::Convert {
+                        source: register.clone(),
+                        type_name,
+                        destination: register.clone(),
+                    });
+                }
+            }
+
+            return instructions;
+        }
+
+        let operands = [(left_type, left_register), (right_type, right_register)];
+        let mut type_registers = vec![];
+
+        for (operand_type, register) in &operands {
+            let type_register = compiler.registers.allocate_register();
+
+            instructions.push(
+                match operand_type.name().filter(|_| operand_type.is_known()) {
+                    Some(type_name) => Instruction::Load {
+                        value: RegisterValue::String(type_name),
+                        register: type_register.clone(),
+                    },
+                    None => Instruction::TypeOf {
+                        source: register.to_string(),
+                        destination: type_register.clone(),
+                    },
+                },
+            );
+            type_registers.push(type_register);
+        }
+
+        let promoted = compiler.allocate_label();
+        let same_type_register = compiler.registers.allocate_register();
+
+        instructions.push(Instruction::Compare {
+            method: ComparisonMethod::EQ,
+            operand1: type_registers[0].clone(),
+            operand2: type_registers[1].clone(),
+            destination: same_type_register.clone(),
+        });
+        instructions.push(Instruction::JumpCToLabel {
+            id: promoted,
+            conditional_address: same_type_register,
+        });
+
+        let promotions = [
+            (Type::Int, Type::Float),
+            (Type::Int, Type::BigInt),
+            (Type::BigInt, Type::Float),
+        ];
+
+        for (lower, higher) in promotions {
+            for (converted, other) in [(0, 1), (1, 0)] {
+                let expected = [(converted, &lower), (other, &higher)];
+
+                // Skip the pairs which an operand of a known type never matches.
+                if expected.iter().any(|(index, expected_type)| {
+                    operands[*index].0.is_known() && operands[*index].0 != **expected_type
+                }) {
+                    continue;
+                }
+
+                let next = compiler.allocate_label();
+
+                for (index, expected_type) in expected {
+                    if operands[index].0.is_known() {
+                        continue;
+                    }
+
+                    let expected_register = compiler.registers.allocate_register();
+                    let differs_register = compiler.registers.allocate_register();
+
+                    instructions.extend([
+                        Instruction::Load {
+                            value: RegisterValue::String(expected_type.name().unwrap_or_default()),
+                            register: expected_register.clone(),
+                        },
+                        Instruction::Compare {
+                            method: ComparisonMethod::NEQ,
+                            operand1: type_registers[index].clone(),
+                            operand2: expected_register,
+                            destination: differs_register.clone(),
+                        },
+                        Instruction::JumpCToLabel {
+                            id: next,
+                            conditional_address: differs_register,
+                        },
+                    ]);
+                }
+
+                instructions.extend([
+                    Instruction::Convert {
+                        source: operands[converted].1.clone(),
+                        type_name: higher.name().unwrap_or_default(),
+                        destination: operands[converted].1.clone(),
+                    },
+                    Instruction::JumpToLabel { id: promoted },
+                    Instruction::LabelTarget { id: next },
+                ]);
+            }
+        }
+
+        let message_register = compiler.registers.allocate_register();
+
+        instructions.extend([
+            Instruction::Load {
+                value: RegisterValue::String(format!(
+                    "the operator `{}` can't combine numbers of different types, one of them needs to be converted with `as`",
+                    operator
+                )),
+                register: message_register.clone(),
+            },
+            Instruction::Interrupt {
+                interrupt: Interrupt {
+                    address: message_register,
+                    kind: InterruptKind::Error,
+                },
+            },
+            Instruction::Halt,
+            Instruction::LabelTarget { id: promoted },
+        ]);
+
+        instructions
+    }
+
+    /// Split the arguments of `is` or `as` into the value and the type on the right.
+    fn type_operands(
+        &self,
+        signature: Option<Pattern>,
+    ) -> CompilerResult<(Pattern, TypeExpression)> {
+        if let Some(Pattern::Pair(pair)) = signature {
+            if let Pattern::Value(ValuePattern { expression }) = pair.right.as_ref() {
+                if let ExpressionKind::Type(type_id) = &expression.kind {
+                    return Ok((*pair.left, type_id.clone()));
+                }
+            }
+        }
+
+        Err(CompilerError::Generic(
+            "expected a type on the right side of `is` or `as`".to_string(),
+        ))
+    }
+
+    /// Compile a type test like `x is Int`, which is `true` for values of the type. An
+    /// optional type also accepts `nil`.
+    ///
+    /// Type arguments are erased at runtime, so `is List[Int]` only tests for a `List`.
+    fn compile_type_test(
+        &self,
+        compiler: &mut Compiler,
+        signature: Option<Pattern>,
+        target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let (value, type_id) = self.type_operands(signature)?;
+
+        let value_register = compiler.registers.allocate_register();
+        let type_register = compiler.registers.allocate_register();
+        let destination_register =
+            target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+
+        let mut instructions =
+            compiler.compile_expression(value.into_expression(), Some(value_register.clone()))?;
+
+        instructions.push(Instruction::TypeOf {
+            source: value_register,
+            destination: type_register.clone(),
+        });
+
+        let type_names = match type_id.optional_value() {
+            Some(value_type) => vec![value_type.canonical().name(), String::from("Nil")],
+            None => vec![type_id.canonical().name()],
+        };
+        let label = compiler.allocate_label();
+
+        compiler.pending_type_tests.insert(
+            label,
+            PendingTypeTest {
+                type_register,
+                result_register: destination_register.clone(),
+                type_names,
+            },
+        );
+        instructions.push(Instruction::LabelTarget { id: label });
+
+        if compiler.context.recursion_depth == 1 {
+            instructions.push(Instruction::Interrupt {
+                interrupt: Interrupt {
+                    address: destination_register,
+                    kind: InterruptKind::Print,
+                },
+            });
+        }
+
+        Ok(instructions)
+    }
+
+    /// Evaluate the arguments of a call to a user-defined multimethod.
+    ///
+    /// Each argument is compiled into a register of its own first, since evaluating it may
+    /// involve another call which overwrites the argument registers. Afterwards, the values
+    /// are collected into the `args` array and the first one is copied into `arg`.
+    fn compile_arguments(
+        &self,
+        compiler: &mut Compiler,
+        arguments: Vec<Pattern>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let mut instructions = vec![];
+        let mut argument_registers = vec![];
+
+        for argument in arguments {
+            let register = compiler.registers.allocate_register();
+
+            instructions.append(
+                &mut compiler
+                    .compile_expression(argument.into_expression(), Some(register.clone()))?,
+            );
+            argument_registers.push(register);
+        }
+
+        instructions.push(Instruction::Load {
+            value: RegisterValue::Array(vec![]),
+            register: "args".to_string(),
+        });
+
+        for register in &argument_registers {
+            instructions.push(Instruction::Append {
+                source: register.clone(),
+                destination: "args".to_string(),
+            });
+        }
+
+        if let Some(first) = argument_registers.first() {
+            instructions.push(Instruction::Copy {
+                source: first.clone(),
+                destination: "arg".to_string(),
+            });
+        }
+
+        Ok(instructions)
+    }
+
+    /// Call the next most specific variant of the method whose body is being compiled.
+    ///
+    /// Without any arguments, the current method's own arguments are passed on, which it
+    /// stores in a local variable before binding its parameters. The call is resolved by
+    /// the linker, either to a single variant or to a dispatcher for the remaining ones.
+    fn compile_next_method(
+        &self,
+        compiler: &mut Compiler,
+        signature: Option<Pattern>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let method = compiler.context.current_method.clone().ok_or_else(|| {
+            CompilerError::Generic("next_method can only be called in a method body".to_string())
+        })?;
+
+        let mut instructions = vec![];
+
+        let arguments = match signature {
+            Some(arguments) => {
+                instructions.append(
+                    &mut self.compile_arguments(compiler, arguments.clone().flatten_pairs())?,
+                );
+
+                Some(arguments)
+            }
+
+            None => {
+                compiler.context.forwards_arguments = true;
+
+                instructions.push(Instruction::LoadLocal {
+                    name: METHOD_ARGUMENTS.to_string(),
+                    register: "args".to_string(),
+                });
+
+                if method.signature.is_some() {
+                    let index_register = compiler.registers.allocate_register();
+
+                    instructions.push(Instruction::Load {
+                        value: RegisterValue::Int64(0),
+                        register: index_register.clone(),
+                    });
+                    instructions.push(Instruction::Index {
+                        source: "args".to_string(),
+                        index: index_register,
+                        destination: "arg".to_string(),
+                    });
+                }
+
+                method.signature.clone()
+            }
+        };
+
+        let address = compiler.add_pending_call(PendingCall {
+            method_name: method.name.clone(),
+            arguments,
+            next_after: Some(Compiler::generate_method_id(&method)),
+        });
+
+        instructions.push(Instruction::Call { address });
+
+        Ok(instructions)
+    }
+
     fn compile_print_argument(
         &self,
         compiler: &mut Compiler,
@@ -257,3 +738,217 @@ impl CallCompilelet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::dispatch::DISPATCH_ARGUMENTS;
+    use crate::compiler::{Compiler, METHOD_ARGUMENTS};
+    use crate::types::{CompilerError, CompilerResult};
+
+    use strontium::machine::instruction::Instruction;
+    use strontium::machine::register::RegisterValue;
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    /// Find the instruction which starts at the given byte address of linked bytecode.
+    fn instruction_at(bytecode: &[Instruction], address: usize) -> Option<&Instruction> {
+        let mut offset = 0;
+
+        for instruction in bytecode {
+            if offset == address {
+                return Some(instruction);
+            }
+
+            let bytes: Vec<u8> = instruction.clone().into();
+            offset += bytes.len();
+        }
+
+        None
+    }
+
+    /// Find the first instruction of each routine entered by a CALL in linked bytecode.
+    fn call_targets(bytecode: &[Instruction]) -> Vec<&Instruction> {
+        bytecode
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => instruction_at(bytecode, *address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the names a type test in the bytecode compares the value's type with.
+    fn tested_types(bytecode: &[Instruction]) -> Vec<&str> {
+        bytecode
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Instruction::Load {
+                    value: RegisterValue::String(type_name),
+                    ..
+                }, Instruction::Compare { .. }] => Some(type_name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn coalescing_only_evaluates_the_default_for_nil() {
+        for source in ["nil ?? 1", "def f(x Int?) x ?? 1 f(nil)"] {
+            let bytecode = compile(source).unwrap();
+
+            let jump = bytecode
+                .iter()
+                .find_map(|instruction| match instruction {
+                    Instruction::JumpC { destination, .. } => Some(*destination as usize),
+                    _ => None,
+                })
+                .unwrap();
+            let default = bytecode
+                .iter()
+                .position(|instruction| {
+                    matches!(
+                        instruction,
+                        Instruction::Load {
+                            value: RegisterValue::Int64(1),
+                            ..
+                        }
+                    )
+                })
+                .unwrap();
+
+            assert_eq!(
+                instruction_at(&bytecode, jump),
+                bytecode.get(default + 1),
+                "{}",
+                source
+            );
+        }
+
+        assert!(!compile("nil ?? 1")
+            .unwrap()
+            .iter()
+            .any(|instruction| matches!(
+                instruction,
+                Instruction::Call { .. } | Instruction::Dispatch { .. }
+            )));
+    }
+
+    #[test]
+    fn type_tests_compare_the_type_of_the_value() {
+        let bytecode = compile("def f(x) x is Int? f(nil)").unwrap();
+
+        assert!(bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::TypeOf { .. })));
+        assert_eq!(tested_types(&bytecode), ["Int", "Nil"]);
+        assert!(!bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Dispatch { .. })));
+    }
+
+    #[test]
+    fn type_tests_accept_the_classes_defined_later() {
+        let bytecode = compile(
+            "def is_shape(x) x is Shape \
+             enum Shape case Circle(r Float) case Square(s Float) end \
+             is_shape(1)",
+        )
+        .unwrap();
+
+        // The method body is linked before the dispatchers of the constructors.
+        assert!(tested_types(&bytecode).starts_with(&["Circle", "Square"]));
+    }
+
+    #[test]
+    fn conversions_are_compiled_to_convert() {
+        let bytecode = compile("1 as Float").unwrap();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Convert { type_name, .. } if type_name == "Float"
+        )));
+    }
+
+    #[test]
+    fn promotion_compares_the_types_of_the_operands() {
+        let bytecode = compile("def f(a, b) a + b f(1, 2.5)").unwrap();
+
+        assert!(bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::TypeOf { .. })));
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Convert { type_name, .. } if type_name == "Float"
+        )));
+        assert!(!bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Dispatch { .. })));
+    }
+
+    #[test]
+    fn comparisons_are_not_promoted() {
+        let bytecode = compile("def f(a, b) a < b f(1, 2.5)").unwrap();
+
+        assert!(!bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::TypeOf { .. } | Instruction::Convert { .. }
+        )));
+    }
+
+    #[test]
+    fn mixed_sized_operands_raise_an_error() {
+        let bytecode = compile("def f(a, b) a + b f(1u8, 2u8)").unwrap();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Load {
+                value: RegisterValue::String(message),
+                ..
+            } if message.starts_with("the operator `+` can't combine numbers of different types")
+        )));
+    }
+
+    #[test]
+    fn next_method_calls_the_less_specific_variant() {
+        let bytecode = compile("def f(n Int) next_method() def f(x) 0 f(1)").unwrap();
+        let targets = call_targets(&bytecode);
+
+        // The arguments are saved before they're bound, so they can be passed on.
+        assert!(targets.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, register }
+                if name == METHOD_ARGUMENTS && register == "args"
+        )));
+        assert!(targets.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, .. } if name == "x"
+        )));
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::LoadLocal { name, register }
+                if name == METHOD_ARGUMENTS && register == "args"
+        )));
+    }
+
+    #[test]
+    fn next_method_is_dispatched_if_a_guard_decides() {
+        let bytecode =
+            compile("def f(n Int) next_method() def f(x) where x == 1 1 def f(x) 0 f(1)").unwrap();
+
+        // The remaining variants are selected by a dispatcher, which saves the arguments first.
+        assert!(call_targets(&bytecode).iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, register } if name == DISPATCH_ARGUMENTS && register == "args"
+        )));
+    }
+
+    #[test]
+    fn next_method_of_the_least_specific_variant_is_an_error() {
+        assert!(matches!(
+            compile("def f(x) next_method() f(1)"),
+            Err(CompilerError::NoNextMethod { method_name, .. }) if method_name == "f"
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+use super::Compilelet;
+use crate::compiler::{Compiler, TypeDefinition};
+use crate::types::{
+    CompilerResult, Expression, ExpressionKind, Method, Pattern, TypeExpression, VariablePattern,
+};
+use strontium::machine::instruction::Instruction;
+use strontium::machine::register::RegisterValue;
+
+/// Compilelet for class definitions like `def class Point(x Float, y Float)`.
+///
+/// The class is registered in the type system, and a constructor and a getter for each
+/// field are defined as native method variants. The constructor turns its arguments into
+/// an instance of the class, whose fields are then accessed by their position.
+pub struct ClassCompilelet;
+
+impl Compilelet for ClassCompilelet {
+    fn compile(
+        &self,
+        compiler: &mut Compiler,
+        expression: Expression,
+        _target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        if let ExpressionKind::Class(class) = expression.kind.clone() {
+            ClassCompilelet::define_type(compiler, TypeDefinition::from(class), &expression)?;
+        }
+
+        Ok(vec![])
+    }
+}
+
+impl ClassCompilelet {
+    /// Register a type with fields, and define its constructor and field getters as
+    /// native methods located at the given definition.
+    pub fn define_type(
+        compiler: &mut Compiler,
+        definition: TypeDefinition,
+        expression: &Expression,
+    ) -> CompilerResult<()> {
+        compiler.types.define(definition.clone())?;
+
+        // The constructor `Point(x Float, y Float)` takes the fields as its arguments.
+        let constructor = Method {
+            name: definition.name.clone(),
+            qualifier: None,
+            type_parameters: vec![],
+            signature: definition.constructor_signature(),
+            return_type: Some(TypeExpression::named(&definition.name)),
+            guard: None,
+            body: Box::new(expression.clone()),
+        };
+
+        compiler.define_native_method(
+            constructor,
+            vec![
+                Instruction::Construct {
+                    type_name: definition.name.clone(),
+                    source: "args".to_string(),
+                    destination: "ret".to_string(),
+                },
+                Instruction::Return,
+            ],
+            expression.start_pos,
+            expression.end_pos,
+        )?;
+
+        // Each field gets a getter like `x(this Point)`, which can be called as `p.x`.
+        for (index, field) in definition.fields().iter().enumerate() {
+            let getter = Method {
+                name: field.name.clone().unwrap_or_default(),
+                qualifier: None,
+                type_parameters: vec![],
+                signature: Some(Pattern::Variable(VariablePattern {
+                    name: Some("this".to_string()),
+                    type_id: Some(TypeExpression::named(&definition.name)),
+                })),
+                return_type: field.type_id.clone(),
+                guard: None,
+                body: Box::new(expression.clone()),
+            };
+
+            let index_register = compiler.registers.allocate_register();
+
+            compiler.define_native_method(
+                getter,
+                vec![
+                    Instruction::Load {
+                        value: RegisterValue::Int64(index as i64),
+                        register: index_register.clone(),
+                    },
+                    Instruction::Index {
+                        source: "arg".to_string(),
+                        index: index_register,
+                        destination: "ret".to_string(),
+                    },
+                    Instruction::Return,
+                ],
+                expression.start_pos,
+                expression.end_pos,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+
+    use strontium::machine::instruction::Instruction;
+    use strontium::machine::register::RegisterValue;
+
+    /// Find the instruction which starts at the given byte address of linked bytecode.
+    fn instruction_at(bytecode: &[Instruction], address: usize) -> Option<&Instruction> {
+        let mut offset = 0;
+
+        for instruction in bytecode {
+            if offset == address {
+                return Some(instruction);
+            }
+
+            let bytes: Vec<u8> = instruction.clone().into();
+            offset += bytes.len();
+        }
+
+        None
+    }
+
+    /// Find the first instruction of each routine entered by a CALL in linked bytecode.
+    fn call_targets(bytecode: &[Instruction]) -> Vec<&Instruction> {
+        bytecode
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => instruction_at(bytecode, *address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn constructor_builds_an_instance() {
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile("def class Point(x Int, y Int) Point(1, 2)".to_string())
+            .unwrap();
+
+        assert!(compiler.types.get("Point").is_some());
+        assert!(call_targets(&bytecode).iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Construct { type_name, source, .. }
+                if type_name == "Point" && source == "args"
+        )));
+    }
+
+    #[test]
+    fn getters_index_the_fields_in_order() {
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile("def class Point(x Int, y Int) y(Point(1, 2))".to_string())
+            .unwrap();
+
+        let mut names = compiler
+            .method_registrations
+            .iter()
+            .map(|registration| registration.method_name.as_str())
+            .collect::<Vec<&str>>();
+        names.sort();
+
+        assert_eq!(names, ["Point", "x", "y"]);
+
+        // The getter of the second field loads its index before reading it from the instance.
+        assert!(bytecode.windows(2).any(|pair| matches!(
+            pair,
+            [
+                Instruction::Load {
+                    value: RegisterValue::Int64(1),
+                    ..
+                },
+                Instruction::Index { source, destination, .. },
+            ] if source == "arg" && destination == "ret"
+        )));
+    }
+}
@@ -0,0 +1,34 @@
+use super::{ClassCompilelet, Compilelet};
+use crate::compiler::{Compiler, TypeDefinition};
+use crate::types::{CompilerResult, Expression, ExpressionKind};
+use strontium::machine::instruction::Instruction;
+
+/// Compilelet for enum definitions like `enum Shape case Circle(radius Float) end`.
+///
+/// The enum itself is registered as a type without a constructor, and each variant is
+/// defined like a class whose first supertype is the enum it belongs to.
+pub struct EnumCompilelet;
+
+impl Compilelet for EnumCompilelet {
+    fn compile(
+        &self,
+        compiler: &mut Compiler,
+        expression: Expression,
+        _target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        if let ExpressionKind::Enum(definition) = expression.kind.clone() {
+            compiler
+                .types
+                .define(TypeDefinition::from(definition.clone()))?;
+
+            for variant in definition.variants {
+                let mut variant = TypeDefinition::from(variant);
+                variant.supertypes.insert(0, definition.name.clone());
+
+                ClassCompilelet::define_type(compiler, variant, &expression)?;
+            }
+        }
+
+        Ok(vec![])
+    }
+}
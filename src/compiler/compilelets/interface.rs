@@ -0,0 +1,25 @@
+use super::Compilelet;
+use crate::compiler::{Compiler, TypeDefinition};
+use crate::types::{CompilerResult, Expression, ExpressionKind};
+use strontium::machine::instruction::Instruction;
+
+/// Compilelet for interface definitions like `interface Shape def area(this) end`.
+///
+/// The interface is only registered in the type system here, since its implementations
+/// can only be checked once all methods have been defined.
+pub struct InterfaceCompilelet;
+
+impl Compilelet for InterfaceCompilelet {
+    fn compile(
+        &self,
+        compiler: &mut Compiler,
+        expression: Expression,
+        _target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        if let ExpressionKind::Interface(interface) = expression.kind {
+            compiler.types.define(TypeDefinition::from(interface))?;
+        }
+
+        Ok(vec![])
+    }
+}
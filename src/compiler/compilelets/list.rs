@@ -0,0 +1,104 @@
+use crate::compiler::{Compilelet, Compiler};
+use crate::types::{CompilerError, CompilerResult, Expression, ExpressionKind};
+use strontium::machine::instruction::Instruction;
+use strontium::machine::register::RegisterValue;
+
+/// Compilelet for list literals like `[1, 2, 3]`.
+///
+/// The elements are stored as a chain of pairs, which is flattened and evaluated from left
+/// to right before each element is appended to an empty list.
+pub struct ListCompilelet;
+
+impl Compilelet for ListCompilelet {
+    fn compile(
+        &self,
+        compiler: &mut Compiler,
+        expression: Expression,
+        target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let elements = match expression.kind {
+            ExpressionKind::List(Some(child)) => child
+                .pattern_or_value_pattern()
+                .map_err(CompilerError::ParserError)?
+                .flatten_pairs(),
+            _ => vec![],
+        };
+
+        let mut instructions = vec![];
+        let mut element_registers = vec![];
+
+        for element in elements {
+            let register = compiler.registers.allocate_register();
+
+            instructions.append(
+                &mut compiler
+                    .compile_expression(element.into_expression(), Some(register.clone()))?,
+            );
+            element_registers.push(register);
+        }
+
+        let destination_register =
+            target_register.unwrap_or_else(|| compiler.registers.allocate_register());
+
+        instructions.push(Instruction::Load {
+            value: RegisterValue::Array(vec![]),
+            register: destination_register.clone(),
+        });
+
+        for register in element_registers {
+            instructions.push(Instruction::Append {
+                source: register,
+                destination: destination_register.clone(),
+            });
+        }
+
+        Ok(instructions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::types::CompilerResult;
+
+    use strontium::machine::instruction::Instruction;
+    use strontium::machine::register::RegisterValue;
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    #[test]
+    fn elements_are_appended_to_an_empty_list_in_order() {
+        let bytecode = compile("[1, 2]").unwrap();
+
+        let list = bytecode
+            .iter()
+            .position(|instruction| {
+                matches!(
+                    instruction,
+                    Instruction::Load {
+                        value: RegisterValue::Array(elements),
+                        ..
+                    } if elements.is_empty()
+                )
+            })
+            .unwrap();
+
+        let appended = bytecode[list + 1..]
+            .iter()
+            .take_while(|instruction| matches!(instruction, Instruction::Append { .. }))
+            .count();
+
+        assert_eq!(appended, 2);
+    }
+
+    #[test]
+    fn empty_list_has_no_elements() {
+        let bytecode = compile("[]").unwrap();
+
+        assert!(!bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Append { .. })));
+    }
+}
@@ -4,11 +4,13 @@ use strontium::machine::instruction::Instruction;
 use strontium::machine::register::RegisterValue::*;
 
 use crate::compiler::Compiler;
-use crate::types::{CompilerResult, Expression, ExpressionKind, Literal};
+use crate::helpers::{parse_int_literal, parse_string_literal};
+use crate::types::{CompilerError, CompilerResult, Constant, Expression, ExpressionKind, Literal};
 
 use super::Compilelet;
 
-/// A compilelet for literal expressions like integers, floats, strings and booleans.
+/// A compilelet for literal expressions like integers, floats, strings, booleans and `nil`,
+/// as well as numbers of a fixed size like `255u8` and integers which don't fit into an `Int`.
 ///
 /// This will find the literal value in the source string using the `start_pos` and `end_pos`
 /// properties of the `Expression`, pull out the literal as a string from the source code,
@@ -24,15 +26,21 @@ impl Compilelet for LiteralCompilelet {
     ) -> CompilerResult<Vec<Instruction>> {
         let mut instructions = vec![];
 
-        if let ExpressionKind::Literal(literal) = expression.kind {
+        if let ExpressionKind::Literal(literal) = expression.kind.clone() {
             let literal_string = compiler
                 .lexer
                 .get_literal_string(expression.start_pos, expression.end_pos);
             let value = match literal {
-                Literal::Int => Int64(literal_string.unwrap().parse::<i64>().unwrap()),
+                Literal::Int => Int64(parse_int_literal(&literal_string.unwrap()).unwrap()),
                 Literal::Float => Float64(literal_string.unwrap().parse::<f64>().unwrap()),
                 Literal::String => String(parse_string_literal(&literal_string.unwrap())),
                 Literal::Boolean => Boolean(literal_string.unwrap().parse::<bool>().unwrap()),
+                Literal::Nil => Empty,
+                // These have already been validated by the parser and the type checker.
+                Literal::Char | Literal::BigInt | Literal::Sized(_) => Compiler::constant_value(
+                    &Constant::evaluate(&compiler.parser, &expression)
+                        .map_err(CompilerError::ParserError)?,
+                )?,
             };
 
             // Define a LOAD instruction to find an empty register and load the value into it.
@@ -46,13 +54,6 @@ impl Compilelet for LiteralCompilelet {
     }
 }
 
-fn parse_string_literal(raw: &str) -> String {
-    raw.strip_prefix('"')
-        .and_then(|value| value.strip_suffix('"'))
-        .unwrap_or(raw)
-        .to_string()
-}
-
 #[cfg(test)]
 mod tests {
     use super::parse_string_literal;
@@ -66,4 +67,12 @@ mod tests {
     fn keeps_unquoted_strings_intact() {
         assert_eq!(parse_string_literal("hello"), "hello");
     }
+
+    #[test]
+    fn resolves_escape_sequences() {
+        assert_eq!(
+            parse_string_literal("\"say \\\"hi\\\"\\n\""),
+            "say \"hi\"\n"
+        );
+    }
 }
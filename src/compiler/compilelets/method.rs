@@ -1,5 +1,5 @@
 use super::Compilelet;
-use crate::compiler::{CompiledMethod, Compiler, Multimethod};
+use crate::compiler::{CompiledMethod, Compiler, METHOD_ARGUMENTS};
 use crate::types::{CompilerResult, Expression, ExpressionKind};
 use strontium::machine::instruction::Instruction;
 
@@ -13,75 +13,100 @@ impl Compilelet for MethodCompilelet {
         expression: Expression,
         _target_register: Option<String>,
     ) -> CompilerResult<Vec<Instruction>> {
-        match expression.kind.clone() {
-            ExpressionKind::Method(method) => {
-                let method_id = Compiler::generate_method_id(&method.name, &method.signature);
-
-                // Register with multimethod dispatch table
-                if let Some(multimethod) = compiler.multimethods.get_mut(&method.name) {
-                    multimethod.add_method(&compiler.parser, method.clone())?;
-                } else {
-                    let mut m = Multimethod::new(&method.name);
-                    m.add_method(&compiler.parser, method.clone())?;
-                    compiler.multimethods.insert(method.name.clone(), m);
-                }
+        if let ExpressionKind::Method(method) = expression.kind.clone() {
+            let method_id = Compiler::generate_method_id(&method);
 
-                // Extract parameter names from the signature
-                let parameter_names = if let Some(ref sig) = method.signature {
-                    Compiler::extract_variable_names(sig)
-                } else {
-                    vec![]
-                };
-
-                // Convert the signature to a dispatch pattern for runtime matching
-                let dispatch_pattern =
-                    Compiler::pattern_to_dispatch_pattern(&method.signature, &compiler.parser);
-
-                // Store a placeholder entry BEFORE compiling the body
-                // This allows recursive methods to reference themselves
-                compiler.compiled_methods.insert(
-                    method_id.clone(),
-                    CompiledMethod {
-                        id: method_id.clone(),
-                        method_name: method.name.clone(),
-                        pattern: dispatch_pattern,
-                        instructions: vec![], // Placeholder - will be filled in
-                        parameter_names: parameter_names.clone(),
-                    },
-                );
+            // Register with multimethod dispatch table
+            compiler.register_method(method.clone())?;
 
-                // Set up local variable scope for compiling the method body
-                let old_locals = compiler.context.local_variables.clone();
-                compiler.context.local_variables = parameter_names.iter().cloned().collect();
-
-                // Build method preamble: copy argument from 'arg' register to local variables
-                let mut body_instructions = vec![];
-                for param_name in &parameter_names {
-                    body_instructions.push(Instruction::StoreLocal {
-                        name: param_name.clone(),
-                        register: "arg".to_string(),
-                    });
-                }
+            // Extract parameter names from the signature
+            let parameter_names = if let Some(ref sig) = method.signature {
+                Compiler::extract_variable_names(sig)
+            } else {
+                vec![]
+            };
+
+            // Store a placeholder entry BEFORE compiling the body
+            // This allows recursive methods to reference themselves
+            compiler.compiled_methods.insert(
+                method_id.clone(),
+                CompiledMethod {
+                    id: method_id.clone(),
+                    method_name: method.name.clone(),
+                    instructions: vec![], // Placeholder - will be filled in
+                    parameter_names: parameter_names.clone(),
+                    start_pos: expression.start_pos,
+                    end_pos: expression.end_pos,
+                    guard: None,
+                },
+            );
+
+            // Set up local variable scope for compiling the method body
+            let old_locals = compiler.context.local_variables.clone();
+            compiler.context.local_variables = parameter_names.iter().cloned().collect();
+
+            // Keep track of the current method so `next_method` can refer to it
+            let old_method = compiler.context.current_method.replace(method.clone());
+            let old_forwards_arguments =
+                std::mem::replace(&mut compiler.context.forwards_arguments, false);
+
+            // Build method preamble: bind the call's arguments to local variables
+            let mut body_instructions = match method.signature {
+                Some(ref sig) => compiler.compile_parameter_bindings(sig),
+                None => vec![],
+            };
+
+            // Compile the method body with result going to 'ret' register
+            body_instructions.append(
+                &mut compiler.compile_expression(*method.body.clone(), Some("ret".to_string()))?,
+            );
 
-                // Compile the method body with result going to 'ret' register
-                body_instructions.append(
-                    &mut compiler
-                        .compile_expression(*method.body.clone(), Some("ret".to_string()))?,
+            // Add RETURN instruction at end of method
+            body_instructions.push(Instruction::Return);
+
+            // Save the arguments before binding them if they are passed on to the next variant
+            if compiler.context.forwards_arguments {
+                body_instructions.insert(
+                    0,
+                    Instruction::StoreLocal {
+                        name: METHOD_ARGUMENTS.to_string(),
+                        register: "args".to_string(),
+                    },
                 );
+            }
+
+            compiler.context.current_method = old_method;
+            compiler.context.forwards_arguments = old_forwards_arguments;
 
-                // Add RETURN instruction at end of method
-                body_instructions.push(Instruction::Return);
+            // Compile the guard clause into a separate routine which binds the
+            // arguments the same way and leaves the condition in the 'ret' register
+            let guard_instructions = match method.guard {
+                Some(ref guard) => {
+                    let mut guard_instructions = match method.signature {
+                        Some(ref sig) => compiler.compile_parameter_bindings(sig),
+                        None => vec![],
+                    };
 
-                // Restore previous scope
-                compiler.context.local_variables = old_locals;
+                    guard_instructions.append(
+                        &mut compiler
+                            .compile_expression(*guard.clone(), Some("ret".to_string()))?,
+                    );
+                    guard_instructions.push(Instruction::Return);
 
-                // Update the compiled method with actual instructions
-                if let Some(compiled) = compiler.compiled_methods.get_mut(&method_id) {
-                    compiled.instructions = body_instructions;
+                    Some(guard_instructions)
                 }
-            }
 
-            _ => (),
+                None => None,
+            };
+
+            // Restore previous scope
+            compiler.context.local_variables = old_locals;
+
+            // Update the compiled method with actual instructions
+            if let Some(compiled) = compiler.compiled_methods.get_mut(&method_id) {
+                compiled.instructions = body_instructions;
+                compiled.guard = guard_instructions;
+            }
         }
 
         Ok(vec![])
@@ -4,14 +4,24 @@ use strontium::machine::instruction::Instruction;
 use super::Compiler;
 
 mod call;
+mod class;
+mod enumeration;
+mod interface;
+mod list;
 mod literal;
 mod method;
+mod safe_call;
 mod value_pattern;
 mod variable_pattern;
 
 pub use self::call::*;
+pub use self::class::*;
+pub use self::enumeration::*;
+pub use self::interface::*;
+pub use self::list::*;
 pub use self::literal::*;
 pub use self::method::*;
+pub use self::safe_call::*;
 pub use self::value_pattern::*;
 pub use self::variable_pattern::*;
 
@@ -0,0 +1,87 @@
+use super::{CallCompilelet, Compilelet};
+use crate::compiler::Compiler;
+use crate::types::{
+    CompilerResult, Expression, ExpressionKind, Method, Pattern, TypeExpression, VariablePattern,
+};
+use strontium::machine::instruction::Instruction;
+use strontium::machine::register::RegisterValue;
+
+/// Compilelet for safe navigations like `person?.name`.
+///
+/// The multimethod is given a native variant `name(this Nil)` which returns `nil`, so the
+/// call can be compiled like any other one and dispatches to it if the receiver is `nil`.
+/// The type checker makes sure the other calls of the multimethod never pass `nil`.
+pub struct SafeCallCompilelet;
+
+impl Compilelet for SafeCallCompilelet {
+    fn compile(
+        &self,
+        compiler: &mut Compiler,
+        expression: Expression,
+        target_register: Option<String>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        if let ExpressionKind::SafeCall(call) = &expression.kind {
+            Self::define_nil_variant(compiler, &call.name, &expression)?;
+
+            let call = Expression {
+                kind: ExpressionKind::Call(call.clone()),
+                ..expression
+            };
+
+            CallCompilelet.compile(compiler, call, target_register)
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl SafeCallCompilelet {
+    /// Define the variant of a multimethod which returns `nil` for a `nil` receiver, unless
+    /// it has already been defined by another safe navigation.
+    fn define_nil_variant(
+        compiler: &mut Compiler,
+        name: &str,
+        expression: &Expression,
+    ) -> CompilerResult<()> {
+        let signature = Some(Pattern::Variable(VariablePattern {
+            name: Some("this".to_string()),
+            type_id: Some(TypeExpression::named("Nil")),
+        }));
+
+        let is_defined = match compiler.multimethods.get(name) {
+            Some(multimethod) => multimethod
+                .methods
+                .iter()
+                .any(|method| method.signature == signature),
+            // Calls of methods which don't exist are reported by the call compilelet.
+            None => true,
+        };
+
+        if is_defined {
+            return Ok(());
+        }
+
+        let method = Method {
+            name: name.to_string(),
+            qualifier: None,
+            type_parameters: vec![],
+            signature,
+            return_type: Some(TypeExpression::named("Nil")),
+            guard: None,
+            body: Box::new(expression.clone()),
+        };
+
+        compiler.define_native_method(
+            method,
+            vec![
+                Instruction::Load {
+                    value: RegisterValue::Empty,
+                    register: "ret".to_string(),
+                },
+                Instruction::Return,
+            ],
+            expression.start_pos,
+            expression.end_pos,
+        )
+    }
+}
@@ -0,0 +1,151 @@
+//! Check that every type provides the methods required by the interfaces it implements.
+//!
+//! Interfaces list method signatures in which `this` stands in for the implementing type.
+//! Since methods may be defined anywhere in the program, the check runs once all of them
+//! are known, and for each class implementing an interface, directly or through one of
+//! its supertypes, each required signature needs to be fully covered by the unguarded
+//! variants of its multimethod.
+
+use crate::compiler::{Compiler, TypeKind};
+use crate::types::{CompilerError, CompilerResult, MethodSignature, Pattern, TypeExpression};
+
+impl Compiler {
+    /// Ensure that each type implements all methods of its interfaces, including the ones
+    /// it inherits through its enum or through interfaces extending other interfaces.
+    pub fn check_interface_conformance(&self) -> CompilerResult<()> {
+        self.types.check_hierarchy()?;
+
+        for definition in self.types.definitions() {
+            let enum_name = self.types.enum_of(&definition.name);
+
+            for supertype in &definition.supertypes {
+                // Variants belong to their enum, which isn't an interface.
+                if Some(supertype) == enum_name.as_ref() {
+                    continue;
+                }
+
+                if !matches!(
+                    self.types.get(supertype).map(|other| &other.kind),
+                    Some(TypeKind::Interface { .. })
+                ) {
+                    return Err(CompilerError::UnknownInterface {
+                        type_name: definition.name.clone(),
+                        interface_name: supertype.clone(),
+                    });
+                }
+            }
+
+            // Only classes have instances, so they're the ones which need the methods.
+            if !matches!(definition.kind, TypeKind::Class { .. }) {
+                continue;
+            }
+
+            for supertype in self.types.linearization(&definition.name)? {
+                let methods = match self.types.get(&supertype).map(|other| &other.kind) {
+                    Some(TypeKind::Interface { methods }) => methods,
+                    _ => continue,
+                };
+
+                for method in methods {
+                    if !self.implements_method(&definition.name, method) {
+                        return Err(CompilerError::MissingInterfaceMethod {
+                            type_name: definition.name.clone(),
+                            interface_name: supertype.clone(),
+                            method_name: method.name.clone(),
+                            signature: method.signature.clone().map(Box::new),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the unguarded variants of a method accept all instances of a class,
+    /// including the variants defined for any of its supertypes.
+    fn implements_method(&self, type_name: &str, method: &MethodSignature) -> bool {
+        self.get_multimethod(&method.name)
+            .is_some_and(|multimethod| {
+                multimethod.accepts_all(
+                    &self.parser,
+                    &self.types,
+                    &Self::substitute_receiver(&method.signature, type_name),
+                )
+            })
+    }
+
+    /// Annotate each `this` parameter in a signature with the given type.
+    fn substitute_receiver(signature: &Option<Pattern>, type_name: &str) -> Option<Pattern> {
+        let elements = signature
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|element| match element {
+                Pattern::Variable(mut variable) if variable.name.as_deref() == Some("this") => {
+                    variable.type_id = Some(TypeExpression::named(type_name));
+                    Pattern::Variable(variable)
+                }
+                _ => element,
+            })
+            .collect();
+
+        Pattern::from_elements(elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::types::{CompilerError, CompilerResult};
+
+    use strontium::machine::instruction::Instruction;
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    const SHAPE: &str = "interface Shape
+  def area(this)
+  def scale(this, factor Float)
+end
+def class Circle(radius Float) with Shape
+";
+
+    #[test]
+    fn class_implementing_all_methods_conforms() {
+        let source = format!(
+            "{}def area(c Circle) 1\ndef scale(c Circle, factor Float) c",
+            SHAPE
+        );
+
+        assert!(compile(&source).is_ok());
+    }
+
+    #[test]
+    fn class_missing_a_method_is_an_error() {
+        let source = format!("{}def area(c Circle) 1", SHAPE);
+
+        assert!(matches!(
+            compile(&source),
+            Err(CompilerError::MissingInterfaceMethod {
+                type_name,
+                interface_name,
+                method_name,
+                ..
+            }) if type_name == "Circle" && interface_name == "Shape" && method_name == "scale"
+        ));
+    }
+
+    #[test]
+    fn class_implementing_a_class_is_an_error() {
+        assert!(matches!(
+            compile("def class Point(x Float)\ndef class Circle(radius Float) with Point"),
+            Err(CompilerError::UnknownInterface {
+                type_name,
+                interface_name,
+            }) if type_name == "Circle" && interface_name == "Point"
+        ));
+    }
+}
@@ -0,0 +1,1417 @@
+//! Compile the signatures of a multimethod into a decision tree which selects a variant.
+//!
+//! Each multimethod gets a dispatcher routine which the runtime jumps to for every
+//! `Dispatch` of its name. The dispatcher inspects the call's arguments in the `args`
+//! array, tests their number, types and values, evaluates any guards and finally jumps
+//! to the body of the most specific variant which accepts them.
+//!
+//! Only instances of classes carry a type at runtime, so a parameter typed with an enum or
+//! interface is tested against each class conforming to it instead. If several variants
+//! accept the same class through different supertypes, the one whose type comes first in
+//! the class's linearization is selected.
+//!
+//! Primary variants with `before` or `after` methods are entered through an effective
+//! method instead, which calls the applicable auxiliary methods around the primary one.
+
+use crate::compiler::{Compiler, Multimethod, Overlap, TypeSystem};
+use crate::parser::Parser;
+use crate::types::{
+    CompilerResult, Constant, Method, MethodQualifier, Pattern, ValuePattern, VariablePattern,
+};
+
+use std::collections::HashMap;
+
+use strontium::machine::instruction::{ComparisonMethod, Instruction, Interrupt, InterruptKind};
+use strontium::machine::register::RegisterValue;
+
+/// The name of the local variable in which the dispatcher keeps the call's arguments,
+/// since evaluating a guard may involve other calls which overwrite the `args` register.
+pub(crate) const DISPATCH_ARGUMENTS: &str = "$args";
+
+/// The name of the local variable in which an effective method keeps the result of its
+/// primary variant while the `after` methods are called.
+const EFFECTIVE_RESULT: &str = "$result";
+
+/// A single test performed by the dispatcher.
+///
+/// The path of a test addresses a value within the arguments, where each element is an
+/// index into the array at the previous level, so `[1, 0]` is the first element of a list
+/// passed as the second argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The array at the given path has exactly, or at least, the given number of elements.
+    Length {
+        path: Vec<usize>,
+        length: usize,
+        exact: bool,
+    },
+    /// The value at the given path has the given runtime type.
+    Type { path: Vec<usize>, type_name: String },
+    /// The value at the given path is equal to the given constant.
+    Value { path: Vec<usize>, value: Constant },
+    /// The guard clause of the given method variant evaluates to `true`.
+    Guard {
+        method_id: String,
+        signature: Option<Pattern>,
+    },
+}
+
+impl Condition {
+    /// Check whether this condition can never hold if the other one does.
+    fn contradicts(&self, other: &Condition, types: &TypeSystem) -> bool {
+        match (self, other) {
+            (
+                Condition::Length {
+                    path: a,
+                    length: n,
+                    exact: true,
+                },
+                Condition::Length {
+                    path: b,
+                    length: m,
+                    exact,
+                },
+            )
+            | (
+                Condition::Length {
+                    path: b,
+                    length: m,
+                    exact,
+                },
+                Condition::Length {
+                    path: a,
+                    length: n,
+                    exact: true,
+                },
+            ) if a == b => {
+                if *exact {
+                    n != m
+                } else {
+                    n < m
+                }
+            }
+
+            (
+                Condition::Type {
+                    path: a,
+                    type_name: t,
+                },
+                Condition::Type {
+                    path: b,
+                    type_name: u,
+                },
+            ) if a == b => !types.may_overlap(t, u),
+
+            (Condition::Value { path: a, value: c }, Condition::Value { path: b, value: d })
+                if a == b =>
+            {
+                !c.numeric_eq(d)
+            }
+
+            // Numbers are compared by their value, so `1` may also match a `Float`.
+            (Condition::Value { path: a, value }, Condition::Type { path: b, type_name })
+            | (Condition::Type { path: b, type_name }, Condition::Value { path: a, value })
+                if a == b =>
+            {
+                value.type_name() != *type_name
+                    && !(Constant::is_numeric_type(&value.type_name())
+                        && Constant::is_numeric_type(type_name))
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Collect the conditions which a value at the given path needs to satisfy to match
+    /// the given pattern, ordered so that the elements of an array are only accessed after
+    /// its length has been checked.
+    fn from_pattern(parser: &Parser, pattern: &Pattern, path: Vec<usize>) -> Vec<Condition> {
+        match pattern {
+            Pattern::Variable(VariablePattern {
+                type_id: Some(type_id),
+                ..
+            }) => vec![Condition::Type {
+                path,
+                type_name: type_id.name(),
+            }],
+
+            // Value patterns in signatures are checked to be constant by the multimethod.
+            Pattern::Value(ValuePattern { expression }) => {
+                match Constant::evaluate(parser, expression) {
+                    Ok(value) => vec![Condition::Value { path, value }],
+                    Err(_) => vec![],
+                }
+            }
+
+            Pattern::List(list) => {
+                let mut conditions = vec![Condition::Type {
+                    path: path.clone(),
+                    type_name: String::from("List"),
+                }];
+
+                conditions.append(&mut Condition::from_sequence(parser, &list.elements, path));
+                conditions
+            }
+
+            Pattern::Pair(_) => {
+                Condition::from_sequence(parser, &pattern.clone().flatten_pairs(), path)
+            }
+            Pattern::Tuple(tuple) => Condition::from_pattern(parser, &tuple.child, path),
+            Pattern::Field(field) => Condition::from_pattern(parser, &field.value, path),
+            Pattern::Variable(_) | Pattern::Rest(_) => vec![],
+        }
+    }
+
+    /// Collect the conditions for an array at the given path to match a sequence of patterns.
+    fn from_sequence(parser: &Parser, elements: &[Pattern], path: Vec<usize>) -> Vec<Condition> {
+        let mut conditions = vec![match elements.last() {
+            Some(Pattern::Rest(_)) => Condition::Length {
+                path: path.clone(),
+                length: elements.len() - 1,
+                exact: false,
+            },
+            _ => Condition::Length {
+                path: path.clone(),
+                length: elements.len(),
+                exact: true,
+            },
+        }];
+
+        for (index, element) in elements.iter().enumerate() {
+            let mut element_path = path.clone();
+            element_path.push(index);
+
+            conditions.append(&mut Condition::from_pattern(parser, element, element_path));
+        }
+
+        conditions
+    }
+}
+
+/// A binary decision tree which selects a method variant for the arguments of a call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionTree {
+    /// Continue with one of two subtrees depending on whether a condition holds.
+    Test {
+        condition: Condition,
+        success: Box<DecisionTree>,
+        failure: Box<DecisionTree>,
+    },
+    /// Select the method variant with the given ID.
+    Leaf {
+        method_id: String,
+        signature: Option<Pattern>,
+    },
+    /// No variant accepts the arguments.
+    Fail,
+}
+
+/// A call to an auxiliary method from within an effective method.
+#[derive(Debug, Clone)]
+pub struct AuxiliaryCall {
+    pub method: Method,
+    /// Whether the method only applies to some of the primary variant's arguments, or has
+    /// a guard, so its conditions need to be tested before calling it.
+    pub is_conditional: bool,
+}
+
+/// A primary variant combined with the `before` and `after` methods which may apply to it.
+#[derive(Debug, Clone)]
+pub struct EffectiveMethod {
+    pub primary: Method,
+    /// The `before` methods, from the most to the least specific.
+    pub before: Vec<AuxiliaryCall>,
+    /// The `after` methods, from the least to the most specific.
+    pub after: Vec<AuxiliaryCall>,
+}
+
+impl EffectiveMethod {
+    /// Combine a primary variant with the auxiliary methods of its multimethod, returning
+    /// `None` if none of them may apply to its arguments.
+    pub fn build(
+        parser: &Parser,
+        types: &TypeSystem,
+        multimethod: &Multimethod,
+        primary: &Method,
+    ) -> Option<EffectiveMethod> {
+        let applicable = |qualifier| {
+            multimethod
+                .auxiliary_methods_by_specificity(types, qualifier)
+                .into_iter()
+                .filter_map(|method| {
+                    let is_conditional = match Multimethod::signature_overlap(
+                        parser,
+                        types,
+                        &method.signature,
+                        &primary.signature,
+                    ) {
+                        Overlap::Contains => method.guard.is_some(),
+                        Overlap::Intersects => true,
+                        Overlap::Disjoint => return None,
+                    };
+
+                    Some(AuxiliaryCall {
+                        method: method.clone(),
+                        is_conditional,
+                    })
+                })
+                .collect::<Vec<AuxiliaryCall>>()
+        };
+
+        let before = applicable(MethodQualifier::Before);
+        let mut after = applicable(MethodQualifier::After);
+        after.reverse();
+
+        if before.is_empty() && after.is_empty() {
+            return None;
+        }
+
+        Some(EffectiveMethod {
+            primary: primary.clone(),
+            before,
+            after,
+        })
+    }
+}
+
+/// A method variant along with the conditions which still need to be tested to select it.
+#[derive(Debug, Clone)]
+struct Row {
+    conditions: Vec<Condition>,
+    method_id: String,
+    signature: Option<Pattern>,
+    /// For each type condition, the position of the declared type in the linearization
+    /// of the class which is tested in its place.
+    distances: Vec<usize>,
+}
+
+impl Row {
+    /// Replace each type condition for an enum or interface with one for a class conforming
+    /// to it, returning a row for each combination of such classes.
+    fn expand(self, types: &TypeSystem) -> Vec<Row> {
+        let mut rows = vec![Row {
+            conditions: vec![],
+            distances: vec![],
+            ..self.clone()
+        }];
+
+        for condition in self.conditions {
+            let (path, type_name) = match &condition {
+                Condition::Type { path, type_name } => (path, type_name),
+                _ => {
+                    for row in rows.iter_mut() {
+                        row.conditions.push(condition.clone());
+                    }
+
+                    continue;
+                }
+            };
+
+            rows = rows
+                .into_iter()
+                .flat_map(|row| {
+                    types
+                        .concrete_types(type_name)
+                        .into_iter()
+                        .map(move |concrete_type| {
+                            let mut row = row.clone();
+
+                            row.distances
+                                .push(types.distance(&concrete_type, type_name).unwrap_or(0));
+                            row.conditions.push(Condition::Type {
+                                path: path.clone(),
+                                type_name: concrete_type,
+                            });
+
+                            row
+                        })
+                })
+                .collect();
+        }
+
+        rows
+    }
+}
+
+impl DecisionTree {
+    /// Build a decision tree for the given variants, ordered from the most to the least specific.
+    pub fn build(parser: &Parser, types: &TypeSystem, methods: &[&Method]) -> DecisionTree {
+        let rows: Vec<Row> = methods
+            .iter()
+            .map(|method| {
+                let method_id = Compiler::generate_method_id(method);
+                let elements = method
+                    .signature
+                    .clone()
+                    .map(Pattern::flatten_pairs)
+                    .unwrap_or_default();
+
+                let mut conditions = Condition::from_sequence(parser, &elements, vec![]);
+
+                // Guards are evaluated last, once all arguments are known to match.
+                if method.guard.is_some() {
+                    conditions.push(Condition::Guard {
+                        method_id: method_id.clone(),
+                        signature: method.signature.clone(),
+                    });
+                }
+
+                Row {
+                    conditions,
+                    method_id,
+                    signature: method.signature.clone(),
+                    distances: vec![],
+                }
+            })
+            .collect();
+
+        let mut ordered: Vec<Row> = vec![];
+
+        for row in rows.into_iter().flat_map(|row| row.expand(types)) {
+            let index = DecisionTree::insertion_index(&ordered, &row, types);
+            ordered.insert(index, row);
+        }
+
+        DecisionTree::from_rows(ordered, types)
+    }
+
+    /// Find the position at which an expanded row is inserted into the rows before it.
+    ///
+    /// Rows testing exactly the same conditions can't be told apart by the arguments, so the
+    /// one whose declared types are closer to the tested classes is moved in front of the
+    /// others, past any rows in between which can never match at the same time.
+    fn insertion_index(rows: &[Row], row: &Row, types: &TypeSystem) -> usize {
+        let mut index = rows.len();
+
+        for (position, other) in rows.iter().enumerate().rev() {
+            if other.conditions == row.conditions {
+                if other.distances <= row.distances {
+                    break;
+                }
+
+                index = position;
+            } else if !other.conditions.iter().any(|condition| {
+                row.conditions
+                    .iter()
+                    .any(|given| condition.contradicts(given, types))
+            }) {
+                break;
+            }
+        }
+
+        index
+    }
+
+    /// Build a decision tree for variants ordered from the most to the least specific.
+    ///
+    /// We always test the next condition of the most specific remaining variant. If it holds,
+    /// the condition is removed from all variants which share it, and variants which
+    /// contradict it are discarded. If it fails, all variants which require it are discarded.
+    fn from_rows(rows: Vec<Row>, types: &TypeSystem) -> DecisionTree {
+        let first = match rows.first() {
+            Some(first) => first,
+            None => return DecisionTree::Fail,
+        };
+
+        let condition = match first.conditions.first() {
+            Some(condition) => condition.clone(),
+            None => {
+                return DecisionTree::Leaf {
+                    method_id: first.method_id.clone(),
+                    signature: first.signature.clone(),
+                }
+            }
+        };
+
+        let success = rows
+            .iter()
+            .filter(|row| {
+                !row.conditions
+                    .iter()
+                    .any(|c| c.contradicts(&condition, types))
+            })
+            .map(|row| Row {
+                conditions: row
+                    .conditions
+                    .iter()
+                    .filter(|c| **c != condition)
+                    .cloned()
+                    .collect(),
+                ..row.clone()
+            })
+            .collect();
+
+        let failure = rows
+            .into_iter()
+            .filter(|row| !row.conditions.contains(&condition))
+            .collect();
+
+        DecisionTree::Test {
+            condition,
+            success: Box::new(DecisionTree::from_rows(success, types)),
+            failure: Box::new(DecisionTree::from_rows(failure, types)),
+        }
+    }
+}
+
+impl Compiler {
+    /// Compile the dispatcher routine of a multimethod which starts at the given address.
+    pub fn compile_dispatcher(
+        &mut self,
+        method_name: &str,
+        tree: &DecisionTree,
+        offset: usize,
+        method_addresses: &HashMap<String, usize>,
+        guard_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let mut instructions = vec![Instruction::StoreLocal {
+            name: DISPATCH_ARGUMENTS.to_string(),
+            register: "args".to_string(),
+        }];
+
+        let tree_offset = offset + self.code_size(&instructions);
+
+        instructions.append(&mut self.compile_decision_tree(
+            method_name,
+            tree,
+            tree_offset,
+            method_addresses,
+            guard_addresses,
+        )?);
+
+        Ok(instructions)
+    }
+
+    /// Compile an effective method which starts at the given address.
+    ///
+    /// The `before` methods are called first, followed by the primary variant, whose result
+    /// is kept while the `after` methods are called, and finally returned.
+    pub fn compile_effective_method(
+        &mut self,
+        effective_method: &EffectiveMethod,
+        offset: usize,
+        method_addresses: &HashMap<String, usize>,
+        guard_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let mut instructions = vec![Instruction::StoreLocal {
+            name: DISPATCH_ARGUMENTS.to_string(),
+            register: "args".to_string(),
+        }];
+
+        for call in &effective_method.before {
+            let call_offset = offset + self.code_size(&instructions);
+
+            instructions.append(&mut self.compile_auxiliary_call(
+                call,
+                call_offset,
+                method_addresses,
+                guard_addresses,
+            )?);
+        }
+
+        let primary = &effective_method.primary;
+
+        instructions.append(&mut self.compile_argument_restore(&primary.signature));
+        instructions.push(Instruction::Call {
+            address: Compiler::address_of(
+                method_addresses,
+                &Compiler::generate_method_id(primary),
+            )?,
+        });
+        instructions.push(Instruction::StoreLocal {
+            name: EFFECTIVE_RESULT.to_string(),
+            register: "ret".to_string(),
+        });
+
+        for call in &effective_method.after {
+            let call_offset = offset + self.code_size(&instructions);
+
+            instructions.append(&mut self.compile_auxiliary_call(
+                call,
+                call_offset,
+                method_addresses,
+                guard_addresses,
+            )?);
+        }
+
+        instructions.push(Instruction::LoadLocal {
+            name: EFFECTIVE_RESULT.to_string(),
+            register: "ret".to_string(),
+        });
+        instructions.push(Instruction::Return);
+
+        Ok(instructions)
+    }
+
+    /// Compile a call to an auxiliary method which starts at the given address, which is
+    /// skipped if any of the method's conditions doesn't hold for the arguments.
+    fn compile_auxiliary_call(
+        &mut self,
+        call: &AuxiliaryCall,
+        offset: usize,
+        method_addresses: &HashMap<String, usize>,
+        guard_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        let mut instructions = vec![];
+        let mut skips = vec![];
+        let method_id = Compiler::generate_method_id(&call.method);
+
+        if call.is_conditional {
+            let elements = call
+                .method
+                .signature
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default();
+            let mut conditions = Condition::from_sequence(&self.parser, &elements, vec![]);
+
+            if call.method.guard.is_some() {
+                conditions.push(Condition::Guard {
+                    method_id: method_id.clone(),
+                    signature: call.method.signature.clone(),
+                });
+            }
+
+            for condition in &conditions {
+                let condition_offset = offset + self.code_size(&instructions);
+                let (mut condition_instructions, condition_register) =
+                    self.compile_condition(condition, condition_offset, guard_addresses)?;
+                let false_register = self.registers.allocate_register();
+                let negated_register = self.registers.allocate_register();
+
+                instructions.append(&mut condition_instructions);
+                instructions.push(Instruction::Load {
+                    value: RegisterValue::Boolean(false),
+                    register: false_register.clone(),
+                });
+                instructions.push(Instruction::Compare {
+                    method: ComparisonMethod::EQ,
+                    operand1: condition_register,
+                    operand2: false_register,
+                    destination: negated_register.clone(),
+                });
+
+                // The destination is patched once the size of the call is known.
+                skips.push(instructions.len());
+                instructions.push(Instruction::JumpC {
+                    destination: 0,
+                    conditional_address: negated_register,
+                });
+            }
+        }
+
+        instructions.append(&mut self.compile_argument_restore(&call.method.signature));
+        instructions.push(Instruction::Call {
+            address: Compiler::address_of(method_addresses, &method_id)?,
+        });
+
+        let end = (offset + self.code_size(&instructions)) as u32;
+
+        for index in skips {
+            if let Instruction::JumpC { destination, .. } = &mut instructions[index] {
+                *destination = end;
+            }
+        }
+
+        Ok(instructions)
+    }
+
+    /// Compile a decision tree which starts at the given address.
+    ///
+    /// Each test is followed by a conditional jump to the code of its success branch,
+    /// which is placed after the code of its failure branch.
+    fn compile_decision_tree(
+        &mut self,
+        method_name: &str,
+        tree: &DecisionTree,
+        offset: usize,
+        method_addresses: &HashMap<String, usize>,
+        guard_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        match tree {
+            DecisionTree::Test {
+                condition,
+                success,
+                failure,
+            } => {
+                let (mut instructions, condition_register) =
+                    self.compile_condition(condition, offset, guard_addresses)?;
+
+                let jump_size = self.instruction_size(&Instruction::JumpC {
+                    destination: 0,
+                    conditional_address: condition_register.clone(),
+                });
+
+                let failure_offset = offset + self.code_size(&instructions) + jump_size;
+                let failure_instructions = self.compile_decision_tree(
+                    method_name,
+                    failure,
+                    failure_offset,
+                    method_addresses,
+                    guard_addresses,
+                )?;
+
+                let success_offset = failure_offset + self.code_size(&failure_instructions);
+
+                instructions.push(Instruction::JumpC {
+                    destination: success_offset as u32,
+                    conditional_address: condition_register,
+                });
+                instructions.extend(failure_instructions);
+                instructions.append(&mut self.compile_decision_tree(
+                    method_name,
+                    success,
+                    success_offset,
+                    method_addresses,
+                    guard_addresses,
+                )?);
+
+                Ok(instructions)
+            }
+
+            DecisionTree::Leaf {
+                method_id,
+                signature,
+            } => {
+                let address = Compiler::address_of(method_addresses, method_id)?;
+                let mut instructions = self.compile_argument_restore(signature);
+
+                instructions.push(Instruction::Jump {
+                    destination: address as u32,
+                });
+
+                Ok(instructions)
+            }
+
+            DecisionTree::Fail => Ok(self.compile_dispatch_failure(method_name)),
+        }
+    }
+
+    /// Raise a runtime error for a call which no variant of the given multimethod accepts.
+    ///
+    /// The program is halted after the error in case the runtime continues after it, since
+    /// there is no result to return to the caller.
+    fn compile_dispatch_failure(&mut self, method_name: &str) -> Vec<Instruction> {
+        let message_register = self.registers.allocate_register();
+
+        vec![
+            Instruction::Load {
+                value: RegisterValue::String(format!(
+                    "no variant of `{}` accepts the arguments",
+                    method_name
+                )),
+                register: message_register.clone(),
+            },
+            Instruction::Interrupt {
+                interrupt: Interrupt {
+                    address: message_register,
+                    kind: InterruptKind::Error,
+                },
+            },
+            Instruction::Halt,
+        ]
+    }
+
+    /// Compile a condition which starts at the given address, returning the instructions
+    /// and the register holding its result.
+    fn compile_condition(
+        &mut self,
+        condition: &Condition,
+        offset: usize,
+        guard_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<(Vec<Instruction>, String)> {
+        let (mut instructions, value_register, method, expected) = match condition {
+            Condition::Length {
+                path,
+                length,
+                exact,
+            } => {
+                let (mut instructions, array_register) = self.compile_path(path);
+                let length_register = self.registers.allocate_register();
+
+                instructions.push(Instruction::Length {
+                    source: array_register,
+                    destination: length_register.clone(),
+                });
+
+                let method = if *exact {
+                    ComparisonMethod::EQ
+                } else {
+                    ComparisonMethod::GTE
+                };
+
+                (
+                    instructions,
+                    length_register,
+                    method,
+                    RegisterValue::Int64(*length as i64),
+                )
+            }
+
+            Condition::Type { path, type_name } => {
+                let (mut instructions, value_register) = self.compile_path(path);
+                let type_register = self.registers.allocate_register();
+
+                instructions.push(Instruction::TypeOf {
+                    source: value_register,
+                    destination: type_register.clone(),
+                });
+
+                // Values of enums and interfaces have the type of one of the classes
+                // conforming to them, so each of those is compared in turn.
+                let concrete_types = self.types.concrete_types(type_name);
+
+                if concrete_types != [type_name.as_str()] {
+                    return Ok(self.compile_type_alternatives(
+                        instructions,
+                        type_register,
+                        &concrete_types,
+                        offset,
+                    ));
+                }
+
+                (
+                    instructions,
+                    type_register,
+                    ComparisonMethod::EQ,
+                    RegisterValue::String(type_name.clone()),
+                )
+            }
+
+            Condition::Value { path, value } => {
+                let (instructions, value_register) = self.compile_path(path);
+
+                let expected = Compiler::constant_value(value)?;
+
+                (instructions, value_register, ComparisonMethod::EQ, expected)
+            }
+
+            // The guard routine leaves its result in the `ret` register.
+            Condition::Guard {
+                method_id,
+                signature,
+            } => {
+                let mut instructions = self.compile_argument_restore(signature);
+
+                instructions.push(Instruction::Call {
+                    address: Compiler::address_of(guard_addresses, method_id)?,
+                });
+
+                return Ok((instructions, "ret".to_string()));
+            }
+        };
+
+        let expected_register = self.registers.allocate_register();
+        let condition_register = self.registers.allocate_register();
+
+        instructions.push(Instruction::Load {
+            value: expected,
+            register: expected_register.clone(),
+        });
+        instructions.push(Instruction::Compare {
+            method,
+            operand1: value_register,
+            operand2: expected_register,
+            destination: condition_register.clone(),
+        });
+
+        Ok((instructions, condition_register))
+    }
+
+    /// Check whether the type in the given register is one of several alternatives, where
+    /// the given instructions start at the given address.
+    ///
+    /// The result is set to `true` in advance, so that a matching type can skip the
+    /// remaining comparisons and jump to the end, which is only reached by falling through
+    /// all of them after the result has been reset to `false`.
+    fn compile_type_alternatives(
+        &mut self,
+        mut instructions: Vec<Instruction>,
+        type_register: String,
+        alternatives: &[String],
+        offset: usize,
+    ) -> (Vec<Instruction>, String) {
+        let result_register = self.registers.allocate_register();
+        let mut matches = vec![];
+
+        instructions.push(Instruction::Load {
+            value: RegisterValue::Boolean(true),
+            register: result_register.clone(),
+        });
+
+        for alternative in alternatives {
+            let expected_register = self.registers.allocate_register();
+            let condition_register = self.registers.allocate_register();
+
+            instructions.push(Instruction::Load {
+                value: RegisterValue::String(alternative.clone()),
+                register: expected_register.clone(),
+            });
+            instructions.push(Instruction::Compare {
+                method: ComparisonMethod::EQ,
+                operand1: type_register.clone(),
+                operand2: expected_register,
+                destination: condition_register.clone(),
+            });
+
+            // The destination is patched once the size of all comparisons is known.
+            matches.push(instructions.len());
+            instructions.push(Instruction::JumpC {
+                destination: 0,
+                conditional_address: condition_register,
+            });
+        }
+
+        instructions.push(Instruction::Load {
+            value: RegisterValue::Boolean(false),
+            register: result_register.clone(),
+        });
+
+        let end = (offset + self.code_size(&instructions)) as u32;
+
+        for index in matches {
+            if let Instruction::JumpC { destination, .. } = &mut instructions[index] {
+                *destination = end;
+            }
+        }
+
+        (instructions, result_register)
+    }
+
+    /// Load the value at the given path within the call's arguments into a register.
+    fn compile_path(&mut self, path: &[usize]) -> (Vec<Instruction>, String) {
+        let mut register = self.registers.allocate_register();
+        let mut instructions = vec![Instruction::LoadLocal {
+            name: DISPATCH_ARGUMENTS.to_string(),
+            register: register.clone(),
+        }];
+
+        for &index in path {
+            let index_register = self.registers.allocate_register();
+            let element_register = self.registers.allocate_register();
+
+            instructions.push(Instruction::Load {
+                value: RegisterValue::Int64(index as i64),
+                register: index_register.clone(),
+            });
+            instructions.push(Instruction::Index {
+                source: register,
+                index: index_register,
+                destination: element_register.clone(),
+            });
+
+            register = element_register;
+        }
+
+        (instructions, register)
+    }
+
+    /// Restore the `args` and `arg` registers before entering a method variant or its
+    /// guard, since they may have been overwritten by the evaluation of another guard.
+    fn compile_argument_restore(&mut self, signature: &Option<Pattern>) -> Vec<Instruction> {
+        let mut instructions = vec![Instruction::LoadLocal {
+            name: DISPATCH_ARGUMENTS.to_string(),
+            register: "args".to_string(),
+        }];
+
+        // Signatures with a single parameter are bound from the `arg` register.
+        if let Some(signature) = signature {
+            if !matches!(signature, Pattern::Pair(_) | Pattern::Rest(_)) {
+                let index_register = self.registers.allocate_register();
+
+                instructions.push(Instruction::Load {
+                    value: RegisterValue::Int64(0),
+                    register: index_register.clone(),
+                });
+                instructions.push(Instruction::Index {
+                    source: "args".to_string(),
+                    index: index_register,
+                    destination: "arg".to_string(),
+                });
+            }
+        }
+
+        instructions
+    }
+
+    /// Calculate the combined byte size of a sequence of instructions.
+    fn code_size(&self, instructions: &[Instruction]) -> usize {
+        instructions
+            .iter()
+            .map(|instruction| self.instruction_size(instruction))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Condition, DecisionTree, EffectiveMethod, DISPATCH_ARGUMENTS};
+    use crate::compiler::{
+        Compiler, Multimethod, TypeDefinition, TypeKind, TypeSystem, METHOD_ARGUMENTS,
+    };
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{CompilerError, CompilerResult, Constant, ExpressionKind, Pattern};
+
+    use std::collections::HashMap;
+
+    use strontium::machine::instruction::{Instruction, Interrupt, InterruptKind};
+
+    /// Add all method definitions to a new multimethod and return the last call's signature.
+    fn define(name: &str, source: &str) -> (Parser, Multimethod, Option<Pattern>) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let mut multimethod = Multimethod::new(name);
+        let mut call = None;
+
+        for expression in parser.parse().unwrap() {
+            match expression.kind {
+                ExpressionKind::Method(method) => {
+                    multimethod.add_method(&parser, method).unwrap();
+                }
+                ExpressionKind::Call(c) => call = c.signature,
+                _ => {}
+            }
+        }
+
+        (parser, multimethod, call)
+    }
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    /// Find the instruction which starts at the given byte address of linked bytecode.
+    fn instruction_at(bytecode: &[Instruction], address: usize) -> Option<&Instruction> {
+        let mut offset = 0;
+
+        for instruction in bytecode {
+            if offset == address {
+                return Some(instruction);
+            }
+
+            let bytes: Vec<u8> = instruction.clone().into();
+            offset += bytes.len();
+        }
+
+        None
+    }
+
+    /// Find the first instruction of each routine entered by a CALL in linked bytecode.
+    fn call_targets(bytecode: &[Instruction]) -> Vec<&Instruction> {
+        bytecode
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => instruction_at(bytecode, *address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build(name: &str, source: &str) -> DecisionTree {
+        let (parser, multimethod, _) = define(name, source);
+
+        let types = TypeSystem::new();
+
+        DecisionTree::build(&parser, &types, &multimethod.methods_by_specificity(&types))
+    }
+
+    #[test]
+    fn tests_values_before_types() {
+        let tree = build("fib", "def fib(n Int) n def fib(0) 0");
+
+        let (success, failure) = match tree {
+            DecisionTree::Test {
+                condition: Condition::Length { length: 1, .. },
+                success,
+                failure,
+            } => (success, failure),
+            _ => panic!("expected a length test"),
+        };
+
+        assert_eq!(*failure, DecisionTree::Fail);
+        assert!(matches!(
+            *success,
+            DecisionTree::Test {
+                condition: Condition::Value {
+                    value: Constant::Int(0),
+                    ..
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn shares_common_tests() {
+        let tree = build("f", "def f(\"a\", b) 1 def f(\"b\", b) 2 def f(a, b) 3");
+
+        // The argument count is only tested once, since all variants take two arguments.
+        match tree {
+            DecisionTree::Test {
+                condition: Condition::Length { length: 2, .. },
+                success,
+                failure,
+            } => {
+                assert_eq!(*failure, DecisionTree::Fail);
+
+                if let DecisionTree::Test { success, .. } = *success {
+                    assert!(matches!(*success, DecisionTree::Leaf { .. }));
+                }
+            }
+            _ => panic!("expected a length test"),
+        }
+    }
+
+    #[test]
+    fn combines_applicable_auxiliary_methods() {
+        let (parser, multimethod, _) = define(
+            "log",
+            "def log(x) x def before log(x Int) 0 def after log(x) 1 def before log(s String, t) 2",
+        );
+
+        let effective_method = EffectiveMethod::build(
+            &parser,
+            &TypeSystem::new(),
+            &multimethod,
+            &multimethod.methods[0],
+        )
+        .unwrap();
+
+        // The `before` method only applies to integers, and the two-argument one never applies.
+        assert_eq!(effective_method.before.len(), 1);
+        assert!(effective_method.before[0].is_conditional);
+        assert_eq!(effective_method.after.len(), 1);
+        assert!(!effective_method.after[0].is_conditional);
+    }
+
+    /// Define the given interfaces, and classes implementing the interfaces listed with them.
+    fn define_types(interfaces: &[&str], classes: &[(&str, &[&str])]) -> TypeSystem {
+        let mut types = TypeSystem::new();
+
+        for name in interfaces {
+            types
+                .define(TypeDefinition {
+                    name: name.to_string(),
+                    kind: TypeKind::Interface { methods: vec![] },
+                    supertypes: vec![],
+                })
+                .unwrap();
+        }
+
+        for (name, supertypes) in classes {
+            types
+                .define(TypeDefinition {
+                    name: name.to_string(),
+                    kind: TypeKind::Class { fields: vec![] },
+                    supertypes: supertypes.iter().map(|name| name.to_string()).collect(),
+                })
+                .unwrap();
+        }
+
+        types
+    }
+
+    #[test]
+    fn implementations_dont_contradict_their_interface() {
+        let (parser, multimethod, _) = define("area", "def area(c Circle) 1 def area(s Shape) 0");
+        let types = define_types(
+            &["Shape"],
+            &[("Circle", &["Shape"]), ("Square", &["Shape"])],
+        );
+
+        let tree =
+            DecisionTree::build(&parser, &types, &multimethod.methods_by_specificity(&types));
+
+        // Values which aren't circles may still be other shapes.
+        match tree {
+            DecisionTree::Test { success, .. } => match *success {
+                DecisionTree::Test {
+                    condition: Condition::Type { type_name, .. },
+                    failure,
+                    ..
+                } => {
+                    assert_eq!(type_name, "Circle");
+                    assert!(matches!(
+                        *failure,
+                        DecisionTree::Test {
+                            condition: Condition::Type { type_name, .. },
+                            ..
+                        } if type_name == "Square"
+                    ));
+                }
+                _ => panic!("expected a type test"),
+            },
+            _ => panic!("expected a length test"),
+        }
+    }
+
+    #[test]
+    fn first_supertype_in_linearization_wins() {
+        let (parser, multimethod, _) = define(
+            "describe",
+            "def describe(p Printable) 1 def describe(s Shape) 0",
+        );
+        let types = define_types(
+            &["Shape", "Printable"],
+            &[("Circle", &["Shape", "Printable"])],
+        );
+
+        let tree =
+            DecisionTree::build(&parser, &types, &multimethod.methods_by_specificity(&types));
+
+        // Both variants accept circles, but `Shape` is listed first.
+        match tree {
+            DecisionTree::Test { success, .. } => match *success {
+                DecisionTree::Test { success, .. } => assert!(matches!(
+                    *success,
+                    DecisionTree::Leaf { signature: Some(signature), .. }
+                        if format!("{}", signature) == "s: Shape"
+                )),
+                _ => panic!("expected a type test"),
+            },
+            _ => panic!("expected a length test"),
+        }
+    }
+
+    #[test]
+    fn unmatched_call_raises_an_error() {
+        let mut compiler = Compiler::new();
+        let instructions = compiler
+            .compile_dispatcher(
+                "f",
+                &DecisionTree::Fail,
+                0,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+        assert!(instructions.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Interrupt {
+                interrupt: Interrupt {
+                    kind: InterruptKind::Error,
+                    ..
+                }
+            }
+        )));
+    }
+
+    #[test]
+    fn variant_without_address_is_an_error() {
+        let mut compiler = Compiler::new();
+        let tree = DecisionTree::Leaf {
+            method_id: String::from("f_missing"),
+            signature: None,
+        };
+
+        assert!(matches!(
+            compiler.compile_dispatcher("f", &tree, 0, &HashMap::new(), &HashMap::new()),
+            Err(CompilerError::UnlinkedMethod(method_id)) if method_id == "f_missing"
+        ));
+    }
+
+    #[test]
+    fn dispatchers_are_linked_before_the_main_program() {
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile("def f(n Int) 1 def f(s String) 2 def g(x) f(x) g(1)".to_string())
+            .unwrap();
+
+        let main_start = match bytecode[0] {
+            Instruction::Jump { destination } => destination as usize,
+            _ => panic!("expected a jump to the main program"),
+        };
+
+        // Each dispatcher starts by saving the arguments, which its tests may overwrite.
+        for registration in &compiler.method_registrations {
+            assert!(registration.address < main_start);
+            assert!(matches!(
+                instruction_at(&bytecode, registration.address),
+                Some(Instruction::StoreLocal { name, register })
+                    if name == DISPATCH_ARGUMENTS && register == "args"
+            ));
+        }
+
+        let mut names = compiler
+            .method_registrations
+            .iter()
+            .map(|registration| registration.method_name.as_str())
+            .collect::<Vec<&str>>();
+        names.sort();
+
+        assert_eq!(names, ["f", "g"]);
+        assert!(instruction_at(&bytecode, main_start).is_some());
+    }
+
+    #[test]
+    fn calls_with_unknown_arguments_are_dispatched() {
+        let bytecode = compile("def f(n Int) 1 def f(s String) 2 def g(x) f(x) g(1)").unwrap();
+
+        assert!(bytecode.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Dispatch { method_name } if method_name == "f"
+        )));
+
+        // The call to `g` accepts any argument, so it's linked to the variant directly.
+        assert!(call_targets(&bytecode).iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, register } if name == "x" && register == "arg"
+        )));
+    }
+
+    /// Count the CALLs which enter a routine binding the variable with the given name.
+    fn calls_binding(bytecode: &[Instruction], variable: &str) -> usize {
+        call_targets(bytecode)
+            .iter()
+            .filter(|instruction| {
+                matches!(instruction, Instruction::StoreLocal { name, .. } if name == variable)
+            })
+            .count()
+    }
+
+    #[test]
+    fn auxiliary_methods_are_called_around_the_primary_variant() {
+        let bytecode = compile("def f(x) 1 def before f(b) 0 def after f(a) 2 f(1)").unwrap();
+
+        // The call enters the effective method, which calls each of the three variants.
+        assert!(call_targets(&bytecode).iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, .. } if name == DISPATCH_ARGUMENTS
+        )));
+        assert_eq!(calls_binding(&bytecode, "b"), 1);
+        assert_eq!(calls_binding(&bytecode, "x"), 1);
+        assert_eq!(calls_binding(&bytecode, "a"), 1);
+    }
+
+    #[test]
+    fn around_methods_are_entered_first() {
+        let bytecode = compile("def f(x) 1 def around f(y) next_method() f(1)").unwrap();
+
+        // The `around` method saves its arguments first, since it passes them on.
+        assert_eq!(calls_binding(&bytecode, METHOD_ARGUMENTS), 1);
+        assert_eq!(calls_binding(&bytecode, "x"), 1);
+    }
+
+    const CLASSIFY: &str =
+        "def classify(n Int) where n < 0 \"negative\" def classify(n Int) \"other\"";
+
+    /// Find the test of a guard clause within a decision tree.
+    fn find_guard(tree: &DecisionTree) -> Option<(&DecisionTree, &DecisionTree)> {
+        match tree {
+            DecisionTree::Test {
+                condition: Condition::Guard { .. },
+                success,
+                failure,
+            } => Some((success, failure)),
+            DecisionTree::Test {
+                success, failure, ..
+            } => find_guard(success).or_else(|| find_guard(failure)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn failed_guard_falls_through_to_the_next_variant() {
+        let (parser, multimethod, _) = define("classify", CLASSIFY);
+        let types = TypeSystem::new();
+
+        let tree =
+            DecisionTree::build(&parser, &types, &multimethod.methods_by_specificity(&types));
+        let (success, failure) = find_guard(&tree).expect("expected a guard test");
+
+        assert!(matches!(
+            success,
+            DecisionTree::Leaf { method_id, .. }
+                if *method_id == Compiler::generate_method_id(&multimethod.methods[0])
+        ));
+        assert!(matches!(
+            failure,
+            DecisionTree::Leaf { method_id, .. }
+                if *method_id == Compiler::generate_method_id(&multimethod.methods[1])
+        ));
+    }
+
+    #[test]
+    fn dispatcher_jumps_to_the_next_variant_after_a_failed_guard() {
+        let (parser, multimethod, _) = define("classify", CLASSIFY);
+        let types = TypeSystem::new();
+        let tree =
+            DecisionTree::build(&parser, &types, &multimethod.methods_by_specificity(&types));
+
+        let guarded = Compiler::generate_method_id(&multimethod.methods[0]);
+        let unguarded = Compiler::generate_method_id(&multimethod.methods[1]);
+        let method_addresses = HashMap::from([(guarded.clone(), 100), (unguarded, 200)]);
+        let guard_addresses = HashMap::from([(guarded, 300)]);
+
+        let instructions = Compiler::new()
+            .compile_dispatcher("classify", &tree, 0, &method_addresses, &guard_addresses)
+            .unwrap();
+
+        // The failure branch directly follows the guard's conditional jump.
+        let guard_call = instructions
+            .iter()
+            .position(|instruction| matches!(instruction, Instruction::Call { address: 300 }))
+            .expect("expected a call to the guard clause");
+
+        assert!(matches!(
+            instructions[guard_call + 1],
+            Instruction::JumpC { .. }
+        ));
+        assert!(matches!(
+            instructions[guard_call + 2..]
+                .iter()
+                .find(|instruction| matches!(instruction, Instruction::Jump { .. })),
+            Some(Instruction::Jump { destination: 200 })
+        ));
+
+        // Both variants remain reachable from the dispatcher.
+        assert!(instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Jump { destination: 100 })));
+    }
+
+    #[test]
+    fn guarded_auxiliary_call_is_skipped_if_the_guard_fails() {
+        let (parser, multimethod, _) =
+            define("log", "def log(x) x def before log(x Int) where x < 0 0");
+        let types = TypeSystem::new();
+        let effective_method =
+            EffectiveMethod::build(&parser, &types, &multimethod, &multimethod.methods[0]).unwrap();
+
+        let before = Compiler::generate_method_id(&multimethod.auxiliary_methods[0]);
+        let primary = Compiler::generate_method_id(&multimethod.methods[0]);
+        let method_addresses = HashMap::from([(before.clone(), 100), (primary, 200)]);
+        let guard_addresses = HashMap::from([(before, 300)]);
+
+        let mut compiler = Compiler::new();
+        let instructions = compiler
+            .compile_auxiliary_call(
+                &effective_method.before[0],
+                0,
+                &method_addresses,
+                &guard_addresses,
+            )
+            .unwrap();
+
+        // A failed guard skips past the call of the `before` method.
+        let skip = instructions
+            .iter()
+            .rev()
+            .find_map(|instruction| match instruction {
+                Instruction::JumpC { destination, .. } => Some(*destination as usize),
+                _ => None,
+            })
+            .expect("expected a conditional jump past the call");
+
+        assert_eq!(skip, compiler.code_size(&instructions));
+        assert!(instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Call { address: 300 })));
+    }
+}
@@ -0,0 +1,610 @@
+//! Find calls which can't reach any variant of a multimethod, calls which always reach the
+//! same variant, and variants which are never selected because the variants tried before
+//! them already accept all of their arguments.
+//!
+//! Each parameter of a signature is approximated by the [`Space`] of values it accepts.
+//! The approximation is conservative in both directions, so a warning is only reported
+//! if a call is guaranteed to fail, or a variant is guaranteed to be unreachable. A type
+//! contains the values of all of its subtypes, and an enum or interface is covered once
+//! each of the classes conforming to it is.
+
+use crate::compiler::{Multimethod, TypeSystem};
+use crate::parser::Parser;
+use crate::type_system::Typed;
+use crate::types::{Constant, Method, Pattern, ValuePattern, VariablePattern};
+
+/// An approximation of the values accepted by a parameter, or passed as an argument.
+#[derive(Debug, Clone, PartialEq)]
+enum Space {
+    /// Any value at all, like an untyped variable or an argument of unknown type.
+    Any,
+    /// All values of the given type, like `n Int`.
+    Type(String),
+    /// A single constant value, like `0`.
+    Value(Constant),
+    /// Some values of the given type which can't be described more precisely, like the
+    /// lists matched by `[head, ...tail]`.
+    Partial(String),
+    /// Any number of remaining values, like `...rest`.
+    Rest,
+}
+
+impl Space {
+    fn from_pattern(parser: &Parser, pattern: &Pattern) -> Space {
+        match pattern {
+            Pattern::Variable(VariablePattern { type_id: None, .. }) => Space::Any,
+            Pattern::Variable(VariablePattern {
+                type_id: Some(type_id),
+                ..
+            }) => Space::Type(type_id.name()),
+            Pattern::Value(ValuePattern { expression }) => {
+                match Constant::evaluate(parser, expression) {
+                    Ok(constant) => Space::Value(constant),
+                    // The value of an argument may only be known at runtime, but its type
+                    // is often still known at compile time.
+                    Err(_) => match expression.get_type().name() {
+                        Some(type_name) => Space::Type(type_name),
+                        None => Space::Any,
+                    },
+                }
+            }
+            Pattern::Rest(_) => Space::Rest,
+            Pattern::List(_) => Space::Partial(String::from("List")),
+            Pattern::Tuple(tuple) => Space::from_pattern(parser, &tuple.child),
+            Pattern::Field(field) => Space::from_pattern(parser, &field.value),
+            Pattern::Pair(_) => Space::Partial(String::from("Tuple")),
+        }
+    }
+
+    /// Convert a signature or the arguments of a call into a sequence of spaces.
+    fn from_signature(parser: &Parser, signature: &Option<Pattern>) -> Vec<Space> {
+        signature
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default()
+            .iter()
+            .map(|pattern| Space::from_pattern(parser, pattern))
+            .collect()
+    }
+
+    /// Check whether this space contains all values of another one.
+    fn contains(&self, types: &TypeSystem, other: &Space) -> bool {
+        match (self, other) {
+            (Space::Any | Space::Rest, _) => true,
+            (Space::Type(a), Space::Type(b) | Space::Partial(b)) => types.is_subtype(b, a),
+            (Space::Type(a), Space::Value(b)) => types.is_subtype(&b.type_name(), a),
+            (Space::Value(a), Space::Value(b)) => a.numeric_eq(b),
+            _ => false,
+        }
+    }
+
+    /// Check whether this space may share any values with another one.
+    fn intersects(&self, types: &TypeSystem, other: &Space) -> bool {
+        match (self, other) {
+            (Space::Any | Space::Rest, _) | (_, Space::Any | Space::Rest) => true,
+            // Value patterns compare numbers by their value, so `1` also accepts `1.0`.
+            (Space::Value(a), Space::Value(b)) => a.numeric_eq(b),
+            (Space::Value(a), Space::Type(b) | Space::Partial(b))
+            | (Space::Type(b) | Space::Partial(b), Space::Value(a)) => {
+                types.is_subtype(&a.type_name(), b)
+            }
+            (Space::Type(a) | Space::Partial(a), Space::Type(b) | Space::Partial(b)) => {
+                types.may_overlap(a, b)
+            }
+        }
+    }
+
+    /// Get the element at the given position of a sequence, where a trailing rest
+    /// stands in for any elements beyond the end.
+    fn element_at(sequence: &[Space], index: usize) -> Option<&Space> {
+        match sequence.get(index) {
+            Some(space) => Some(space),
+            None => sequence.last().filter(|space| **space == Space::Rest),
+        }
+    }
+
+    /// Check whether a signature accepts all arguments accepted by another one.
+    fn contains_sequence(types: &TypeSystem, reference: &[Space], given: &[Space]) -> bool {
+        let fixed_length = match given.last() {
+            Some(Space::Rest) => given.len() - 1,
+            _ => given.len(),
+        };
+
+        // A given rest can only be contained by a reference which accepts the same number
+        // of arguments or fewer before its own rest.
+        if fixed_length < given.len() && reference.last() != Some(&Space::Rest) {
+            return false;
+        }
+
+        let lengths_match = match reference.last() {
+            Some(Space::Rest) => reference.len() - 1 <= fixed_length,
+            _ => reference.len() == given.len(),
+        };
+
+        lengths_match
+            && (0..fixed_length).all(|index| {
+                Space::element_at(reference, index)
+                    .is_some_and(|space| space.contains(types, &given[index]))
+            })
+    }
+
+    /// Check whether a signature may accept the arguments of a call.
+    ///
+    /// The arguments may end with a rest, if they are described by another signature like
+    /// those passed on by `next_method()`.
+    fn intersects_sequence(types: &TypeSystem, parameters: &[Space], arguments: &[Space]) -> bool {
+        let (arguments, is_open) = match arguments.split_last() {
+            Some((Space::Rest, fixed)) => (fixed, true),
+            _ => (arguments, false),
+        };
+
+        let lengths_match = match parameters.last() {
+            Some(Space::Rest) => parameters.len() - 1 <= arguments.len() || is_open,
+            _ => {
+                parameters.len() == arguments.len()
+                    || (is_open && parameters.len() > arguments.len())
+            }
+        };
+
+        lengths_match
+            && arguments.iter().enumerate().all(|(index, argument)| {
+                Space::element_at(parameters, index)
+                    .is_some_and(|parameter| parameter.intersects(types, argument))
+            })
+    }
+
+    /// Check whether the given signatures jointly accept all arguments of another one.
+    ///
+    /// If no single signature contains the given one, booleans are split into their two
+    /// values, enums and interfaces into the classes conforming to them, and a trailing
+    /// rest into an exact number of arguments and at least one more, until either every
+    /// case is covered or no further splits are possible.
+    fn is_covered(types: &TypeSystem, rows: &[Vec<Space>], given: &[Space]) -> bool {
+        if rows
+            .iter()
+            .any(|row| Space::contains_sequence(types, row, given))
+        {
+            return true;
+        }
+
+        let boolean = Space::Type(String::from("Boolean"));
+
+        if let Some(index) = given.iter().position(|space| *space == boolean) {
+            return [true, false].iter().all(|&value| {
+                let mut split = given.to_vec();
+                split[index] = Space::Value(Constant::Boolean(value));
+
+                Space::is_covered(types, rows, &split)
+            });
+        }
+
+        let abstract_type = given
+            .iter()
+            .enumerate()
+            .find_map(|(index, space)| match space {
+                Space::Type(type_name)
+                    if types.concrete_types(type_name) != [type_name.clone()] =>
+                {
+                    Some((index, types.concrete_types(type_name)))
+                }
+                _ => None,
+            });
+
+        if let Some((index, concrete_types)) = abstract_type {
+            return concrete_types.into_iter().all(|concrete_type| {
+                let mut split = given.to_vec();
+                split[index] = Space::Type(concrete_type);
+
+                Space::is_covered(types, rows, &split)
+            });
+        }
+
+        if let Some(Space::Rest) = given.last() {
+            let fixed_length = given.len() - 1;
+            let longest_row = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+            if fixed_length < longest_row {
+                let exact = given[..fixed_length].to_vec();
+                let mut longer = exact.clone();
+                longer.extend([Space::Any, Space::Rest]);
+
+                return Space::is_covered(types, rows, &exact)
+                    && Space::is_covered(types, rows, &longer);
+            }
+        }
+
+        false
+    }
+}
+
+/// How the arguments accepted by one signature relate to those accepted by another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overlap {
+    /// The signature accepts all arguments accepted by the other one.
+    Contains,
+    /// The signature may accept some of the arguments accepted by the other one.
+    Intersects,
+    /// The signature accepts none of the arguments accepted by the other one.
+    Disjoint,
+}
+
+impl Multimethod {
+    /// Determine how the arguments accepted by a signature relate to those of another one.
+    pub fn signature_overlap(
+        parser: &Parser,
+        types: &TypeSystem,
+        reference: &Option<Pattern>,
+        given: &Option<Pattern>,
+    ) -> Overlap {
+        let reference = Space::from_signature(parser, reference);
+        let given = Space::from_signature(parser, given);
+
+        if Space::contains_sequence(types, &reference, &given) {
+            Overlap::Contains
+        } else if Space::intersects_sequence(types, &reference, &given) {
+            Overlap::Intersects
+        } else {
+            Overlap::Disjoint
+        }
+    }
+
+    /// Check whether any variant of this multimethod may accept the given arguments.
+    ///
+    /// Arguments whose value or type is only known at runtime are assumed to match, so this
+    /// only returns `false` for calls which are guaranteed to fail, like `fib(1.5)` for a
+    /// `fib` defined only for `0`, `1` and `n Int`.
+    pub fn may_accept(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        arguments: &Option<Pattern>,
+    ) -> bool {
+        let arguments = Space::from_signature(parser, arguments);
+
+        self.dispatch_order(types).into_iter().any(|method| {
+            Space::intersects_sequence(
+                types,
+                &Space::from_signature(parser, &method.signature),
+                &arguments,
+            )
+        })
+    }
+
+    /// Find the variant which is guaranteed to be selected for the given arguments, if any.
+    ///
+    /// This is the case if the first variant in order of specificity which may accept the
+    /// arguments is certain to accept all of them and has no guard, like `fib(n Int)` for the
+    /// call `fib(10)`. Calls whose arguments are only known at runtime, like `fib(n - 1)`,
+    /// can't be resolved if there is any variant more specific than `fib(n)`.
+    pub fn resolve_statically(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        arguments: &Option<Pattern>,
+    ) -> Option<&Method> {
+        Multimethod::select_statically(parser, types, self.dispatch_order(types), arguments)
+    }
+
+    /// Find the variant among the given ones, ordered from the most to the least specific,
+    /// which is guaranteed to be selected for the given arguments.
+    pub fn select_statically<'a>(
+        parser: &Parser,
+        types: &TypeSystem,
+        methods: Vec<&'a Method>,
+        arguments: &Option<Pattern>,
+    ) -> Option<&'a Method> {
+        let arguments = Space::from_signature(parser, arguments);
+
+        let method = methods.into_iter().find(|method| {
+            Space::intersects_sequence(
+                types,
+                &Space::from_signature(parser, &method.signature),
+                &arguments,
+            )
+        })?;
+
+        let signature = Space::from_signature(parser, &method.signature);
+
+        if method.guard.is_none() && Space::contains_sequence(types, &signature, &arguments) {
+            Some(method)
+        } else {
+            None
+        }
+    }
+
+    /// Find the unguarded variants which are guaranteed to accept the given arguments.
+    pub fn accepting_methods(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        arguments: &Option<Pattern>,
+    ) -> Vec<&Method> {
+        let arguments = Space::from_signature(parser, arguments);
+
+        self.methods
+            .iter()
+            .filter(|method| method.guard.is_none())
+            .filter(|method| {
+                Space::contains_sequence(
+                    types,
+                    &Space::from_signature(parser, &method.signature),
+                    &arguments,
+                )
+            })
+            .collect()
+    }
+
+    /// Check whether the unguarded primary variants jointly accept all of the given
+    /// arguments, like those required by an interface.
+    pub fn accepts_all(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        arguments: &Option<Pattern>,
+    ) -> bool {
+        let rows = self
+            .methods_by_specificity(types)
+            .into_iter()
+            .filter(|method| method.guard.is_none())
+            .map(|method| Space::from_signature(parser, &method.signature))
+            .collect::<Vec<Vec<Space>>>();
+
+        Space::is_covered(types, &rows, &Space::from_signature(parser, arguments))
+    }
+
+    /// Find all variants which can never be selected, since the unguarded variants tried
+    /// before them already accept all of their arguments.
+    pub fn unreachable_methods(&self, parser: &Parser, types: &TypeSystem) -> Vec<&Method> {
+        let mut rows = vec![];
+        let mut unreachable = vec![];
+
+        for method in self.methods_by_specificity(types) {
+            let signature = Space::from_signature(parser, &method.signature);
+
+            if Space::is_covered(types, &rows, &signature) {
+                unreachable.push(method);
+            }
+
+            if method.guard.is_none() {
+                rows.push(signature);
+            }
+        }
+
+        unreachable
+    }
+
+    /// Find the variants of enums which aren't handled, even though other variants of the
+    /// same enum are handled at the same position.
+    ///
+    /// Each result contains the first variant which handles some of the enum's variants,
+    /// the name of the enum, and the names of the variants which are passed to it in place
+    /// of the handled one but aren't accepted by any unguarded variant.
+    pub fn missing_variants(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+    ) -> Vec<(&Method, String, Vec<String>)> {
+        let methods = self.methods_by_specificity(types);
+        let rows = methods
+            .iter()
+            .filter(|method| method.guard.is_none())
+            .map(|method| Space::from_signature(parser, &method.signature))
+            .collect::<Vec<Vec<Space>>>();
+
+        let mut checked = vec![];
+        let mut missing = vec![];
+
+        for method in methods {
+            let signature = Space::from_signature(parser, &method.signature);
+
+            for (index, space) in signature.iter().enumerate() {
+                let enum_name = match space {
+                    Space::Type(type_name) => types.enum_of(type_name),
+                    _ => None,
+                };
+
+                let enum_name = match enum_name {
+                    Some(enum_name) if !checked.contains(&(enum_name.clone(), index)) => enum_name,
+                    _ => continue,
+                };
+
+                let variants = types
+                    .variants(&enum_name)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|variant| {
+                        let mut given = signature.clone();
+                        given[index] = Space::Type(variant.to_string());
+
+                        !Space::is_covered(types, &rows, &given)
+                    })
+                    .cloned()
+                    .collect::<Vec<String>>();
+
+                if !variants.is_empty() {
+                    missing.push((method, enum_name.clone(), variants));
+                }
+
+                checked.push((enum_name, index));
+            }
+        }
+
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{Compiler, Multimethod, TypeDefinition, TypeSystem};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{Expression, ExpressionKind, Pattern};
+
+    use strontium::machine::instruction::Instruction;
+
+    fn parse(source: &str) -> (Parser, Vec<Expression>) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let expressions = parser.parse().unwrap();
+
+        (parser, expressions)
+    }
+
+    /// Add all method definitions to a new multimethod and return the last call's signature.
+    fn define(name: &str, source: &str) -> (Parser, Multimethod, Option<Pattern>) {
+        let (parser, expressions) = parse(source);
+        let mut multimethod = Multimethod::new(name);
+        let mut call = None;
+
+        for expression in expressions {
+            match expression.kind {
+                ExpressionKind::Method(method) => {
+                    multimethod.add_method(&parser, method).unwrap();
+                }
+                ExpressionKind::Call(c) => call = c.signature,
+                _ => {}
+            }
+        }
+
+        (parser, multimethod, call)
+    }
+
+    #[test]
+    fn call_with_unhandled_type_is_not_accepted() {
+        let (parser, multimethod, call) =
+            define("fib", "def fib(0) 0 def fib(1) 1 def fib(n Int) n fib(1.5)");
+
+        assert!(!multimethod.may_accept(&parser, &TypeSystem::new(), &call));
+    }
+
+    #[test]
+    fn call_with_unknown_type_is_accepted() {
+        let (parser, multimethod, call) = define("fib", "def fib(n Int) n fib(x)");
+
+        assert!(multimethod.may_accept(&parser, &TypeSystem::new(), &call));
+    }
+
+    #[test]
+    fn call_with_known_type_is_resolved_statically() {
+        let (parser, multimethod, call) =
+            define("fib", "def fib(0) 0 def fib(1) 1 def fib(n Int) n fib(10)");
+
+        let method = multimethod
+            .resolve_statically(&parser, &TypeSystem::new(), &call)
+            .unwrap();
+
+        assert_eq!(format!("{}", method.signature.clone().unwrap()), "n: Int");
+    }
+
+    #[test]
+    fn call_with_unknown_value_is_dispatched() {
+        let (parser, multimethod, call) = define("fib", "def fib(0) 0 def fib(n Int) n fib(x)");
+
+        assert!(multimethod
+            .resolve_statically(&parser, &TypeSystem::new(), &call)
+            .is_none());
+    }
+
+    #[test]
+    fn variant_covered_by_values_is_unreachable() {
+        let (parser, multimethod, _) = define(
+            "f",
+            "def f(true) 1 def f(false) 0 def f(b Boolean) 2 def f(n) 3",
+        );
+
+        let unreachable = multimethod.unreachable_methods(&parser, &TypeSystem::new());
+
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!(
+            format!("{}", unreachable[0].signature.clone().unwrap()),
+            "b: Boolean"
+        );
+    }
+
+    #[test]
+    fn more_general_variants_are_reachable() {
+        let (parser, multimethod, _) =
+            define("f", "def f(0) 0 def f(n Int) n def f(n) n def f(...rest) 0");
+
+        assert!(multimethod
+            .unreachable_methods(&parser, &TypeSystem::new())
+            .is_empty());
+    }
+
+    /// Register the enums defined in the given source code and their variants.
+    fn define_enums(source: &str) -> TypeSystem {
+        let mut types = TypeSystem::new();
+
+        for expression in parse(source).1 {
+            if let ExpressionKind::Enum(definition) = expression.kind {
+                types
+                    .define(TypeDefinition::from(definition.clone()))
+                    .unwrap();
+
+                for variant in definition.variants {
+                    let mut variant = TypeDefinition::from(variant);
+                    variant.supertypes.insert(0, definition.name.clone());
+
+                    types.define(variant).unwrap();
+                }
+            }
+        }
+
+        types
+    }
+
+    #[test]
+    fn unhandled_enum_variants_are_missing() {
+        let types =
+            define_enums("enum Shape case Circle(r Float) case Square(s Float) case Dot end");
+        let (parser, multimethod, _) = define("area", "def area(c Circle) 1 def area(d Dot) 0");
+
+        let missing = multimethod.missing_variants(&parser, &types);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].1, "Shape");
+        assert_eq!(missing[0].2, vec!["Square".to_string()]);
+    }
+
+    #[test]
+    fn fallback_variant_handles_all_enum_variants() {
+        let types = define_enums("enum Shape case Circle(r Float) case Square(s Float) end");
+        let (parser, multimethod, _) = define("area", "def area(c Circle) 1 def area(s) 0");
+
+        assert!(multimethod.missing_variants(&parser, &types).is_empty());
+    }
+
+    #[test]
+    fn variants_cover_their_enum() {
+        let types = define_enums("enum Shape case Circle(r Float) case Square(s Float) end");
+        let (parser, multimethod, _) = define(
+            "area",
+            "def area(c Circle) 1 def area(s Square) 0 def area(s Shape) 2",
+        );
+
+        let unreachable = multimethod.unreachable_methods(&parser, &types);
+
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!(
+            format!("{}", unreachable[0].signature.clone().unwrap()),
+            "s: Shape"
+        );
+    }
+
+    #[test]
+    fn sized_aliases_accept_values_of_their_runtime_type() {
+        let mut compiler = Compiler::new();
+        let bytecode = compiler
+            .compile("def f(n Int64) n def f(x Float64) x f(1) f(2.5)".to_string())
+            .unwrap();
+
+        assert!(compiler.warnings.is_empty());
+        assert!(!bytecode
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Dispatch { .. })));
+    }
+}
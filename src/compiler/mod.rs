@@ -1,12 +1,15 @@
 use crate::lexer::Lexer;
 use crate::parser::Parser;
-use crate::type_system::Typed;
-use crate::types::{CompilerResult, Expression, ExpressionKind, Literal, Pattern, VariablePattern};
+use crate::type_system::NumericType;
+use crate::types::{
+    CompilerResult, CompilerWarning, Constant, Expression, ExpressionKind, Method, NodeKind,
+    Pattern, RestPattern, VariablePattern,
+};
 use crate::CompilerError;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-use strontium::machine::instruction::{DispatchPattern, Instruction};
+use strontium::machine::instruction::{ComparisonMethod, Instruction};
 use strontium::machine::register::{RegisterValue, Registers};
 
 pub type Environment<T> = HashMap<String, T>;
@@ -18,52 +21,94 @@ pub struct CompiledMethod {
     pub id: String,
     /// The name of the multimethod this belongs to.
     pub method_name: String,
-    /// The dispatch pattern for runtime matching.
-    pub pattern: DispatchPattern,
     /// The compiled bytecode for this method body.
     pub instructions: Vec<Instruction>,
     /// Names of pattern variables that need to be bound at call time.
     pub parameter_names: Vec<String>,
+    /// The position of the method definition in the source code.
+    pub start_pos: usize,
+    pub end_pos: usize,
+    /// The compiled guard clause, which binds the arguments, evaluates the guard
+    /// condition into the `ret` register and returns.
+    pub guard: Option<Vec<Instruction>>,
 }
 
-/// Tracks a pending CALL that needs address resolution during linking.
+/// Tracks a call to a multimethod which may be resolved to a single variant during linking.
+///
+/// The call site emits a CALL whose address is the index of its pending call. Once all
+/// variants are known, the linker either patches in the address of the variant which is
+/// guaranteed to be selected, or replaces the CALL with a DISPATCH at runtime.
 #[derive(Debug, Clone)]
 pub struct PendingCall {
-    /// Index in the instruction vector where the CALL is located.
-    pub instruction_index: usize,
-    /// The method ID this call targets.
-    pub target_method_id: String,
+    /// The name of the multimethod being called.
+    pub method_name: String,
+    /// The arguments of the call, which are used to select a variant.
+    pub arguments: Option<Pattern>,
+    /// For calls to `next_method`, the ID of the calling variant. Only the variants which
+    /// are less specific than it are considered.
+    pub next_after: Option<String>,
 }
 
-/// Information about a method for dispatch registration.
+/// Tracks a type test like `x is Shape`, which is expanded once all types are defined.
+///
+/// The test emits a label in place of its comparisons, since the classes conforming to an
+/// enum or interface may be defined after it. Before linking, the label is replaced by a
+/// comparison of the value's type with each of them.
+#[derive(Debug, Clone)]
+pub struct PendingTypeTest {
+    /// The register holding the name of the value's type.
+    pub type_register: String,
+    /// The register which receives whether the value is of one of the types.
+    pub result_register: String,
+    /// The names of the types tested for, like `Int` and `Nil` for `x is Int?`.
+    pub type_names: Vec<String>,
+}
+
+/// Information about a multimethod for dispatch registration.
+///
+/// Each multimethod is registered once with the address of its dispatcher, which selects
+/// the variant matching the call's arguments and jumps to its body.
 #[derive(Debug, Clone)]
 pub struct MethodRegistration {
     pub method_name: String,
-    pub pattern: DispatchPattern,
+    /// The address of the multimethod's dispatcher routine.
     pub address: usize,
 }
 
+mod builtins;
 mod compilelets;
+mod conformance;
+mod dispatch;
 mod errors;
+mod exhaustiveness;
 mod multimethod;
 mod type_system;
 
+pub use self::builtins::Builtin;
+pub use self::dispatch::{AuxiliaryCall, Condition, DecisionTree, EffectiveMethod};
 pub use self::errors::ErrorReporter;
+pub use self::exhaustiveness::Overlap;
 pub use self::multimethod::Multimethod;
-pub use self::type_system::TypeSystem;
+pub use self::type_system::{TypeChecker, TypeDefinition, TypeKind, TypeSystem};
 pub use compilelets::{
-    CallCompilelet, Compilelet, LiteralCompilelet, MethodCompilelet, ValuePatternCompilelet,
-    VariablePatternCompilelet,
+    CallCompilelet, ClassCompilelet, Compilelet, EnumCompilelet, InterfaceCompilelet,
+    ListCompilelet, LiteralCompilelet, MethodCompilelet, SafeCallCompilelet,
+    ValuePatternCompilelet, VariablePatternCompilelet,
 };
 
+/// The name of the local variable in which a method keeps its arguments if it passes them
+/// on to the next variant with `next_method()`.
+pub const METHOD_ARGUMENTS: &str = "$method_args";
+
 pub struct CompilationContext {
     pub recursion_depth: usize,
     /// Names of pattern variables in the current method scope.
     /// Used to compile variable references as LoadLocal.
     pub local_variables: HashSet<String>,
-    /// Tracks the total number of instructions emitted so far.
-    /// Used to calculate CALL instruction indices for linking.
-    pub instruction_count: usize,
+    /// The method whose body is currently being compiled, used to resolve `next_method`.
+    pub current_method: Option<Method>,
+    /// Whether the current method body passes its own arguments on to `next_method()`.
+    pub forwards_arguments: bool,
 }
 
 pub struct Compiler {
@@ -71,8 +116,8 @@ pub struct Compiler {
     _variables: Environment<Expression>,
     /// Keeps track of registers as they would be allocated in the Strontium machine.
     pub registers: Registers,
-    /// Maps expression types to pieces of code able to compile that specific expression.
-    compilelets: HashMap<String, &'static dyn Compilelet>,
+    /// Maps kinds of expressions to pieces of code able to compile that specific expression.
+    compilelets: HashMap<NodeKind, &'static dyn Compilelet>,
     pub lexer: Lexer,
     pub parser: Parser,
     pub context: CompilationContext,
@@ -85,39 +130,48 @@ pub struct Compiler {
     /// Stores compiled method bodies indexed by their unique ID.
     /// The ID is formed from the method name and a hash of its signature.
     pub compiled_methods: HashMap<String, CompiledMethod>,
-    /// Tracks CALL instructions that need address resolution during linking.
+    /// Tracks CALL instructions that need to be resolved during linking.
     pub pending_calls: Vec<PendingCall>,
     /// Method registration info for the VM's dispatch table, populated during linking.
     pub method_registrations: Vec<MethodRegistration>,
+    /// The number of labels allocated for jumps within the code of an expression.
+    labels: usize,
+    /// Type tests which are expanded before linking, by the label marking their position.
+    pub pending_type_tests: HashMap<usize, PendingTypeTest>,
+    /// Problems found in the source code which don't prevent it from being compiled.
+    pub warnings: Vec<CompilerWarning>,
     /// A structure which keeps track of defined types.
-    _types: TypeSystem,
+    pub types: TypeSystem,
     /// Reports errors to the user with helpful information.
     _errors: ErrorReporter,
 }
 
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Compiler {
     pub fn new() -> Self {
-        env_logger::init();
+        // The logger may already have been set up by another compiler in the same process.
+        let _ = env_logger::try_init();
         let mut compilelets = HashMap::new();
 
+        compilelets.insert(NodeKind::Call, &CallCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::SafeCall, &SafeCallCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::Method, &MethodCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::Class, &ClassCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::Enum, &EnumCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::Interface, &InterfaceCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::Literal, &LiteralCompilelet as &dyn Compilelet);
+        compilelets.insert(NodeKind::List, &ListCompilelet as &dyn Compilelet);
         compilelets.insert(
-            "CallExpression".to_string(),
-            &CallCompilelet as &dyn Compilelet,
-        );
-        compilelets.insert(
-            "MethodExpression".to_string(),
-            &MethodCompilelet as &dyn Compilelet,
-        );
-        compilelets.insert("Float".to_string(), &LiteralCompilelet as &dyn Compilelet);
-        compilelets.insert("Int".to_string(), &LiteralCompilelet as &dyn Compilelet);
-        compilelets.insert("String".to_string(), &LiteralCompilelet as &dyn Compilelet);
-        compilelets.insert("Boolean".to_string(), &LiteralCompilelet as &dyn Compilelet);
-        compilelets.insert(
-            "ValuePattern".to_string(),
+            NodeKind::ValuePattern,
             &ValuePatternCompilelet as &dyn Compilelet,
         );
         compilelets.insert(
-            "VariablePattern".to_string(),
+            NodeKind::VariablePattern,
             &VariablePatternCompilelet as &dyn Compilelet,
         );
 
@@ -130,13 +184,17 @@ impl Compiler {
             context: CompilationContext {
                 recursion_depth: 0,
                 local_variables: HashSet::new(),
-                instruction_count: 0,
+                current_method: None,
+                forwards_arguments: false,
             },
             multimethods: HashMap::new(),
             compiled_methods: HashMap::new(),
             pending_calls: vec![],
             method_registrations: vec![],
-            _types: TypeSystem,
+            labels: 0,
+            pending_type_tests: HashMap::new(),
+            warnings: vec![],
+            types: TypeSystem::new(),
             _errors: ErrorReporter,
         }
     }
@@ -154,58 +212,207 @@ impl Compiler {
             Pattern::Tuple(tuple) => Self::extract_variable_names(&tuple.child),
             Pattern::Field(field) => Self::extract_variable_names(&field.value),
             Pattern::Value(_) => vec![],
+            Pattern::Rest(RestPattern { name: Some(n) }) => vec![n.clone()],
+            Pattern::Rest(RestPattern { name: None }) => vec![],
+            Pattern::List(list) => list
+                .elements
+                .iter()
+                .flat_map(Self::extract_variable_names)
+                .collect(),
         }
     }
 
-    /// Generate a unique ID for a method variant based on its name and signature.
-    pub fn generate_method_id(name: &str, signature: &Option<Pattern>) -> String {
+    /// Generate the instructions which bind the variables of a method signature to the
+    /// arguments of a call.
+    ///
+    /// A call stores its first argument in the `arg` register, which the runtime uses for
+    /// dispatch, and all of its arguments as an array in the `args` register. Signatures
+    /// with a single parameter are bound directly from `arg`, while multiple parameters
+    /// and rest patterns are destructured from `args` by their position.
+    pub fn compile_parameter_bindings(&mut self, signature: &Pattern) -> Vec<Instruction> {
         match signature {
-            Some(pattern) => format!("{}_{:?}", name, pattern),
-            None => name.to_string(),
+            Pattern::Pair(_) | Pattern::Rest(_) => {
+                let elements = signature.clone().flatten_pairs();
+                self.compile_sequence_bindings(&elements, "args".to_string())
+            }
+
+            _ => self.compile_pattern_bindings(signature, "arg".to_string()),
         }
     }
 
-    /// Convert a Pattern to a DispatchPattern for runtime matching.
-    pub fn pattern_to_dispatch_pattern(
-        pattern: &Option<Pattern>,
-        parser: &Parser,
-    ) -> DispatchPattern {
+    /// Bind the variables contained in a pattern to the value stored in the given register.
+    fn compile_pattern_bindings(&mut self, pattern: &Pattern, source: String) -> Vec<Instruction> {
         match pattern {
-            None => DispatchPattern::Any,
-            Some(Pattern::Variable(_)) => DispatchPattern::Any,
-            Some(Pattern::Value(value_pattern)) => {
-                // Try to extract a literal value
-                match &value_pattern.expression.kind {
-                    ExpressionKind::Literal(Literal::Int) => {
-                        // Get the actual integer value from the source
-                        if let Ok(lexeme) = parser.get_lexeme(
-                            value_pattern.expression.start_pos,
-                            value_pattern.expression.end_pos,
-                        ) {
-                            if let Ok(n) = lexeme.parse::<i64>() {
-                                return DispatchPattern::Value(RegisterValue::Int64(n));
-                            }
-                        }
-                        DispatchPattern::Any
-                    }
-                    ExpressionKind::Literal(Literal::Float) => {
-                        if let Ok(lexeme) = parser.get_lexeme(
-                            value_pattern.expression.start_pos,
-                            value_pattern.expression.end_pos,
-                        ) {
-                            if let Ok(n) = lexeme.parse::<f64>() {
-                                return DispatchPattern::Value(RegisterValue::Float64(n));
-                            }
-                        }
-                        DispatchPattern::Any
-                    }
-                    _ => DispatchPattern::Any,
-                }
+            Pattern::Variable(VariablePattern {
+                name: Some(name), ..
+            })
+            | Pattern::Rest(RestPattern { name: Some(name) }) => vec![Instruction::StoreLocal {
+                name: name.clone(),
+                register: source,
+            }],
+            Pattern::Tuple(tuple) => self.compile_pattern_bindings(&tuple.child, source),
+            Pattern::Field(field) => self.compile_pattern_bindings(&field.value, source),
+            Pattern::List(list) => self.compile_sequence_bindings(&list.elements, source),
+            Pattern::Pair(_) => {
+                let elements = pattern.clone().flatten_pairs();
+                self.compile_sequence_bindings(&elements, source)
+            }
+            Pattern::Variable(_) | Pattern::Rest(_) | Pattern::Value(_) => vec![],
+        }
+    }
+
+    /// Bind the variables of a sequence of patterns to the elements of the array stored in
+    /// the given register. A trailing rest pattern receives a slice of the remaining elements.
+    fn compile_sequence_bindings(
+        &mut self,
+        elements: &[Pattern],
+        source: String,
+    ) -> Vec<Instruction> {
+        let mut instructions = vec![];
+
+        for (index, element) in elements.iter().enumerate() {
+            // Skip elements like value patterns or `_` which don't bind anything.
+            if Self::extract_variable_names(element).is_empty() {
+                continue;
+            }
+
+            let index_register = self.registers.allocate_register();
+            let element_register = self.registers.allocate_register();
+
+            instructions.push(Instruction::Load {
+                value: RegisterValue::Int64(index as i64),
+                register: index_register.clone(),
+            });
+
+            if let Pattern::Rest(_) = element {
+                let length_register = self.registers.allocate_register();
+
+                instructions.push(Instruction::Length {
+                    source: source.clone(),
+                    destination: length_register.clone(),
+                });
+                instructions.push(Instruction::Slice {
+                    source: source.clone(),
+                    start: index_register,
+                    end: length_register,
+                    destination: element_register.clone(),
+                });
+            } else {
+                instructions.push(Instruction::Index {
+                    source: source.clone(),
+                    index: index_register,
+                    destination: element_register.clone(),
+                });
             }
-            _ => DispatchPattern::Any,
+
+            instructions.append(&mut self.compile_pattern_bindings(element, element_register));
+        }
+
+        instructions
+    }
+
+    /// Generate a unique ID for a method variant based on its qualifier, name, signature
+    /// and guard.
+    pub fn generate_method_id(method: &Method) -> String {
+        let id = match &method.qualifier {
+            Some(qualifier) => format!("{}_{}", qualifier, method.name),
+            None => method.name.clone(),
+        };
+
+        let id = match &method.signature {
+            Some(pattern) => format!("{}_{:?}", id, pattern),
+            None => id,
+        };
+
+        match &method.guard {
+            Some(guard) => format!("{}_where_{:?}", id, guard),
+            None => id,
         }
     }
 
+    /// Get the runtime value of a constant which has been computed at compile time.
+    pub fn constant_value(constant: &Constant) -> CompilerResult<RegisterValue> {
+        Ok(match constant {
+            Constant::Int(n) => RegisterValue::Int64(*n),
+            Constant::Float(n) => RegisterValue::Float64(*n),
+            Constant::BigInt(n) => RegisterValue::BigInt(n.clone()),
+            Constant::SizedInt(n, numeric_type) => Self::sized_int_value(*n, *numeric_type)
+                .ok_or_else(|| {
+                    CompilerError::Generic(format!(
+                        "`{}` is not a valid value of type `{}`",
+                        constant,
+                        numeric_type.name()
+                    ))
+                })?,
+            Constant::Float32(n) => RegisterValue::Float32(*n),
+            Constant::String(s) => RegisterValue::String(s.clone()),
+            Constant::Char(c) => RegisterValue::Char(c.clone()),
+            Constant::Boolean(b) => RegisterValue::Boolean(*b),
+            Constant::Nil => RegisterValue::Empty,
+        })
+    }
+
+    /// Get the runtime value of an integer of a fixed size, unless it's out of range.
+    ///
+    /// Integers of type `Int64` are `Int` constants and floats have constants of their own,
+    /// so there is no value for an integer of these types.
+    fn sized_int_value(n: i128, numeric_type: NumericType) -> Option<RegisterValue> {
+        match numeric_type {
+            NumericType::Int8 => i8::try_from(n).ok().map(RegisterValue::Int8),
+            NumericType::Int16 => i16::try_from(n).ok().map(RegisterValue::Int16),
+            NumericType::Int32 => i32::try_from(n).ok().map(RegisterValue::Int32),
+            NumericType::UInt8 => u8::try_from(n).ok().map(RegisterValue::UInt8),
+            NumericType::UInt16 => u16::try_from(n).ok().map(RegisterValue::UInt16),
+            NumericType::UInt32 => u32::try_from(n).ok().map(RegisterValue::UInt32),
+            NumericType::UInt64 => u64::try_from(n).ok().map(RegisterValue::UInt64),
+            NumericType::Int64 | NumericType::Float32 | NumericType::Float64 => None,
+        }
+    }
+
+    /// Add a method variant to the multimethod of the same name, creating the multimethod
+    /// if this is its first variant.
+    pub fn register_method(&mut self, method: Method) -> CompilerResult<()> {
+        self.multimethods
+            .entry(method.name.clone())
+            .or_insert_with(|| Multimethod::new(&method.name))
+            .add_method(&self.parser, method)
+    }
+
+    /// Define a method variant whose body consists of the given instructions instead of
+    /// compiled Mag code, like the constructors and getters generated for a class.
+    pub fn define_native_method(
+        &mut self,
+        mut method: Method,
+        instructions: Vec<Instruction>,
+        start_pos: usize,
+        end_pos: usize,
+    ) -> CompilerResult<()> {
+        method.erase_static_types();
+
+        let method_id = Self::generate_method_id(&method);
+        let parameter_names = method
+            .signature
+            .as_ref()
+            .map(Self::extract_variable_names)
+            .unwrap_or_default();
+
+        self.register_method(method.clone())?;
+        self.compiled_methods.insert(
+            method_id.clone(),
+            CompiledMethod {
+                id: method_id,
+                method_name: method.name.clone(),
+                instructions,
+                parameter_names,
+                start_pos,
+                end_pos,
+                guard: None,
+            },
+        );
+
+        Ok(())
+    }
+
     pub fn compile_expression(
         &mut self,
         expression: Expression,
@@ -215,14 +422,11 @@ impl Compiler {
         self.context.recursion_depth += 1;
 
         let mut bytecode = vec![];
-        let expression_type = expression.get_type().unwrap();
+        let node_kind = expression.node_kind();
 
-        if let Some(compilelet) = self.compilelets.get(&expression_type) {
+        if let Some(compilelet) = self.compilelets.get(&node_kind) {
             let mut compiled = compilelet.compile(self, expression, target_register)?;
 
-            // Update instruction count for call tracking
-            self.context.instruction_count += compiled.len();
-
             bytecode.append(&mut compiled);
             self.context.recursion_depth -= 1;
 
@@ -230,8 +434,8 @@ impl Compiler {
         } else {
             self.context.recursion_depth -= 1;
             Err(CompilerError::Generic(format!(
-                "No compilelet found for type {}",
-                expression_type
+                "No compilelet found for {}",
+                node_kind
             )))
         }
     }
@@ -241,29 +445,178 @@ impl Compiler {
         let tokens = self.lexer.parse();
 
         self.parser.add_tokens(source, tokens);
-        let expressions = self.parser.parse()?;
+        let mut expressions = self.parser.parse()?;
         let mut main_bytecode = vec![];
 
-        for mut expr in expressions {
-            expr.desugar();
+        for expression in expressions.iter_mut() {
+            expression.desugar();
+        }
+
+        // Report type errors before any bytecode is emitted.
+        let type_errors = TypeChecker::new(&self.parser).check(&expressions);
+
+        if !type_errors.is_empty() {
+            return Err(CompilerError::TypeErrors(type_errors));
+        }
+
+        // Type arguments and optional types aren't known at runtime, so parameters
+        // annotated with them accept any value once the program has been checked.
+        for expression in expressions.iter_mut() {
+            if let ExpressionKind::Method(method) = &mut expression.kind {
+                method.erase_static_types();
+            }
+        }
+
+        for expr in expressions {
             main_bytecode.append(&mut self.compile_expression(expr, None)?);
         }
 
+        self.check_interface_conformance()?;
+        self.check_unreachable_methods();
+        self.check_missing_variants();
+
         main_bytecode.push(Instruction::Halt);
 
+        // Expand the type tests now that all types are defined.
+        main_bytecode = self.expand_type_tests(main_bytecode);
+
+        let method_ids = self
+            .compiled_methods
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+
+        for method_id in method_ids {
+            let mut compiled_method = self.compiled_methods.remove(&method_id).unwrap();
+
+            compiled_method.instructions =
+                self.expand_type_tests(std::mem::take(&mut compiled_method.instructions));
+            compiled_method.guard = compiled_method
+                .guard
+                .take()
+                .map(|guard| self.expand_type_tests(guard));
+
+            self.compiled_methods.insert(method_id, compiled_method);
+        }
+
         // Link the bytecode: resolve CALL addresses
         let linked = self.link_bytecode(main_bytecode)?;
 
         Ok(linked)
     }
 
-    /// Link bytecode by resolving method call addresses.
+    /// Replace the label of each pending type test with the comparisons of the value's type
+    /// with the concrete types it may have.
+    ///
+    /// The result is set to `true` in advance, so that a matching type can skip the
+    /// remaining comparisons, which otherwise fall through to resetting it to `false`.
+    fn expand_type_tests(&mut self, instructions: Vec<Instruction>) -> Vec<Instruction> {
+        let mut expanded = vec![];
+
+        for instruction in instructions {
+            let test = match &instruction {
+                Instruction::LabelTarget { id } => self.pending_type_tests.remove(id),
+                _ => None,
+            };
+
+            let Some(test) = test else {
+                expanded.push(instruction);
+                continue;
+            };
+
+            let alternatives = test
+                .type_names
+                .iter()
+                .flat_map(|type_name| self.types.concrete_types(type_name))
+                .collect::<Vec<String>>();
+            let end = self.allocate_label();
+
+            expanded.push(Instruction::Load {
+                value: RegisterValue::Boolean(true),
+                register: test.result_register.clone(),
+            });
+
+            for alternative in alternatives {
+                let expected_register = self.registers.allocate_register();
+                let condition_register = self.registers.allocate_register();
+
+                expanded.push(Instruction::Load {
+                    value: RegisterValue::String(alternative),
+                    register: expected_register.clone(),
+                });
+                expanded.push(Instruction::Compare {
+                    method: ComparisonMethod::EQ,
+                    operand1: test.type_register.clone(),
+                    operand2: expected_register,
+                    destination: condition_register.clone(),
+                });
+                expanded.push(Instruction::JumpCToLabel {
+                    id: end,
+                    conditional_address: condition_register,
+                });
+            }
+
+            expanded.push(Instruction::Load {
+                value: RegisterValue::Boolean(false),
+                register: test.result_register,
+            });
+            expanded.push(Instruction::LabelTarget { id: end });
+        }
+
+        expanded
+    }
+
+    /// Report a warning for each method variant which can never be selected.
+    fn check_unreachable_methods(&mut self) {
+        for multimethod in self.multimethods.values() {
+            for method in multimethod.unreachable_methods(&self.parser, &self.types) {
+                let method_id = Self::generate_method_id(method);
+
+                if let Some(compiled_method) = self.compiled_methods.get(&method_id) {
+                    self.warnings.push(CompilerWarning::UnreachableMethod {
+                        method_name: method.name.clone(),
+                        signature: method.signature.clone(),
+                        start_pos: compiled_method.start_pos,
+                        end_pos: compiled_method.end_pos,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Report a warning for each multimethod which handles some variants of an enum but
+    /// misses others.
+    fn check_missing_variants(&mut self) {
+        for multimethod in self.multimethods.values() {
+            for (method, type_name, variants) in
+                multimethod.missing_variants(&self.parser, &self.types)
+            {
+                let method_id = Self::generate_method_id(method);
+
+                if let Some(compiled_method) = self.compiled_methods.get(&method_id) {
+                    self.warnings.push(CompilerWarning::MissingVariants {
+                        method_name: method.name.clone(),
+                        type_name,
+                        variants,
+                        start_pos: compiled_method.start_pos,
+                        end_pos: compiled_method.end_pos,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Link bytecode by resolving method call addresses and the addresses of labels.
     ///
     /// Layout:
     /// [JUMP to main start]
     /// [method 1 body][RETURN]
     /// [method 2 body][RETURN]
     /// ...
+    /// [effective methods]
+    /// [multimethod 1 dispatcher]
+    /// ...
+    /// [next method dispatchers]
     /// [main bytecode][HALT]
     fn link_bytecode(
         &mut self,
@@ -273,33 +626,186 @@ impl Compiler {
 
         // If no methods defined, just return main bytecode
         if self.compiled_methods.is_empty() {
-            return Ok(main_bytecode);
+            return Ok(self.resolve_labels(main_bytecode));
         }
 
+        // Select the variant of each call which can be resolved statically, and replace
+        // the remaining calls with a runtime dispatch.
+        let mut method_names = self.multimethods.keys().cloned().collect::<Vec<String>>();
+        method_names.sort();
+
+        // Combine the primary variants with any `before` and `after` methods which may
+        // apply to them.
+        let effective_methods = method_names
+            .iter()
+            .flat_map(|method_name| {
+                let multimethod = &self.multimethods[method_name];
+
+                multimethod
+                    .methods_by_specificity(&self.types)
+                    .into_iter()
+                    .filter_map(|primary| {
+                        EffectiveMethod::build(&self.parser, &self.types, multimethod, primary)
+                    })
+                    .collect::<Vec<EffectiveMethod>>()
+            })
+            .collect::<Vec<EffectiveMethod>>();
+
+        let effective_ids = effective_methods
+            .iter()
+            .map(|effective_method| Self::generate_method_id(&effective_method.primary))
+            .collect::<HashSet<String>>();
+
+        let mut next_dispatchers = vec![];
+        let call_targets = self
+            .pending_calls
+            .iter()
+            .map(|call| self.resolve_call(call, &effective_ids, &mut next_dispatchers))
+            .collect::<CompilerResult<Vec<Option<String>>>>()?;
+
+        let compiled_methods = self
+            .compiled_methods
+            .values()
+            .map(|compiled_method| {
+                (
+                    compiled_method.id.clone(),
+                    self.select_calls(&compiled_method.instructions, &call_targets),
+                    compiled_method
+                        .guard
+                        .as_ref()
+                        .map(|guard| self.select_calls(guard, &call_targets)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let main_bytecode = self.select_calls(&main_bytecode, &call_targets);
+
         // Calculate method addresses (in bytes)
         // First instruction is JUMP to skip methods
         let jump_size = self.instruction_size(&Instruction::Jump { destination: 0 });
         let mut method_addresses: HashMap<String, usize> = HashMap::new();
         let mut current_offset = jump_size;
 
-        // Calculate byte offset for each method and build registration info
-        self.method_registrations.clear();
-        for (method_id, compiled_method) in &self.compiled_methods {
+        // Calculate byte offset for each method and its guard clause
+        let mut guard_addresses: HashMap<String, usize> = HashMap::new();
+
+        for (method_id, instructions, guard) in &compiled_methods {
             method_addresses.insert(method_id.clone(), current_offset);
 
-            // Record this method for dispatch registration
+            for instr in instructions {
+                current_offset += self.instruction_size(instr);
+            }
+
+            // The guard clause is placed directly after the method body.
+            if let Some(guard) = guard {
+                guard_addresses.insert(method_id.clone(), current_offset);
+
+                for instr in guard {
+                    current_offset += self.instruction_size(instr);
+                }
+            }
+        }
+
+        // Compile the effective methods, which are entered instead of the primary variants
+        // they combine with auxiliary methods.
+        let mut entry_addresses = method_addresses.clone();
+        let mut combinations = vec![];
+
+        for effective_method in &effective_methods {
+            let method_id = Self::generate_method_id(&effective_method.primary);
+            let combination = self.compile_effective_method(
+                effective_method,
+                current_offset,
+                &method_addresses,
+                &guard_addresses,
+            )?;
+
+            entry_addresses.insert(method_id.clone(), current_offset);
+
+            for instr in &combination {
+                current_offset += self.instruction_size(instr);
+            }
+
+            combinations.push(combination);
+        }
+
+        for effective_id in &effective_ids {
+            method_addresses.insert(
+                Self::effective_method_id(effective_id),
+                entry_addresses[effective_id],
+            );
+        }
+
+        // Compile a dispatcher for each multimethod, which selects the matching variant
+        // at runtime, and register it under the multimethod's name. Names are sorted to
+        // keep the generated bytecode deterministic.
+        self.method_registrations.clear();
+
+        let mut dispatchers = vec![];
+
+        for method_name in method_names {
+            let tree = DecisionTree::build(
+                &self.parser,
+                &self.types,
+                &self.multimethods[&method_name].dispatch_order(&self.types),
+            );
+            let dispatcher = self.compile_dispatcher(
+                &method_name,
+                &tree,
+                current_offset,
+                &entry_addresses,
+                &guard_addresses,
+            )?;
+
             self.method_registrations.push(MethodRegistration {
-                method_name: compiled_method.method_name.clone(),
-                pattern: compiled_method.pattern.clone(),
+                method_name,
                 address: current_offset,
             });
 
-            for instr in &compiled_method.instructions {
+            for instr in &dispatcher {
+                current_offset += self.instruction_size(instr);
+            }
+
+            dispatchers.push(dispatcher);
+        }
+
+        // Compile a dispatcher over the less specific variants for each method which
+        // calls `next_method` with arguments that can only be matched at runtime.
+        for method_id in next_dispatchers {
+            let method_name = self.compiled_methods[&method_id].method_name.clone();
+            let multimethod = &self.multimethods[&method_name];
+            let tree = DecisionTree::build(
+                &self.parser,
+                &self.types,
+                &multimethod.methods_after(&self.types, &method_id),
+            );
+
+            // Primary methods delegate to the next primary method only, while `around`
+            // methods enter the effective method of the next primary method.
+            let addresses = if multimethod.is_primary_method(&method_id) {
+                &method_addresses
+            } else {
+                &entry_addresses
+            };
+
+            let dispatcher = self.compile_dispatcher(
+                &method_name,
+                &tree,
+                current_offset,
+                addresses,
+                &guard_addresses,
+            )?;
+
+            method_addresses.insert(Self::next_dispatcher_id(&method_id), current_offset);
+
+            for instr in &dispatcher {
                 current_offset += self.instruction_size(instr);
             }
+
+            dispatchers.push(dispatcher);
         }
 
-        // Main bytecode starts after all methods
+        // Main bytecode starts after all methods and dispatchers
         let main_start = current_offset;
 
         // Build final bytecode
@@ -308,45 +814,145 @@ impl Compiler {
             destination: main_start as u32,
         });
 
-        // 2. All method bodies
-        for (_, compiled_method) in &self.compiled_methods {
-            linked.extend(compiled_method.instructions.clone());
+        // 2. All method bodies, each followed by its guard clause
+        for (_, instructions, guard) in compiled_methods {
+            linked.extend(Self::patch_calls(
+                instructions,
+                &call_targets,
+                &method_addresses,
+            )?);
+
+            if let Some(guard) = guard {
+                linked.extend(Self::patch_calls(guard, &call_targets, &method_addresses)?);
+            }
+        }
+
+        // 3. The effective methods combining primary and auxiliary methods
+        for combination in combinations {
+            linked.extend(combination);
         }
 
-        // 3. Main bytecode with patched CALL addresses
-        for (i, instr) in main_bytecode.into_iter().enumerate() {
-            match instr {
-                Instruction::Call { address: 0 } => {
-                    // Find the pending call for this index
-                    let call_index = i;
-                    if let Some(pending) = self
-                        .pending_calls
-                        .iter()
-                        .find(|p| p.instruction_index == call_index)
-                    {
-                        // Look up the method's byte address
-                        if let Some(&byte_addr) = method_addresses.get(&pending.target_method_id) {
-                            linked.push(Instruction::Call { address: byte_addr });
-                        } else {
-                            // Method not found - keep placeholder for debugging
-                            linked.push(Instruction::Call { address: 0 });
-                        }
-                    } else {
-                        // No pending call record - keep placeholder
-                        linked.push(Instruction::Call { address: 0 });
-                    }
-                }
-                _ => linked.push(instr),
+        // 4. The dispatchers of each multimethod and of `next_method` calls
+        for dispatcher in dispatchers {
+            linked.extend(dispatcher);
+        }
+
+        // 5. Main bytecode with patched CALL addresses
+        linked.extend(Self::patch_calls(
+            main_bytecode,
+            &call_targets,
+            &method_addresses,
+        )?);
+
+        Ok(self.resolve_labels(linked))
+    }
+
+    /// Replace the jumps to labels in linked bytecode with jumps to the addresses of the
+    /// labels, which are removed.
+    fn resolve_labels(&self, instructions: Vec<Instruction>) -> Vec<Instruction> {
+        let mut addresses = HashMap::new();
+        let mut offset = 0;
+
+        for instruction in &instructions {
+            if let Instruction::LabelTarget { id } = instruction {
+                addresses.insert(*id, offset as u32);
             }
+
+            offset += self.instruction_size(instruction);
         }
 
-        Ok(linked)
+        instructions
+            .into_iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::LabelTarget { .. } => None,
+                Instruction::JumpToLabel { id } => Some(Instruction::Jump {
+                    destination: addresses[&id],
+                }),
+                Instruction::JumpCToLabel {
+                    id,
+                    conditional_address,
+                } => Some(Instruction::JumpC {
+                    destination: addresses[&id],
+                    conditional_address,
+                }),
+                instruction => Some(instruction),
+            })
+            .collect()
+    }
+
+    /// Replace each CALL which couldn't be resolved to a single variant with a DISPATCH.
+    fn select_calls(
+        &self,
+        instructions: &[Instruction],
+        call_targets: &[Option<String>],
+    ) -> Vec<Instruction> {
+        instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Call { address } if call_targets[*address].is_none() => {
+                    Instruction::Dispatch {
+                        method_name: self.pending_calls[*address].method_name.clone(),
+                    }
+                }
+                _ => instruction.clone(),
+            })
+            .collect()
+    }
+
+    /// Replace the placeholder address of each remaining CALL with the address of its
+    /// target variant.
+    fn patch_calls(
+        instructions: Vec<Instruction>,
+        call_targets: &[Option<String>],
+        method_addresses: &HashMap<String, usize>,
+    ) -> CompilerResult<Vec<Instruction>> {
+        instructions
+            .into_iter()
+            .map(|instruction| match instruction {
+                // Calls without a target have already been replaced with a DISPATCH.
+                Instruction::Call { address } => Ok(Instruction::Call {
+                    address: Self::address_of(
+                        method_addresses,
+                        call_targets[address].as_deref().unwrap_or_default(),
+                    )?,
+                }),
+                _ => Ok(instruction),
+            })
+            .collect()
+    }
+
+    /// Look up the address at which a method variant, or the routine linked under the given
+    /// key, has been placed.
+    ///
+    /// Address 0 is the jump to the main program, so a missing address is an error instead
+    /// of being replaced with a default.
+    fn address_of(addresses: &HashMap<String, usize>, method_id: &str) -> CompilerResult<usize> {
+        addresses
+            .get(method_id)
+            .copied()
+            .ok_or_else(|| CompilerError::UnlinkedMethod(method_id.to_string()))
     }
 
     /// Calculate the byte size of an instruction when encoded.
+    ///
+    /// Labels are removed when the bytecode is linked, and jumps to them are replaced by
+    /// jumps of the same size.
     fn instruction_size(&self, instr: &Instruction) -> usize {
+        let instr = match instr {
+            Instruction::LabelTarget { .. } => return 0,
+            Instruction::JumpToLabel { .. } => Instruction::Jump { destination: 0 },
+            Instruction::JumpCToLabel {
+                conditional_address,
+                ..
+            } => Instruction::JumpC {
+                destination: 0,
+                conditional_address: conditional_address.clone(),
+            },
+            instr => instr.clone(),
+        };
+
         // Convert to bytes and measure length
-        let bytes: Vec<u8> = instr.clone().into();
+        let bytes: Vec<u8> = instr.into();
         bytes.len()
     }
 
@@ -354,11 +960,177 @@ impl Compiler {
         self.multimethods.get(name)
     }
 
-    /// Record a pending call that needs address resolution.
-    pub fn add_pending_call(&mut self, instruction_index: usize, target_method_id: String) {
-        self.pending_calls.push(PendingCall {
-            instruction_index,
-            target_method_id,
-        });
+    /// Allocate a label to jump to within the code of an expression, whose address is only
+    /// known once the bytecode is linked.
+    pub fn allocate_label(&mut self) -> usize {
+        self.labels += 1;
+        self.labels
+    }
+
+    /// Record a call which may be resolved statically, returning the placeholder address
+    /// of its CALL instruction.
+    pub fn add_pending_call(&mut self, call: PendingCall) -> usize {
+        self.pending_calls.push(call);
+
+        self.pending_calls.len() - 1
+    }
+
+    /// Find the variant a pending call is linked to, or `None` if it needs to be dispatched
+    /// at runtime.
+    ///
+    /// Calls to `next_method` which can't be resolved statically are linked to a dispatcher
+    /// for the less specific variants of the caller, which is registered in `next_dispatchers`.
+    fn resolve_call(
+        &self,
+        call: &PendingCall,
+        effective_ids: &HashSet<String>,
+        next_dispatchers: &mut Vec<String>,
+    ) -> CompilerResult<Option<String>> {
+        let multimethod = match self.multimethods.get(&call.method_name) {
+            Some(multimethod) => multimethod,
+            None => return Ok(None),
+        };
+
+        let candidates = match &call.next_after {
+            Some(method_id) => multimethod.methods_after(&self.types, method_id),
+            None => {
+                multimethod.check_ambiguity(&self.parser, &self.types, &call.arguments)?;
+                multimethod.dispatch_order(&self.types)
+            }
+        };
+
+        if let Some(method) = Multimethod::select_statically(
+            &self.parser,
+            &self.types,
+            candidates.clone(),
+            &call.arguments,
+        ) {
+            let method_id = Self::generate_method_id(method);
+
+            // Primary variants combined with auxiliary methods are entered through their
+            // effective method, unless they're the next method of another primary variant.
+            let is_next_primary = call
+                .next_after
+                .as_ref()
+                .is_some_and(|caller_id| multimethod.is_primary_method(caller_id));
+
+            if effective_ids.contains(&method_id) && !is_next_primary {
+                return Ok(Some(Self::effective_method_id(&method_id)));
+            }
+
+            return Ok(Some(method_id));
+        }
+
+        match &call.next_after {
+            Some(_) if candidates.is_empty() => Err(CompilerError::NoNextMethod {
+                method_name: call.method_name.clone(),
+                arguments: call.arguments.clone(),
+            }),
+            Some(method_id) => {
+                if !next_dispatchers.contains(method_id) {
+                    next_dispatchers.push(method_id.clone());
+                }
+
+                Ok(Some(Self::next_dispatcher_id(method_id)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the key under which the effective method of a primary variant is linked.
+    fn effective_method_id(method_id: &str) -> String {
+        format!("{}_effective", method_id)
+    }
+
+    /// Get the key under which the `next_method` dispatcher of a variant is linked.
+    fn next_dispatcher_id(method_id: &str) -> String {
+        format!("{}_next", method_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compiler;
+    use crate::type_system::NumericType;
+    use crate::types::{CompilerError, CompilerResult, Constant};
+
+    use strontium::machine::instruction::Instruction;
+    use strontium::machine::register::RegisterValue;
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    /// Find the instruction which starts at the given byte address of linked bytecode.
+    fn instruction_at(bytecode: &[Instruction], address: usize) -> Option<&Instruction> {
+        let mut offset = 0;
+
+        for instruction in bytecode {
+            if offset == address {
+                return Some(instruction);
+            }
+
+            let bytes: Vec<u8> = instruction.clone().into();
+            offset += bytes.len();
+        }
+
+        None
+    }
+
+    /// Find the first instruction of each routine entered by a CALL in linked bytecode.
+    fn call_targets(bytecode: &[Instruction]) -> Vec<&Instruction> {
+        bytecode
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => instruction_at(bytecode, *address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Check whether the bytecode contains a runtime dispatch on the given multimethod.
+    fn dispatches(bytecode: &[Instruction], name: &str) -> bool {
+        bytecode.iter().any(|instruction| {
+            matches!(instruction, Instruction::Dispatch { method_name } if method_name == name)
+        })
+    }
+
+    #[test]
+    fn calls_are_linked_to_the_selected_variant() {
+        let bytecode = compile("def f(n Int) n def f(s String) s f(1)").unwrap();
+        let targets = call_targets(&bytecode);
+
+        assert!(!dispatches(&bytecode, "f"));
+        assert!(targets.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, .. } if name == "n"
+        )));
+        assert!(!targets.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::StoreLocal { name, .. } if name == "s"
+        )));
+    }
+
+    #[test]
+    fn guarded_variants_are_selected_at_runtime() {
+        let bytecode = compile("def f(n Int) where n > 0 n def f(x) 0 f(1)").unwrap();
+
+        assert!(dispatches(&bytecode, "f"));
+    }
+
+    #[test]
+    fn sized_constants_keep_their_type() {
+        assert!(matches!(
+            Compiler::constant_value(&Constant::SizedInt(255, NumericType::UInt8)),
+            Ok(RegisterValue::UInt8(255))
+        ));
+        assert!(matches!(
+            Compiler::constant_value(&Constant::SizedInt(-129, NumericType::Int8)),
+            Err(CompilerError::Generic(_))
+        ));
+        assert!(matches!(
+            Compiler::constant_value(&Constant::SizedInt(1, NumericType::Float32)),
+            Err(CompilerError::Generic(_))
+        ));
     }
 }
@@ -1,6 +1,10 @@
+use crate::compiler::{Compiler, TypeSystem};
 use crate::parser::Parser;
-use crate::types::Method;
-use crate::types::{CompilerError, CompilerResult, Pattern};
+use crate::type_system::{Type, Typed};
+use crate::types::{CompilerError, CompilerResult, ParserError, Pattern, ValuePattern};
+use crate::types::{Method, MethodQualifier};
+
+use std::cmp::Ordering;
 
 /// A collection of methods with different function signatures which share a common name.
 #[derive(Debug, Clone)]
@@ -9,6 +13,9 @@ pub struct Multimethod {
     pub name: String,
     /// Contains pairs of method signatures and bodies.
     pub methods: Vec<Method>,
+    /// Contains the `before`, `after` and `around` methods, which are combined with the
+    /// primary methods above when the multimethod is called.
+    pub auxiliary_methods: Vec<Method>,
 }
 
 impl Multimethod {
@@ -16,31 +23,119 @@ impl Multimethod {
         Self {
             name: String::from(name),
             methods: vec![],
+            auxiliary_methods: vec![],
         }
     }
 
-    pub fn linearize(&self, parser: &Parser, pattern: Option<Pattern>) -> CompilerResult<Method> {
+    /// Find all methods whose signature matches the given call pattern.
+    ///
+    /// The methods are ordered from the most to the least specific signature. Methods with
+    /// a guard clause are placed before unguarded methods of equal specificity, since their
+    /// guard needs to be checked before falling back to the more general variant. Typed
+    /// parameters are ordered by the given type system, so a variant for a class is tried
+    /// before one for an interface it implements. If two applicable methods can't be
+    /// ordered, for example `f(0, b)` and `f(a, 0)` for the call `f(0, 0)`, an
+    /// [`CompilerError::AmbiguousMethod`] error is returned.
+    pub fn applicable_methods(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        pattern: &Option<Pattern>,
+    ) -> CompilerResult<Vec<&Method>> {
         let mut matching_methods = vec![];
 
         for method in &self.methods {
             match (pattern.clone(), method.signature.clone()) {
-                (None, None) => matching_methods.push((method, 0)),
+                (None, None) => matching_methods.push(method),
+                // A lone rest pattern like `def f(...args)` also accepts calls without arguments.
+                (None, Some(Pattern::Rest(_))) => matching_methods.push(method),
                 (Some(p), Some(s)) => {
                     if s.matches_with(parser, p.clone()) {
-                        matching_methods.push((method, p.get_precedence()));
+                        matching_methods.push(method);
                     }
                 }
                 (Some(..), None) | (None, Some(..)) => {}
             }
         }
 
-        // Sort the resulting method signatures by their pattern's precedence.
-        matching_methods.sort_by(|m1, m2| m1.1.cmp(&m2.1));
+        self.order_by_specificity(types, matching_methods, true)
+    }
+
+    /// Get all methods of this multimethod, ordered from the most to the least specific.
+    ///
+    /// Unlike [`Multimethod::applicable_methods`], this doesn't fail for incomparable methods,
+    /// which keep the order in which they were defined.
+    pub fn methods_by_specificity(&self, types: &TypeSystem) -> Vec<&Method> {
+        self.order_by_specificity(types, self.methods.iter().collect(), false)
+            .unwrap_or_default()
+    }
+
+    /// Get the auxiliary methods with the given qualifier, ordered from the most to the least
+    /// specific.
+    pub fn auxiliary_methods_by_specificity(
+        &self,
+        types: &TypeSystem,
+        qualifier: MethodQualifier,
+    ) -> Vec<&Method> {
+        let methods = self
+            .auxiliary_methods
+            .iter()
+            .filter(|method| method.qualifier == Some(qualifier))
+            .collect();
+
+        self.order_by_specificity(types, methods, false)
+            .unwrap_or_default()
+    }
 
-        if matching_methods.len() > 0 {
-            let (linearized_method, _precedence) = matching_methods[0];
+    /// Get the order in which variants are tried when the multimethod is called, which
+    /// consists of all `around` methods followed by the primary methods.
+    pub fn dispatch_order(&self, types: &TypeSystem) -> Vec<&Method> {
+        let mut methods = self.auxiliary_methods_by_specificity(types, MethodQualifier::Around);
+        methods.append(&mut self.methods_by_specificity(types));
 
-            Ok(linearized_method.clone())
+        methods
+    }
+
+    /// Get the variants which are tried after the one with the given ID, which are the
+    /// candidates for a call to `next_method` within its body.
+    ///
+    /// An `around` method delegates to the next `around` method or the primary methods,
+    /// while a primary method only delegates to less specific primary methods.
+    pub fn methods_after(&self, types: &TypeSystem, method_id: &str) -> Vec<&Method> {
+        self.dispatch_order(types)
+            .into_iter()
+            .skip_while(|method| Compiler::generate_method_id(method) != method_id)
+            .skip(1)
+            .collect()
+    }
+
+    /// Check whether the variant with the given ID is one of the primary methods.
+    pub fn is_primary_method(&self, method_id: &str) -> bool {
+        self.methods
+            .iter()
+            .any(|method| Compiler::generate_method_id(method) == method_id)
+    }
+
+    pub fn linearize(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        pattern: Option<Pattern>,
+    ) -> CompilerResult<Method> {
+        if let Some(linearized_method) = self.applicable_methods(parser, types, &pattern)?.first() {
+            // Generic methods are instantiated with the statically known argument types.
+            let arguments = pattern
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default()
+                .iter()
+                .map(|argument| match argument {
+                    Pattern::Value(ValuePattern { expression }) => expression.get_type(),
+                    _ => Type::Unknown,
+                })
+                .collect::<Vec<Type>>();
+
+            Ok(linearized_method.instantiate(&arguments))
         } else {
             Err(CompilerError::MethodSignatureNotFound {
                 method_name: self.name.clone(),
@@ -50,31 +145,395 @@ impl Multimethod {
     }
 
     pub fn add_method(&mut self, parser: &Parser, method: Method) -> CompilerResult<()> {
+        // Value patterns are compared by their value, so it needs to be known at compile time.
+        if let Some(signature) = &method.signature {
+            if let Err(ParserError::NotConstant(expression)) =
+                signature.expect_constant_values(parser)
+            {
+                return Err(CompilerError::NonConstantPattern {
+                    method_name: method.name,
+                    expression,
+                });
+            }
+        }
+
         // Break out early if the method already exists.
-        for m in &self.methods {
-            match [m.signature.clone(), method.signature.clone()] {
-                [Some(p1), Some(p2)] => {
-                    if p1.matches_with(parser, p2.clone()) {
-                        return Err(CompilerError::DuplicateMethodSignature {
-                            method_name: method.name,
-                            signature: method.signature,
-                        });
-                    }
-                }
-                // no match.
-                [Some(_), None] | [None, Some(_)] => {}
-                [None, None] => {
-                    println!("MATCH!");
-
-                    return Err(CompilerError::DuplicateMethodSignature {
-                        method_name: method.name,
-                        signature: method.signature,
-                    });
-                }
+        let methods = match method.qualifier {
+            Some(_) => &self.auxiliary_methods,
+            None => &self.methods,
+        };
+
+        for m in methods {
+            // Guards can't be compared, so guarded variants are never considered duplicates.
+            if m.guard.is_some() || method.guard.is_some() || m.qualifier != method.qualifier {
+                continue;
+            }
+
+            if Self::is_same_signature(parser, &m.signature, &method.signature) {
+                return Err(CompilerError::DuplicateMethodSignature {
+                    method_name: method.name,
+                    signature: method.signature,
+                });
+            }
+        }
+
+        match method.qualifier {
+            Some(_) => self.auxiliary_methods.push(method),
+            None => self.methods.push(method),
+        }
+
+        Ok(())
+    }
+
+    /// Compare the specificity of two method signatures, treating them as sequences of
+    /// parameters so that `def f(x)` is considered more specific than `def f(...xs)`.
+    pub fn compare_signatures(
+        types: &TypeSystem,
+        reference: &Option<Pattern>,
+        given: &Option<Pattern>,
+    ) -> Option<Ordering> {
+        Pattern::compare_sequences(
+            types,
+            &reference
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default(),
+            &given
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Check whether two signatures accept exactly the same arguments.
+    fn is_same_signature(
+        parser: &Parser,
+        reference: &Option<Pattern>,
+        given: &Option<Pattern>,
+    ) -> bool {
+        Pattern::are_equivalent_sequences(
+            parser,
+            &reference
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default(),
+            &given
+                .clone()
+                .map(Pattern::flatten_pairs)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Sort methods from the most to the least specific signature.
+    ///
+    /// Since specificity is only a partial order, we repeatedly pick a method which isn't
+    /// less specific than any of the remaining ones. If there are several such methods and
+    /// they can't be compared with each other, the choice is ambiguous, which results in an
+    /// error if `strict` is set, or falls back to the definition order otherwise.
+    fn order_by_specificity<'a>(
+        &self,
+        types: &TypeSystem,
+        mut remaining: Vec<&'a Method>,
+        strict: bool,
+    ) -> CompilerResult<Vec<&'a Method>> {
+        let mut ordered = vec![];
+
+        while !remaining.is_empty() {
+            let most_specific = Self::most_specific(types, &remaining);
+
+            if strict {
+                self.expect_unambiguous(types, &remaining, &most_specific)?;
             }
+
+            // Prefer guarded variants over unguarded ones of the same specificity.
+            let index = most_specific
+                .iter()
+                .copied()
+                .find(|&i| remaining[i].guard.is_some())
+                .or(most_specific.first().copied())
+                .unwrap_or(0);
+
+            ordered.push(remaining.remove(index));
+        }
+
+        Ok(ordered)
+    }
+
+    /// Ensure that a call with the given arguments isn't ambiguous, since the most specific
+    /// of the variants which are guaranteed to accept them can't be ordered, like
+    /// `f(a Int, b)` and `f(a, b Int)` for the call `f(1, 2)`.
+    pub fn check_ambiguity(
+        &self,
+        parser: &Parser,
+        types: &TypeSystem,
+        arguments: &Option<Pattern>,
+    ) -> CompilerResult<()> {
+        let methods = self.accepting_methods(parser, types, arguments);
+
+        self.expect_unambiguous(types, &methods, &Self::most_specific(types, &methods))
+    }
+
+    /// Get the indices of the methods which no other method is more specific than.
+    fn most_specific(types: &TypeSystem, methods: &[&Method]) -> Vec<usize> {
+        (0..methods.len())
+            .filter(|&i| {
+                methods.iter().all(|other| {
+                    Self::compare_signatures(types, &other.signature, &methods[i].signature)
+                        != Some(Ordering::Greater)
+                })
+            })
+            .collect()
+    }
+
+    /// Return an error if any two of the most specific methods can't be ordered.
+    fn expect_unambiguous(
+        &self,
+        types: &TypeSystem,
+        methods: &[&Method],
+        most_specific: &[usize],
+    ) -> CompilerResult<()> {
+        let is_ambiguous = most_specific.iter().any(|&i| {
+            most_specific.iter().any(|&j| {
+                Self::compare_signatures(types, &methods[i].signature, &methods[j].signature)
+                    .is_none()
+            })
+        });
+
+        if is_ambiguous {
+            return Err(CompilerError::AmbiguousMethod {
+                method_name: self.name.clone(),
+                signatures: most_specific
+                    .iter()
+                    .map(|&i| methods[i].signature.clone())
+                    .collect(),
+            });
         }
-        self.methods.push(method);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Multimethod;
+    use crate::compiler::{Compiler, TypeDefinition, TypeKind, TypeSystem};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{CompilerError, CompilerResult, ExpressionKind, Pattern};
+
+    use strontium::machine::instruction::Instruction;
+
+    /// Add all method definitions to a new multimethod, returning the result of adding them
+    /// and the last call's signature.
+    fn try_define(
+        name: &str,
+        source: &str,
+    ) -> (Parser, Multimethod, CompilerResult<()>, Option<Pattern>) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let mut multimethod = Multimethod::new(name);
+        let mut result = Ok(());
+        let mut call = None;
+
+        for expression in parser.parse().unwrap() {
+            match expression.kind {
+                ExpressionKind::Method(method) => {
+                    result = result.and(multimethod.add_method(&parser, method));
+                }
+                ExpressionKind::Call(c) => call = c.signature,
+                _ => {}
+            }
+        }
+
+        (parser, multimethod, result, call)
+    }
+
+    fn compile(source: &str) -> CompilerResult<Vec<Instruction>> {
+        Compiler::new().compile(source.to_string())
+    }
+
+    #[test]
+    fn most_specific_variant_wins() {
+        let (parser, multimethod, result, call) =
+            try_define("fib", "def fib(n) n def fib(n Int) n def fib(0) 0 fib(0)");
+
+        assert!(result.is_ok());
+
+        let methods = multimethod
+            .applicable_methods(&parser, &TypeSystem::new(), &call)
+            .unwrap();
+        let signatures = methods
+            .iter()
+            .map(|method| format!("{}", method.signature.clone().unwrap()))
+            .collect::<Vec<String>>();
+
+        assert!(matches!(methods[0].signature, Some(Pattern::Value(_))));
+        assert_eq!(signatures[1..], ["n: Int".to_string(), "n".to_string()]);
+    }
+
+    #[test]
+    fn incomparable_variants_are_ambiguous() {
+        let (parser, multimethod, result, call) =
+            try_define("f", "def f(0, b) b def f(a, 0) a f(0, 0)");
+
+        assert!(result.is_ok());
+        assert!(matches!(
+            multimethod.applicable_methods(&parser, &TypeSystem::new(), &call),
+            Err(CompilerError::AmbiguousMethod { .. })
+        ));
+    }
+
+    #[test]
+    fn ambiguous_call_is_a_compile_error() {
+        let result = compile("def f(a Int, b) 1 def f(a, b Int) 2 f(1, 2)");
+
+        assert!(matches!(
+            result,
+            Err(CompilerError::AmbiguousMethod { method_name, .. }) if method_name == "f"
+        ));
+        assert!(compile("def f(a Int, b) 1 def f(a, b Int) 2 f(1, \"b\")").is_ok());
+    }
+
+    #[test]
+    fn more_specific_signature_is_not_a_duplicate() {
+        let (_, _, result, _) = try_define("f", "def f(n) n def f(n Int) n");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn renamed_variable_is_a_duplicate() {
+        let (_, _, result, _) = try_define("f", "def f(n Int) n def f(m Int) m");
+
+        assert!(matches!(
+            result,
+            Err(CompilerError::DuplicateMethodSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn sized_alias_is_a_duplicate() {
+        let (_, _, result, _) = try_define("f", "def f(x Int) x def f(x Int64) x");
+
+        assert!(matches!(
+            result,
+            Err(CompilerError::DuplicateMethodSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn type_arguments_are_ignored_by_duplicates() {
+        let (_, _, result, _) = try_define("f", "def f(l List[Int]) l def f(l List[String]) l");
+
+        assert!(matches!(
+            result,
+            Err(CompilerError::DuplicateMethodSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn equal_values_are_duplicates() {
+        let (_, _, result, _) = try_define("f", "def f(1) 1 def f(1.0) 1");
+
+        assert!(matches!(
+            result,
+            Err(CompilerError::DuplicateMethodSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn value_patterns_match_equal_constants() {
+        for call in ["f(1)", "f(1.0)", "f(0x1)", "f(2 - 1)"] {
+            let (parser, multimethod, result, call) =
+                try_define("f", &format!("def f(1) 1 def f(n) n {}", call));
+
+            assert!(result.is_ok());
+
+            let methods = multimethod
+                .applicable_methods(&parser, &TypeSystem::new(), &call)
+                .unwrap();
+
+            assert!(matches!(methods[0].signature, Some(Pattern::Value(_))));
+        }
+    }
+
+    #[test]
+    fn methods_after_skips_more_specific_variants() {
+        let (parser, multimethod, _, _) =
+            try_define("f", "def f(n) 1 def f(0) next_method() def f(n Int) 2");
+
+        let zero = multimethod
+            .methods
+            .iter()
+            .find(|method| matches!(method.signature, Some(Pattern::Value(_))))
+            .unwrap();
+        let method_id = Compiler::generate_method_id(zero);
+
+        let types = TypeSystem::new();
+        let candidates = multimethod.methods_after(&types, &method_id);
+        let next =
+            Multimethod::select_statically(&parser, &types, candidates.clone(), &zero.signature);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            format!("{}", next.unwrap().signature.clone().unwrap()),
+            "n: Int"
+        );
+    }
+
+    #[test]
+    fn guarded_variants_come_first() {
+        let (parser, multimethod, result, call) = try_define(
+            "classify",
+            "def classify(n) \"positive\" def classify(n) where n < 0 \"negative\" classify(1)",
+        );
+
+        assert!(result.is_ok());
+
+        let methods = multimethod
+            .applicable_methods(&parser, &TypeSystem::new(), &call)
+            .unwrap();
+
+        assert_eq!(methods.len(), 2);
+        assert!(methods[0].guard.is_some());
+        assert!(methods[1].guard.is_none());
+    }
+
+    #[test]
+    fn subtypes_are_more_specific() {
+        let (_, multimethod, result, _) = try_define(
+            "area",
+            "def area(s Shape) 0 def area(s) 1 def area(c Circle) 2",
+        );
+        let mut types = TypeSystem::new();
+
+        types
+            .define(TypeDefinition {
+                name: "Shape".to_string(),
+                kind: TypeKind::Interface { methods: vec![] },
+                supertypes: vec![],
+            })
+            .unwrap();
+        types
+            .define(TypeDefinition {
+                name: "Circle".to_string(),
+                kind: TypeKind::Class { fields: vec![] },
+                supertypes: vec!["Shape".to_string()],
+            })
+            .unwrap();
+
+        assert!(result.is_ok());
+
+        let signatures = multimethod
+            .methods_by_specificity(&types)
+            .iter()
+            .map(|method| format!("{}", method.signature.clone().unwrap()))
+            .collect::<Vec<String>>();
+
+        assert_eq!(signatures, ["c: Circle", "s: Shape", "s"]);
+    }
+}
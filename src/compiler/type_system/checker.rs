@@ -0,0 +1,1030 @@
+//! Infer the types of expressions and check them before any bytecode is emitted.
+//!
+//! The types of literals are known right away, and the types of local variables follow
+//! from the annotations in a method's signature. The type of a call is the return type
+//! shared by all variants which may accept its arguments, which is either declared or
+//! inferred from their bodies. The type parameters of generic variants are inferred from
+//! the arguments of each call. Values which may be `nil` need to be handled before they
+//! are passed to a method which doesn't accept `nil`. Within the branches of a type test
+//! like `if x is Int then`, the type of the variable is narrowed accordingly. Anything
+//! else is left as [`Type::Unknown`] and checked at runtime.
+
+use crate::compiler::{Builtin, Compiler, TypeDefinition, TypeSystem};
+use crate::parser::Parser;
+use crate::type_system::{NumericType, Type, TypeError, Typed};
+use crate::types::{
+    Call, Constant, Expression, ExpressionKind, Literal, Method, Pattern, Prefix, TokenKind,
+    TypeExpression, ValuePattern, VariablePattern,
+};
+
+use std::collections::{HashMap, HashSet};
+
+/// The types of the local variables which are in scope.
+type Locals = HashMap<String, Type>;
+
+/// A variant of a multimethod as far as the checker is concerned.
+#[derive(Debug, Clone)]
+enum Variant {
+    /// A method defined in the program, whose return type is inferred from its body.
+    Defined(Method),
+    /// A built-in method, or a constructor or getter generated for a class, whose return
+    /// type is known.
+    Native {
+        signature: Option<Pattern>,
+        type_parameters: Vec<String>,
+        result: Type,
+    },
+}
+
+impl Variant {
+    fn signature(&self) -> &Option<Pattern> {
+        match self {
+            Variant::Defined(method) => &method.signature,
+            Variant::Native { signature, .. } => signature,
+        }
+    }
+
+    fn type_parameters(&self) -> &[String] {
+        match self {
+            Variant::Defined(method) => &method.type_parameters,
+            Variant::Native {
+                type_parameters, ..
+            } => type_parameters,
+        }
+    }
+}
+
+/// Checks the types of a whole program, collecting all errors instead of stopping at the
+/// first one.
+pub struct TypeChecker<'a> {
+    parser: &'a Parser,
+    /// The types defined by the program.
+    types: TypeSystem,
+    /// The variants of each multimethod, including those generated for classes.
+    variants: HashMap<String, Vec<Variant>>,
+    /// The return types inferred for method variants, by their ID.
+    return_types: HashMap<String, Type>,
+    /// The variants whose return type is being inferred, which are assumed to return
+    /// a value of unknown type when they call themselves.
+    in_progress: HashSet<String>,
+    errors: Vec<TypeError>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(parser: &'a Parser) -> Self {
+        let mut checker = Self {
+            parser,
+            types: TypeSystem::new(),
+            variants: HashMap::new(),
+            return_types: HashMap::new(),
+            in_progress: HashSet::new(),
+            errors: vec![],
+        };
+
+        for builtin in Builtin::all() {
+            checker.add_variant(
+                builtin.name,
+                Variant::Native {
+                    signature: builtin.signature(),
+                    type_parameters: builtin.type_parameters(),
+                    result: builtin.result,
+                },
+            );
+        }
+
+        checker
+    }
+
+    /// Check the given desugared expressions, returning all type errors found in them.
+    pub fn check(mut self, expressions: &[Expression]) -> Vec<TypeError> {
+        for expression in expressions {
+            self.declare(expression);
+        }
+
+        for expression in expressions {
+            match &expression.kind {
+                ExpressionKind::Method(method) => {
+                    self.return_type(method);
+                }
+                _ => {
+                    self.infer(expression, &Locals::new());
+                }
+            }
+        }
+
+        self.errors
+    }
+
+    /// Register the types and method variants defined by a top-level expression, so they
+    /// can be used before their definition.
+    fn declare(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Method(method) => {
+                self.add_variant(&method.name, Variant::Defined(method.clone()))
+            }
+
+            ExpressionKind::Class(class) => {
+                self.declare_class(TypeDefinition::from(class.clone()), expression)
+            }
+
+            ExpressionKind::Enum(definition) => {
+                self.define_type(TypeDefinition::from(definition.clone()), expression);
+
+                for variant in &definition.variants {
+                    let mut variant = TypeDefinition::from(variant.clone());
+                    variant.supertypes.insert(0, definition.name.clone());
+
+                    self.declare_class(variant, expression);
+                }
+            }
+
+            ExpressionKind::Interface(interface) => {
+                self.define_type(TypeDefinition::from(interface.clone()), expression);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Register a class along with its constructor and the getters of its fields.
+    fn declare_class(&mut self, definition: TypeDefinition, expression: &Expression) {
+        self.add_variant(
+            &definition.name,
+            Variant::Native {
+                signature: definition.constructor_signature(),
+                type_parameters: vec![],
+                result: Type::named(&definition.name),
+            },
+        );
+
+        for field in definition.fields() {
+            self.add_variant(
+                &field.name.clone().unwrap_or_default(),
+                Variant::Native {
+                    signature: Some(Pattern::Variable(VariablePattern {
+                        name: Some(String::from("this")),
+                        type_id: Some(TypeExpression::named(&definition.name)),
+                    })),
+                    type_parameters: vec![],
+                    result: field.type_id.as_ref().map_or(Type::Unknown, Type::from),
+                },
+            );
+        }
+
+        self.define_type(definition, expression);
+    }
+
+    /// Add a type to the type system, reporting it if a type of the same name exists.
+    fn define_type(&mut self, definition: TypeDefinition, expression: &Expression) {
+        let name = definition.name.clone();
+
+        if self.types.define(definition).is_err() {
+            self.errors.push(TypeError::DuplicateType {
+                name,
+                start_pos: expression.start_pos,
+                end_pos: expression.end_pos,
+            });
+        }
+    }
+
+    fn add_variant(&mut self, name: &str, variant: Variant) {
+        self.variants
+            .entry(name.to_string())
+            .or_default()
+            .push(variant);
+    }
+
+    /// Get the type of the value returned by a method variant, checking its body and guard.
+    ///
+    /// The type is inferred from the body unless the method declares it, in which case the
+    /// body is checked against it. It may refer to the type parameters of the method.
+    fn return_type(&mut self, method: &Method) -> Type {
+        let method_id = Compiler::generate_method_id(method);
+
+        if let Some(result) = self.return_types.get(&method_id) {
+            return result.clone();
+        }
+
+        if !self.in_progress.insert(method_id.clone()) {
+            return Type::Unknown;
+        }
+
+        let mut locals = Locals::new();
+
+        if let Some(signature) = &method.signature {
+            Self::bind(signature, &method.type_parameters, &mut locals);
+        }
+
+        if let Some(guard) = &method.guard {
+            self.expect(guard, &locals, Type::Boolean);
+        }
+
+        let result = match &method.return_type {
+            Some(return_type) => {
+                let declared = Type::from_annotation(return_type, &method.type_parameters);
+                self.expect(&method.body, &locals, declared.clone());
+
+                declared
+            }
+            None => self.infer(&method.body, &locals),
+        };
+
+        self.in_progress.remove(&method_id);
+        self.return_types.insert(method_id, result.clone());
+
+        result
+    }
+
+    /// Add the variables bound by a pattern in a signature to the locals, whose annotations
+    /// may refer to the given type parameters.
+    fn bind(pattern: &Pattern, type_parameters: &[String], locals: &mut Locals) {
+        match pattern {
+            Pattern::Variable(VariablePattern {
+                name: Some(name),
+                type_id,
+            }) => {
+                locals.insert(
+                    name.clone(),
+                    type_id.as_ref().map_or(Type::Unknown, |type_id| {
+                        Type::from_annotation(type_id, type_parameters)
+                    }),
+                );
+            }
+            Pattern::Rest(rest) => {
+                if let Some(name) = &rest.name {
+                    locals.insert(name.clone(), Type::named("List"));
+                }
+            }
+            Pattern::Pair(pair) => {
+                Self::bind(&pair.left, type_parameters, locals);
+                Self::bind(&pair.right, type_parameters, locals);
+            }
+            Pattern::List(list) => {
+                for element in &list.elements {
+                    Self::bind(element, type_parameters, locals);
+                }
+            }
+            Pattern::Tuple(tuple) => Self::bind(&tuple.child, type_parameters, locals),
+            Pattern::Field(field) => Self::bind(&field.value, type_parameters, locals),
+            Pattern::Variable(_) | Pattern::Value(_) => {}
+        }
+    }
+
+    /// Infer the type of an expression, recording any type errors within it.
+    fn infer(&mut self, expression: &Expression, locals: &Locals) -> Type {
+        match &expression.kind {
+            ExpressionKind::Pattern(pattern) => self.infer_pattern(pattern, locals),
+            ExpressionKind::Call(call) => self.infer_call(call, expression, locals),
+            ExpressionKind::SafeCall(call) => self.infer_safe_call(call, locals),
+            ExpressionKind::Prefix(prefix) => self.infer_prefix(prefix, expression, locals),
+
+            ExpressionKind::Conditional(conditional) => {
+                self.expect(&conditional.condition, locals, Type::Boolean);
+
+                let (then_locals, else_locals) = Self::narrow(&conditional.condition, locals);
+                let then_type = self.infer(&conditional.then_arm, &then_locals);
+                let else_type = conditional
+                    .else_arm
+                    .as_ref()
+                    .map(|else_arm| self.infer(else_arm, &else_locals));
+
+                match else_type {
+                    Some(else_type) if else_type == then_type => then_type,
+                    _ => Type::Unknown,
+                }
+            }
+
+            ExpressionKind::List(Some(child)) => {
+                let elements = child
+                    .pattern_or_value_pattern()
+                    .map(Pattern::flatten_pairs)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|element| self.infer_pattern(element, locals))
+                    .collect::<Vec<Type>>();
+
+                Type::list_of(&elements)
+            }
+
+            // A block evaluates to its last expression, or to `nil` if it's empty.
+            ExpressionKind::Block(block) => block
+                .children
+                .iter()
+                .map(|child| self.infer(child, locals))
+                .last()
+                .unwrap_or(Type::Nil),
+
+            ExpressionKind::Literal(Literal::Sized(_)) => {
+                self.check_range(expression, expression.get_type());
+
+                expression.get_type()
+            }
+
+            _ => expression.get_type(),
+        }
+    }
+
+    /// Check that a number with a suffix like `255u8` is within the range of its type.
+    fn check_range(&mut self, expression: &Expression, target: Type) {
+        if Constant::evaluate(self.parser, expression).is_err() {
+            let value = self
+                .parser
+                .get_lexeme(expression.start_pos, expression.end_pos)
+                .unwrap_or_default();
+
+            self.errors.push(TypeError::OutOfRange {
+                value,
+                target,
+                start_pos: expression.start_pos,
+                end_pos: expression.end_pos,
+            });
+        }
+    }
+
+    /// Get the locals within the branches of a conditional, narrowing the type of a variable
+    /// if the condition is a type test like `x is Int`.
+    fn narrow(condition: &Expression, locals: &Locals) -> (Locals, Locals) {
+        let (mut then_locals, mut else_locals) = (locals.clone(), locals.clone());
+
+        if let Some((name, type_id)) = Self::type_test(condition) {
+            let tested = Type::from(&type_id);
+            let current = locals.get(&name).cloned().unwrap_or(Type::Unknown);
+
+            // An optional value which isn't `nil` is the value itself, and vice versa.
+            match (&current, &tested) {
+                (Type::Optional(value), Type::Nil) => {
+                    else_locals.insert(name.clone(), *value.clone());
+                }
+                (Type::Optional(value), tested) if **value == *tested => {
+                    else_locals.insert(name.clone(), Type::Nil);
+                }
+                _ => {}
+            }
+
+            then_locals.insert(name, tested);
+        }
+
+        (then_locals, else_locals)
+    }
+
+    /// Get the variable and the type it's tested for in a condition like `x is Int`.
+    fn type_test(condition: &Expression) -> Option<(String, TypeExpression)> {
+        let pair = match &condition.kind {
+            ExpressionKind::Call(Call {
+                name,
+                signature: Some(Pattern::Pair(pair)),
+            }) if name == "is" => pair,
+            _ => return None,
+        };
+
+        let variable = match pair.left.as_ref() {
+            Pattern::Value(ValuePattern { expression }) => match &expression.kind {
+                ExpressionKind::Pattern(Pattern::Variable(variable)) => variable,
+                _ => return None,
+            },
+            Pattern::Variable(variable) => variable,
+            _ => return None,
+        };
+
+        match (&variable.name, pair.right.as_ref()) {
+            (Some(name), Pattern::Value(ValuePattern { expression })) => match &expression.kind {
+                ExpressionKind::Type(type_id) => Some((name.clone(), type_id.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn infer_pattern(&mut self, pattern: &Pattern, locals: &Locals) -> Type {
+        match pattern {
+            Pattern::Value(ValuePattern { expression }) => self.infer(expression, locals),
+            Pattern::Variable(VariablePattern {
+                name: Some(name),
+                type_id: None,
+            }) => locals.get(name).cloned().unwrap_or(Type::Unknown),
+            Pattern::Pair(pair) => {
+                self.infer_pattern(&pair.left, locals);
+                self.infer_pattern(&pair.right, locals);
+
+                Type::Unknown
+            }
+            Pattern::Tuple(tuple) => self.infer_pattern(&tuple.child, locals),
+            Pattern::Field(field) => self.infer_pattern(&field.value, locals),
+            _ => Type::Unknown,
+        }
+    }
+
+    fn infer_prefix(&mut self, prefix: &Prefix, expression: &Expression, locals: &Locals) -> Type {
+        // A negative number with a suffix like `-128i8` is checked along with its sign.
+        if let (TokenKind::Minus, ExpressionKind::Literal(Literal::Sized(numeric_type))) =
+            (&prefix.operator.kind, &prefix.operand.kind)
+        {
+            self.check_range(expression, Type::numeric(*numeric_type));
+
+            return Type::numeric(*numeric_type);
+        }
+
+        let operand = self.infer(&prefix.operand, locals);
+
+        match (&prefix.operator.kind, operand) {
+            (TokenKind::Minus | TokenKind::Plus, operand) if operand.is_numeric() => operand,
+            (TokenKind::Bang, Type::Boolean) => Type::Boolean,
+            _ => Type::Unknown,
+        }
+    }
+
+    fn infer_call(&mut self, call: &Call, expression: &Expression, locals: &Locals) -> Type {
+        let arguments = call
+            .signature
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default();
+        let argument_types = arguments
+            .iter()
+            .map(|argument| self.infer_pattern(argument, locals))
+            .collect::<Vec<Type>>();
+
+        match call.name.as_str() {
+            "+" | "-" | "*" | "/" | "^" | "%" => {
+                for (argument, argument_type) in arguments.iter().zip(&argument_types) {
+                    if argument_type.is_known() && !argument_type.is_numeric() {
+                        let (start_pos, end_pos) = Self::span(argument, expression);
+
+                        self.errors.push(TypeError::InvalidOperand {
+                            operator: call.name.clone(),
+                            operand: argument_type.clone(),
+                            start_pos,
+                            end_pos,
+                        });
+                    }
+                }
+
+                match argument_types.as_slice() {
+                    [left, right] if left.is_numeric() && right.is_numeric() => {
+                        let result = Type::promote(left, right);
+
+                        if !result.is_known() {
+                            self.errors.push(TypeError::MixedNumericTypes {
+                                operator: call.name.clone(),
+                                left: left.clone(),
+                                right: right.clone(),
+                                start_pos: expression.start_pos,
+                                end_pos: expression.end_pos,
+                            });
+                        }
+
+                        result
+                    }
+                    [left, right] => Type::promote(left, right),
+                    _ => Type::Unknown,
+                }
+            }
+
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => Type::Boolean,
+
+            "??" => match argument_types.as_slice() {
+                [Type::Nil, default] => default.clone(),
+                [Type::Optional(value), default] if **value == *default => default.clone(),
+                [Type::Optional(value), Type::Optional(default)] if value == default => {
+                    Type::optional(*default.clone())
+                }
+                // The default of a value which is never `nil` is never used.
+                [value, _] if value.is_known() => value.clone(),
+                _ => Type::Unknown,
+            },
+
+            "is" => Type::Boolean,
+
+            "as" => self.infer_conversion(&arguments, &argument_types, expression),
+
+            "print" => Type::Nil,
+
+            "next_method" => Type::Unknown,
+
+            _ => {
+                self.check_optional_arguments(&call.name, &arguments, &argument_types, expression);
+                self.check_native_arguments(&call.name, &arguments, &argument_types, expression);
+                self.infer_method_call(&call.name, &argument_types)
+            }
+        }
+    }
+
+    /// Infer the type of a conversion like `n as Float`, which is only possible between
+    /// numbers and strings. A constant integer needs to be in the range of the target type.
+    fn infer_conversion(
+        &mut self,
+        arguments: &[Pattern],
+        argument_types: &[Type],
+        call: &Expression,
+    ) -> Type {
+        let target = match arguments.get(1) {
+            Some(Pattern::Value(ValuePattern { expression })) => match &expression.kind {
+                ExpressionKind::Type(type_id) => Type::from(type_id),
+                _ => return Type::Unknown,
+            },
+            _ => return Type::Unknown,
+        };
+        let value = argument_types.first().cloned().unwrap_or(Type::Unknown);
+
+        let is_convertible =
+            |value_type: &Type| value_type.is_numeric() || *value_type == Type::String;
+
+        // A character can only be converted into the string which consists of it.
+        let is_valid = if value == Type::Char {
+            target == Type::String
+        } else {
+            is_convertible(&target) && (!value.is_known() || is_convertible(&value))
+        };
+
+        if !is_valid {
+            let (start_pos, end_pos) = (call.start_pos, call.end_pos);
+
+            self.errors.push(TypeError::InvalidConversion {
+                from: value,
+                to: target.clone(),
+                start_pos,
+                end_pos,
+            });
+        } else if let Some(Pattern::Value(ValuePattern { expression })) = arguments.first() {
+            let integer = match Constant::evaluate(self.parser, expression) {
+                Ok(Constant::Int(n)) => Some(i128::from(n)),
+                Ok(Constant::SizedInt(n, _)) => Some(n),
+                _ => None,
+            };
+            let numeric_type = target.name().as_deref().and_then(NumericType::from_name);
+
+            if let (Some(n), Some(numeric_type)) = (integer, numeric_type) {
+                if !numeric_type.is_float() && !numeric_type.contains(n) {
+                    self.errors.push(TypeError::OutOfRange {
+                        value: n.to_string(),
+                        target: target.clone(),
+                        start_pos: call.start_pos,
+                        end_pos: call.end_pos,
+                    });
+                }
+            }
+        }
+
+        target
+    }
+
+    /// Infer the type of a safe navigation like `person?.name`, which is `nil` if the person
+    /// may be `nil`.
+    fn infer_safe_call(&mut self, call: &Call, locals: &Locals) -> Type {
+        let receiver = call.signature.as_ref().map_or(Type::Unknown, |receiver| {
+            self.infer_pattern(receiver, locals)
+        });
+
+        match receiver {
+            Type::Nil => Type::Nil,
+            Type::Optional(value) => Type::optional(self.infer_method_call(&call.name, &[*value])),
+            receiver => self.infer_method_call(&call.name, &[receiver]),
+        }
+    }
+
+    /// Report the arguments a method without any variants defined in the program can't
+    /// accept, like the string in `slice(name, 1, "3")`, since there is no variant which
+    /// could handle them at runtime.
+    fn check_native_arguments(
+        &mut self,
+        name: &str,
+        arguments: &[Pattern],
+        argument_types: &[Type],
+        call: &Expression,
+    ) {
+        let variants = self.variants.get(name).cloned().unwrap_or_default();
+
+        if variants.is_empty()
+            || variants
+                .iter()
+                .any(|variant| matches!(variant, Variant::Defined(_)))
+            || variants
+                .iter()
+                .any(|variant| self.instantiate(variant, argument_types).is_some())
+        {
+            return;
+        }
+
+        // Without a variant taking the same number of arguments, the expected types are
+        // ambiguous.
+        let candidates = variants
+            .iter()
+            .filter(|variant| {
+                variant
+                    .signature()
+                    .clone()
+                    .map(Pattern::flatten_pairs)
+                    .unwrap_or_default()
+                    .len()
+                    == arguments.len()
+            })
+            .collect::<Vec<&Variant>>();
+
+        let [variant] = candidates.as_slice() else {
+            return;
+        };
+        let parameters = variant
+            .signature()
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default();
+
+        for (index, (argument, argument_type)) in arguments.iter().zip(argument_types).enumerate() {
+            // Values which may be `nil` are reported by `check_optional_arguments`.
+            if !argument_type.is_known() || matches!(argument_type, Type::Optional(_) | Type::Nil) {
+                continue;
+            }
+
+            let mut probe = vec![Type::Unknown; argument_types.len()];
+            probe[index] = argument_type.clone();
+
+            if self.instantiate(variant, &probe).is_some() {
+                continue;
+            }
+
+            if let Some(Pattern::Variable(VariablePattern {
+                type_id: Some(type_id),
+                ..
+            })) = parameters.get(index)
+            {
+                let (start_pos, end_pos) = Self::span(argument, call);
+
+                self.errors.push(TypeError::UnexpectedType {
+                    expected: Type::from_annotation(type_id, variant.type_parameters()),
+                    found: argument_type.clone(),
+                    start_pos,
+                    end_pos,
+                });
+            }
+        }
+    }
+
+    /// Report the arguments which may be `nil` if no variant accepts them, but one would
+    /// accept their values.
+    fn check_optional_arguments(
+        &mut self,
+        name: &str,
+        arguments: &[Pattern],
+        argument_types: &[Type],
+        call: &Expression,
+    ) {
+        let variants = self.variants.get(name).cloned().unwrap_or_default();
+        let values = argument_types
+            .iter()
+            .map(|argument| match argument {
+                Type::Optional(value) => *value.clone(),
+                Type::Nil => Type::Unknown,
+                _ => argument.clone(),
+            })
+            .collect::<Vec<Type>>();
+
+        let accepts = |types: &[Type]| {
+            variants
+                .iter()
+                .any(|variant| self.instantiate(variant, types).is_some())
+        };
+
+        if accepts(argument_types) || !accepts(&values) {
+            return;
+        }
+
+        for (argument, argument_type) in arguments.iter().zip(argument_types) {
+            if matches!(argument_type, Type::Optional(_) | Type::Nil) {
+                let (start_pos, end_pos) = Self::span(argument, call);
+
+                self.errors.push(TypeError::OptionalValue {
+                    found: argument_type.clone(),
+                    start_pos,
+                    end_pos,
+                });
+            }
+        }
+    }
+
+    /// Get the return type shared by all variants which may accept the given arguments,
+    /// instantiated with the type parameters inferred from them.
+    fn infer_method_call(&mut self, name: &str, arguments: &[Type]) -> Type {
+        let candidates = self
+            .variants
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|variant| Some((self.instantiate(&variant, arguments)?, variant)))
+            .collect::<Vec<(HashMap<String, Type>, Variant)>>();
+
+        let mut results = candidates.iter().map(|(bindings, variant)| match variant {
+            Variant::Defined(method) => self.return_type(method).substitute(bindings),
+            Variant::Native { result, .. } => result.substitute(bindings),
+        });
+
+        let first = match results.next() {
+            Some(first) => first,
+            None => return Type::Unknown,
+        };
+
+        if results.all(|result| result == first) {
+            first
+        } else {
+            Type::Unknown
+        }
+    }
+
+    /// Bind the type parameters of a variant to the types of the given arguments, if its
+    /// signature may accept them.
+    fn instantiate(&self, variant: &Variant, arguments: &[Type]) -> Option<HashMap<String, Type>> {
+        let mut bindings = HashMap::new();
+        let parameters = variant
+            .signature()
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default();
+
+        let lengths_match = match parameters.last() {
+            Some(Pattern::Rest(_)) => parameters.len() - 1 <= arguments.len(),
+            _ => parameters.len() == arguments.len(),
+        };
+
+        let accepts = lengths_match
+            && parameters
+                .iter()
+                .zip(arguments)
+                .all(|(parameter, argument)| match (parameter, argument.name()) {
+                    (
+                        Pattern::Variable(VariablePattern {
+                            type_id: Some(type_id),
+                            ..
+                        }),
+                        name,
+                    ) => {
+                        let expected = Type::from_annotation(type_id, variant.type_parameters());
+
+                        match (&expected, name) {
+                            (Type::Parameter(_) | Type::Optional(_), _) => {
+                                expected.unify(argument, &mut bindings)
+                            }
+                            // Only values of the same type can be compared by their type
+                            // arguments, which isn't the case for a subtype.
+                            (_, Some(name)) if expected.name() == Some(name.clone()) => {
+                                expected.unify(argument, &mut bindings)
+                            }
+                            (_, Some(name)) => self.types.may_overlap(&name, &type_id.name()),
+                            // Only optional parameters accept a value which may be `nil`.
+                            (_, None) => !matches!(argument, Type::Optional(_)),
+                        }
+                    }
+                    (Pattern::Value(ValuePattern { expression }), Some(argument)) => {
+                        match Constant::evaluate(self.parser, expression) {
+                            Ok(value) => {
+                                let type_name = value.type_name();
+
+                                type_name == argument
+                                    || (Constant::is_numeric_type(&type_name)
+                                        && Constant::is_numeric_type(&argument))
+                            }
+                            Err(_) => true,
+                        }
+                    }
+                    (Pattern::List(_), Some(argument)) => argument == "List",
+                    _ => true,
+                });
+
+        if accepts {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+
+    /// Record an error if the type of an expression is known to differ from the expected one.
+    fn expect(&mut self, expression: &Expression, locals: &Locals, expected: Type) {
+        let found = self.infer(expression, locals);
+
+        if found.is_known() && !self.conforms(&found, &expected) {
+            self.errors.push(TypeError::UnexpectedType {
+                expected,
+                found,
+                start_pos: expression.start_pos,
+                end_pos: expression.end_pos,
+            });
+        }
+    }
+
+    /// Check whether a value of the found type can be used where the expected one is, which
+    /// includes its supertypes.
+    fn conforms(&self, found: &Type, expected: &Type) -> bool {
+        match (found, expected) {
+            _ if found == expected => true,
+            (_, Type::Unknown) => true,
+            (
+                Type::Generic { name, arguments },
+                Type::Generic {
+                    name: other,
+                    arguments: others,
+                },
+            ) => {
+                name == other
+                    && arguments.len() == others.len()
+                    && arguments.iter().zip(others).all(|(argument, other)| {
+                        *argument == Type::Unknown || self.conforms(argument, other)
+                    })
+            }
+            // The elements of a list aren't always known, like those of a rest parameter.
+            (Type::Named(name), Type::Generic { name: other, .. }) => name == other,
+            // Both `nil` and a value of the type itself can be used where it may be `nil`.
+            (Type::Nil, Type::Optional(_)) => true,
+            (Type::Optional(found), Type::Optional(expected)) => {
+                **found == Type::Unknown || self.conforms(found, expected)
+            }
+            (found, Type::Optional(expected)) => self.conforms(found, expected),
+            _ => match (found.name(), expected) {
+                (Some(found), Type::Named(expected)) => self.types.is_subtype(&found, expected),
+                _ => false,
+            },
+        }
+    }
+
+    /// Get the position of an argument in the source code, falling back to the whole call
+    /// for arguments which aren't expressions.
+    fn span(argument: &Pattern, call: &Expression) -> (usize, usize) {
+        match argument {
+            Pattern::Value(ValuePattern { expression }) => {
+                (expression.start_pos, expression.end_pos)
+            }
+            _ => (call.start_pos, call.end_pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeChecker;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::type_system::{Type, TypeError};
+
+    fn check(source: &str) -> Vec<TypeError> {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let mut expressions = parser.parse().unwrap();
+
+        for expression in expressions.iter_mut() {
+            expression.desugar();
+        }
+
+        TypeChecker::new(&parser).check(&expressions)
+    }
+
+    #[test]
+    fn arithmetic_on_strings_is_an_error() {
+        let errors = check("1 + \"two\"");
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::InvalidOperand {
+                operand: Type::String,
+                start_pos: 4,
+                end_pos: 9,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn infers_the_types_of_locals_and_calls() {
+        let errors = check(
+            "def name(n Int) \"number\" def name(s String) \"text\" def twice(n Int) name(n) * 2",
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::InvalidOperand {
+                operand: Type::String,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn instantiates_generic_methods() {
+        let errors = check(
+            "def first[T](list List[T]) T list[0] first([\"a\", \"b\"]) + 1 first([1, 2]) + 1",
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::InvalidOperand {
+                operand: Type::String,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn optional_values_need_to_be_handled() {
+        let errors = check(
+            "def class Person(nickname String?) \
+             def greet(name String) name \
+             def greet_person(p Person) greet(p.nickname) \
+             def greet_safely(p Person) greet(p.nickname ?? \"friend\") \
+             def shout(p Person?) p?.nickname",
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::OptionalValue {
+                found: Type::Optional(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn type_tests_narrow_variables() {
+        let errors = check(
+            "def increment(x Int?) if x is Nil then 0 else x + 1 end \
+             def convert(flag Boolean) flag as Int",
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::InvalidConversion {
+                from: Type::Boolean,
+                to: Type::Int,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn sized_numbers_are_checked() {
+        let errors = check("def f() 256u8 def g() 1u8 + 1i32 def h() 300 as UInt8 def i() -128i8");
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], TypeError::OutOfRange { .. }));
+        assert!(matches!(&errors[1], TypeError::MixedNumericTypes { .. }));
+        assert!(matches!(&errors[2], TypeError::OutOfRange { .. }));
+    }
+
+    #[test]
+    fn string_indices_are_characters() {
+        let errors = check("def f(s String) s[0] as String def g(s String) s[0] as Int");
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], TypeError::InvalidConversion { .. }));
+    }
+
+    #[test]
+    fn list_elements_keep_their_type() {
+        let errors = check("def f() [1, 2][0] + 1 def g() append([true], false)[1] + 1");
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], TypeError::InvalidOperand { .. }));
+    }
+
+    #[test]
+    fn blocks_have_the_type_of_their_last_expression() {
+        let errors = check("def f() do 1 \"two\" end def g() do end f() + 1 g() + 1");
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            TypeError::InvalidOperand {
+                operand: Type::String,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &errors[1],
+            TypeError::InvalidOperand {
+                operand: Type::Nil,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn duplicate_types_are_an_error() {
+        let errors = check("def class Point(x Int) def class Point(y Int)");
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::DuplicateType { name, .. } if name == "Point"
+        ));
+    }
+
+    #[test]
+    fn values_of_unknown_type_are_accepted() {
+        assert!(check("def double(n) n * 2 double(2)").is_empty());
+    }
+}
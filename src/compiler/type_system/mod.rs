@@ -1 +1,406 @@
-pub struct TypeSystem;
+//! Keep track of the types defined in a program and how they relate to each other.
+//!
+//! Types form a graph in which each type points to its supertypes, like the enum of a
+//! variant or the interfaces a class implements. Since a type may have several supertypes,
+//! they're put into a single order with the C3 linearization, which lists each type
+//! before its supertypes while keeping the order in which they were declared.
+
+use crate::types::{
+    Class, CompilerError, CompilerResult, Enum, Interface, MethodSignature, Pattern,
+    VariablePattern,
+};
+
+use std::collections::HashMap;
+
+mod checker;
+
+pub use self::checker::TypeChecker;
+
+/// The types which are built into the language and can't be redefined.
+pub const BUILTIN_TYPES: [&str; 18] = [
+    "Int", "Float", "String", "Boolean", "List", "Nil", "Int8", "Int16", "Int32", "Int64", "UInt8",
+    "UInt16", "UInt32", "UInt64", "Float32", "Float64", "BigInt", "Char",
+];
+
+/// A user-defined type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDefinition {
+    /// The name of the type, which is also the name of a class's constructor.
+    pub name: String,
+    pub kind: TypeKind,
+    /// The types whose values include all values of this type, like the enum of a variant
+    /// or the interfaces implemented by a class.
+    pub supertypes: Vec<String>,
+}
+
+/// The different kinds of user-defined types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    /// A class or a variant of an enum, whose instances are created by its constructor.
+    /// The fields are stored in the order in which they're declared.
+    Class { fields: Vec<VariablePattern> },
+    /// An enum, whose values are instances of one of the given variants.
+    Enum { variants: Vec<String> },
+    /// An interface, whose implementations need to provide the given methods.
+    Interface { methods: Vec<MethodSignature> },
+}
+
+impl TypeDefinition {
+    /// Get the fields of each instance, which only classes have.
+    pub fn fields(&self) -> &[VariablePattern] {
+        match &self.kind {
+            TypeKind::Class { fields } => fields,
+            _ => &[],
+        }
+    }
+
+    /// Get the signature of the constructor, which takes a value for each field.
+    pub fn constructor_signature(&self) -> Option<Pattern> {
+        Pattern::from_elements(
+            self.fields()
+                .iter()
+                .cloned()
+                .map(Pattern::Variable)
+                .collect(),
+        )
+    }
+
+    /// Get the position of the field with the given name within an instance.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields()
+            .iter()
+            .position(|field| field.name.as_deref() == Some(name))
+    }
+}
+
+impl From<Class> for TypeDefinition {
+    fn from(class: Class) -> Self {
+        Self {
+            name: class.name,
+            kind: TypeKind::Class {
+                fields: class.fields,
+            },
+            supertypes: class
+                .interfaces
+                .iter()
+                .map(|interface| interface.name())
+                .collect(),
+        }
+    }
+}
+
+impl From<Enum> for TypeDefinition {
+    fn from(definition: Enum) -> Self {
+        Self {
+            name: definition.name,
+            kind: TypeKind::Enum {
+                variants: definition
+                    .variants
+                    .into_iter()
+                    .map(|variant| variant.name)
+                    .collect(),
+            },
+            supertypes: definition
+                .interfaces
+                .iter()
+                .map(|interface| interface.name())
+                .collect(),
+        }
+    }
+}
+
+impl From<Interface> for TypeDefinition {
+    fn from(interface: Interface) -> Self {
+        Self {
+            name: interface.name,
+            kind: TypeKind::Interface {
+                methods: interface.methods,
+            },
+            supertypes: interface
+                .interfaces
+                .iter()
+                .map(|supertype| supertype.name())
+                .collect(),
+        }
+    }
+}
+
+/// A structure which keeps track of defined types.
+#[derive(Debug, Clone, Default)]
+pub struct TypeSystem {
+    types: HashMap<String, TypeDefinition>,
+}
+
+impl TypeSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new type, which fails if a type with the same name already exists.
+    pub fn define(&mut self, definition: TypeDefinition) -> CompilerResult<()> {
+        if self.is_defined(&definition.name) {
+            return Err(CompilerError::DuplicateType(definition.name));
+        }
+
+        self.types.insert(definition.name.clone(), definition);
+
+        Ok(())
+    }
+
+    /// Get the definition of the user-defined type with the given name.
+    pub fn get(&self, name: &str) -> Option<&TypeDefinition> {
+        self.types.get(name)
+    }
+
+    /// Get all user-defined types, sorted by their name.
+    pub fn definitions(&self) -> Vec<&TypeDefinition> {
+        let mut definitions = self.types.values().collect::<Vec<&TypeDefinition>>();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        definitions
+    }
+
+    /// Get the variants of the enum with the given name, or `None` if it isn't an enum.
+    pub fn variants(&self, name: &str) -> Option<&[String]> {
+        match &self.get(name)?.kind {
+            TypeKind::Enum { variants } => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the enum the given type is a variant of, if any.
+    pub fn enum_of(&self, name: &str) -> Option<String> {
+        self.get(name)?
+            .supertypes
+            .iter()
+            .find(|supertype| self.variants(supertype).is_some())
+            .cloned()
+    }
+
+    /// Check whether a type with the given name is either built in or has been defined.
+    pub fn is_defined(&self, name: &str) -> bool {
+        BUILTIN_TYPES.contains(&name) || self.types.contains_key(name)
+    }
+
+    /// Get the type itself followed by all of its supertypes, from the most to the least
+    /// specific one.
+    ///
+    /// An error is returned if the supertypes can't be ordered consistently, for example
+    /// if two of them list their own supertypes in opposite orders, or refer to each other.
+    pub fn linearization(&self, name: &str) -> CompilerResult<Vec<String>> {
+        self.linearize(name, &mut vec![])
+    }
+
+    fn linearize(&self, name: &str, visiting: &mut Vec<String>) -> CompilerResult<Vec<String>> {
+        let supertypes = match self.get(name) {
+            Some(definition) => &definition.supertypes,
+            None => return Ok(vec![name.to_string()]),
+        };
+
+        if visiting.iter().any(|visited| visited == name) {
+            return Err(CompilerError::InconsistentTypeHierarchy(name.to_string()));
+        }
+
+        visiting.push(name.to_string());
+
+        let mut sequences = supertypes
+            .iter()
+            .map(|supertype| self.linearize(supertype, visiting))
+            .collect::<CompilerResult<Vec<Vec<String>>>>()?;
+        sequences.push(supertypes.clone());
+
+        visiting.pop();
+
+        let mut linearization = vec![name.to_string()];
+
+        // Repeatedly take the first head which doesn't appear after the start of any sequence.
+        while sequences.iter().any(|sequence| !sequence.is_empty()) {
+            let next = sequences
+                .iter()
+                .filter_map(|sequence| sequence.first())
+                .find(|head| {
+                    !sequences
+                        .iter()
+                        .any(|sequence| sequence.iter().skip(1).any(|other| other == *head))
+                })
+                .cloned()
+                .ok_or_else(|| CompilerError::InconsistentTypeHierarchy(name.to_string()))?;
+
+            for sequence in sequences.iter_mut() {
+                sequence.retain(|other| *other != next);
+            }
+
+            linearization.push(next);
+        }
+
+        Ok(linearization)
+    }
+
+    /// Check whether all values of a type are also values of another type.
+    pub fn is_subtype(&self, name: &str, other: &str) -> bool {
+        name == other
+            || self
+                .linearization(name)
+                .is_ok_and(|linearization| linearization.iter().any(|t| t == other))
+    }
+
+    /// Get how far the given supertype is from a type in its linearization, or `None` if
+    /// it isn't a supertype at all.
+    pub fn distance(&self, name: &str, supertype: &str) -> Option<usize> {
+        self.linearization(name)
+            .ok()?
+            .iter()
+            .position(|other| other == supertype)
+    }
+
+    /// Ensure that the supertypes of each type can be linearized.
+    pub fn check_hierarchy(&self) -> CompilerResult<()> {
+        self.definitions()
+            .into_iter()
+            .try_for_each(|definition| self.linearization(&definition.name).map(|_| ()))
+    }
+
+    /// Get the types a value may have at runtime if it belongs to the given type.
+    ///
+    /// Only instances of classes carry a type at runtime, so the values of an enum or
+    /// interface are those of the classes which conform to it. Any other type, including
+    /// unknown ones, is returned as is.
+    pub fn concrete_types(&self, name: &str) -> Vec<String> {
+        match self.get(name).map(|definition| &definition.kind) {
+            Some(TypeKind::Enum { .. }) | Some(TypeKind::Interface { .. }) => self
+                .definitions()
+                .into_iter()
+                .filter(|definition| matches!(definition.kind, TypeKind::Class { .. }))
+                .filter(|definition| self.is_subtype(&definition.name, name))
+                .map(|definition| definition.name.clone())
+                .collect(),
+
+            _ => vec![name.to_string()],
+        }
+    }
+
+    /// Check whether a value may belong to both of the given types.
+    pub fn may_overlap(&self, name: &str, other: &str) -> bool {
+        let others = self.concrete_types(other);
+
+        self.concrete_types(name)
+            .iter()
+            .any(|concrete_type| others.contains(concrete_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TypeDefinition, TypeKind, TypeSystem};
+    use crate::types::{CompilerError, VariablePattern};
+
+    fn class(name: &str, supertypes: &[&str]) -> TypeDefinition {
+        TypeDefinition {
+            name: name.to_string(),
+            kind: TypeKind::Class {
+                fields: vec![
+                    VariablePattern {
+                        name: Some("x".to_string()),
+                        type_id: None,
+                    },
+                    VariablePattern {
+                        name: Some("y".to_string()),
+                        type_id: None,
+                    },
+                ],
+            },
+            supertypes: supertypes.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn interface(name: &str, supertypes: &[&str]) -> TypeDefinition {
+        TypeDefinition {
+            name: name.to_string(),
+            kind: TypeKind::Interface { methods: vec![] },
+            supertypes: supertypes.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn defines_types_once() {
+        let mut types = TypeSystem::new();
+
+        assert!(types.define(class("Point", &[])).is_ok());
+        assert!(types.is_defined("Point"));
+        assert!(matches!(
+            types.define(class("Point", &[])),
+            Err(CompilerError::DuplicateType(name)) if name == "Point"
+        ));
+    }
+
+    #[test]
+    fn builtin_types_cant_be_redefined() {
+        let mut types = TypeSystem::new();
+
+        assert!(types.define(interface("Int", &[])).is_err());
+        assert!(types.define(class("Nil", &[])).is_err());
+        assert!(types.define(class("UInt8", &[])).is_err());
+        assert!(types.define(class("Float64", &[])).is_err());
+        assert!(types.define(class("BigInt", &[])).is_err());
+        assert!(types.define(class("Char", &[])).is_err());
+    }
+
+    #[test]
+    fn finds_fields_by_name() {
+        assert_eq!(class("Point", &[]).field_index("y"), Some(1));
+        assert_eq!(class("Point", &[]).field_index("z"), None);
+    }
+
+    #[test]
+    fn interfaces_contain_their_implementations() {
+        let mut types = TypeSystem::new();
+
+        types.define(interface("Shape", &[])).unwrap();
+        types.define(class("Square", &["Shape"])).unwrap();
+        types.define(class("Circle", &["Shape"])).unwrap();
+        types.define(class("Point", &[])).unwrap();
+
+        assert!(types.is_subtype("Circle", "Shape"));
+        assert!(!types.is_subtype("Shape", "Circle"));
+        assert_eq!(
+            types.concrete_types("Shape"),
+            vec!["Circle".to_string(), "Square".to_string()]
+        );
+        assert!(types.may_overlap("Shape", "Square"));
+        assert!(!types.may_overlap("Shape", "Point"));
+    }
+
+    #[test]
+    fn linearizes_supertypes_in_declaration_order() {
+        let mut types = TypeSystem::new();
+
+        types.define(interface("Object", &[])).unwrap();
+        types.define(interface("Shape", &["Object"])).unwrap();
+        types.define(interface("Printable", &["Object"])).unwrap();
+        types
+            .define(class("Circle", &["Shape", "Printable"]))
+            .unwrap();
+
+        assert_eq!(
+            types.linearization("Circle").unwrap(),
+            vec!["Circle", "Shape", "Printable", "Object"]
+        );
+        assert_eq!(types.distance("Circle", "Printable"), Some(2));
+        assert!(types.is_subtype("Circle", "Object"));
+    }
+
+    #[test]
+    fn inconsistent_hierarchies_cant_be_linearized() {
+        let mut types = TypeSystem::new();
+
+        types.define(interface("A", &[])).unwrap();
+        types.define(interface("B", &["A"])).unwrap();
+        types.define(class("C", &["A", "B"])).unwrap();
+        types.define(interface("D", &["E"])).unwrap();
+        types.define(interface("E", &["D"])).unwrap();
+
+        assert!(types.linearization("C").is_err());
+        assert!(types.linearization("D").is_err());
+        assert!(types.check_hierarchy().is_err());
+    }
+}
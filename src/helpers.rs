@@ -1,3 +1,7 @@
+use crate::type_system::NumericType;
+
+use num_bigint::BigInt;
+
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Split up a Unicode source string into a collection of graphemes.
@@ -7,3 +11,96 @@ use unicode_segmentation::UnicodeSegmentation;
 pub fn convert_to_graphemes(input: String) -> Vec<String> {
     input.graphemes(true).map(|g| g.to_string()).collect()
 }
+
+/// Parse the lexeme of an integer literal, which may be written in decimal or with a
+/// `0x`, `0o` or `0b` prefix for hexadecimal, octal and binary numbers.
+pub fn parse_int_literal(lexeme: &str) -> Option<i64> {
+    parse_wide_int_literal(lexeme).and_then(|value| i64::try_from(value).ok())
+}
+
+/// Parse the lexeme of an integer literal like [`parse_int_literal`], but into an `i128` so
+/// the value of any numeric type like `UInt64` can be represented.
+pub fn parse_wide_int_literal(lexeme: &str) -> Option<i128> {
+    let (digits, radix) = match lexeme.get(0..2) {
+        Some("0x") | Some("0X") => (&lexeme[2..], 16),
+        Some("0o") | Some("0O") => (&lexeme[2..], 8),
+        Some("0b") | Some("0B") => (&lexeme[2..], 2),
+        _ => (lexeme, 10),
+    };
+
+    i128::from_str_radix(digits, radix).ok()
+}
+
+/// Parse the lexeme of an integer literal like [`parse_int_literal`], but into a `BigInt` so
+/// integers of any size can be represented.
+pub fn parse_big_int_literal(lexeme: &str) -> Option<BigInt> {
+    let (digits, radix) = match lexeme.get(0..2) {
+        Some("0x") | Some("0X") => (&lexeme[2..], 16),
+        Some("0o") | Some("0O") => (&lexeme[2..], 8),
+        Some("0b") | Some("0B") => (&lexeme[2..], 2),
+        _ => (lexeme, 10),
+    };
+
+    BigInt::parse_bytes(digits.as_bytes(), radix)
+}
+
+/// Split the lexeme of a number literal into its digits and the type selected by its
+/// suffix, like `255` and `UInt8` for `255u8`.
+pub fn split_number_suffix(lexeme: &str) -> (&str, Option<NumericType>) {
+    for length in [3, 2] {
+        if let Some(split) = lexeme.len().checked_sub(length).filter(|split| *split > 0) {
+            if let Some(numeric_type) = lexeme.get(split..).and_then(NumericType::from_suffix) {
+                return (&lexeme[..split], Some(numeric_type));
+            }
+        }
+    }
+
+    (lexeme, None)
+}
+
+/// Decode the lexeme of a string literal by removing its delimiters and resolving escape
+/// sequences like `\"`, `\\`, `\n` or `\t`.
+pub fn parse_string_literal(raw: &str) -> String {
+    let contents = raw
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(raw);
+
+    resolve_escape_sequences(contents)
+}
+
+/// Decode the lexeme of a character literal like `'é'` or `'\n'`, which needs to contain
+/// exactly one grapheme cluster.
+pub fn parse_char_literal(raw: &str) -> Option<String> {
+    let contents = raw.strip_prefix('\'')?.strip_suffix('\'')?;
+    let value = resolve_escape_sequences(contents);
+
+    if convert_to_graphemes(value.clone()).len() == 1 {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn resolve_escape_sequences(contents: &str) -> String {
+    let mut value = String::new();
+    let mut characters = contents.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            value.push(character);
+            continue;
+        }
+
+        match characters.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('0') => value.push('\0'),
+            Some(escaped) => value.push(escaped),
+            None => value.push('\\'),
+        }
+    }
+
+    value
+}
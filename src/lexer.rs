@@ -4,6 +4,8 @@
 //! into UTF-8 characters and consumes them one at a time to create a list of
 //! tokens that exactly represent the code contained in the source string.
 
+use crate::helpers::parse_int_literal;
+use crate::type_system::NumericType;
 use crate::types::{Keyword, Literal, Token, TokenKind};
 
 use unicode_segmentation::UnicodeSegmentation;
@@ -16,6 +18,12 @@ pub struct Lexer {
     pub source: Vec<String>,
 }
 
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Lexer {
     pub fn new() -> Self {
         Self {
@@ -48,11 +56,12 @@ impl Lexer {
                 "!" => self.match_next("=", TokenKind::BangEqual, TokenKind::Bang),
                 ":" => self.single_token(TokenKind::Colon),
                 "," => self.single_token(TokenKind::Comma),
-                "." => self.single_token(TokenKind::Dot),
+                "." => self.parse_dot(),
                 "[" => self.single_token(TokenKind::LeftBracket),
                 "(" => self.single_token(TokenKind::LeftParen),
                 "%" => self.single_token(TokenKind::Percent),
-                "?" => self.single_token(TokenKind::QuestionMark),
+                "^" => self.single_token(TokenKind::Caret),
+                "?" => self.parse_question_mark(),
                 ")" => self.single_token(TokenKind::RightParen),
                 "]" => self.single_token(TokenKind::RightBracket),
 
@@ -96,6 +105,7 @@ impl Lexer {
                 }
 
                 "\"" => self.parse_string(),
+                "'" => self.parse_char(),
 
                 _ => self.parse_identifier_or_keyword(character),
             };
@@ -152,6 +162,7 @@ impl Lexer {
             "if" => TokenKind::Keyword(Keyword::If),
             "import" => TokenKind::Keyword(Keyword::Import),
             "interface" => TokenKind::Keyword(Keyword::Interface),
+            "is" => TokenKind::Keyword(Keyword::Is),
             "it" => TokenKind::Keyword(Keyword::It),
             "for" => TokenKind::Keyword(Keyword::For),
             "match" => TokenKind::Keyword(Keyword::Match),
@@ -160,18 +171,57 @@ impl Lexer {
             "then" => TokenKind::Keyword(Keyword::Then),
             "this" => TokenKind::Keyword(Keyword::This),
             "var" => TokenKind::Keyword(Keyword::Var),
+            "where" => TokenKind::Keyword(Keyword::Where),
             "with" => TokenKind::Keyword(Keyword::With),
             "while" => TokenKind::Keyword(Keyword::While),
 
             "true" => TokenKind::Literal(Literal::Boolean),
             "false" => TokenKind::Literal(Literal::Boolean),
+            "nil" => TokenKind::Literal(Literal::Nil),
 
             _ => TokenKind::Identifier,
         }
     }
 
+    /// Parse a single question mark like in `Int?`, or one of the operators `?.` and `??`.
+    fn parse_question_mark(&mut self) -> TokenKind {
+        self.advance();
+
+        if self.eof() {
+            return TokenKind::QuestionMark;
+        }
+
+        match self.current().as_str() {
+            "." => {
+                self.advance();
+                TokenKind::QuestionDot
+            }
+            "?" => {
+                self.advance();
+                TokenKind::QuestionQuestion
+            }
+            _ => TokenKind::QuestionMark,
+        }
+    }
+
+    /// Parse a single dot or the `...` prefix of a rest pattern.
+    fn parse_dot(&mut self) -> TokenKind {
+        let is_ellipsis = self.source.get(self.position + 1).map(String::as_str) == Some(".")
+            && self.source.get(self.position + 2).map(String::as_str) == Some(".");
+
+        if is_ellipsis {
+            self.advance();
+            self.advance();
+            self.advance();
+
+            TokenKind::Ellipsis
+        } else {
+            self.single_token(TokenKind::Dot)
+        }
+    }
+
     fn parse_comment(&mut self) -> TokenKind {
-        let mut comment = String::from(self.source[self.position].clone());
+        let mut comment = self.source[self.position].clone();
 
         self.advance();
 
@@ -204,8 +254,8 @@ impl Lexer {
                 "A" | "B" | "C" | "D" | "E" | "F" | "G" | "H" | "I" | "J" | "K" | "L" | "M"
                 | "N" | "O" | "P" | "Q" | "R" | "S" | "T" | "U" | "V" | "W" | "X" | "Y" | "Z"
                 | "a" | "b" | "c" | "d" | "e" | "f" | "g" | "h" | "i" | "j" | "k" | "l" | "m"
-                | "n" | "o" | "p" | "q" | "r" | "s" | "t" | "v" | "w" | "x" | "y" | "z" | "0"
-                | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => {
+                | "n" | "o" | "p" | "q" | "r" | "s" | "t" | "u" | "v" | "w" | "x" | "y" | "z"
+                | "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => {
                     self.advance();
                     type_string = format!("{}{}", type_string, character);
                 }
@@ -218,10 +268,45 @@ impl Lexer {
     }
 
     fn parse_number(&mut self) -> TokenKind {
-        let mut number_string = String::from(self.source[self.position].clone());
+        let start_pos = self.position;
+        let mut number_string = self.source[self.position].clone();
 
         self.advance();
 
+        // Integers with a radix prefix like `0x1F`, `0o17` or `0b101`.
+        if number_string == "0" && !self.eof() {
+            let radix = match self.source[self.position].as_str() {
+                "x" | "X" => Some(16),
+                "o" | "O" => Some(8),
+                "b" | "B" => Some(2),
+                _ => None,
+            };
+
+            if let Some(radix) = radix {
+                self.advance();
+
+                let digits_start = self.position;
+
+                while !self.eof() && self.is_digit(radix) {
+                    self.advance();
+                }
+
+                if self.position > digits_start {
+                    if let Some(numeric_type) = self.parse_number_suffix(true) {
+                        return TokenKind::Literal(Literal::Sized(numeric_type));
+                    }
+                }
+
+                // Keep malformed literals like `0b12` or `0x` in a single token, which the
+                // parser rejects instead of splitting it into several numbers.
+                while !self.eof() && self.is_digit(36) {
+                    self.advance();
+                }
+
+                return self.integer_literal(start_pos);
+            }
+        }
+
         // Start parsing the number.
         while !self.eof() {
             let character = self.source[self.position].clone();
@@ -236,13 +321,58 @@ impl Lexer {
             }
         }
 
-        if number_string.contains(".") {
-            TokenKind::Literal(Literal::Float)
-        } else {
-            TokenKind::Literal(Literal::Int)
+        let is_float = number_string.contains(".");
+
+        match self.parse_number_suffix(!is_float) {
+            Some(numeric_type) => TokenKind::Literal(Literal::Sized(numeric_type)),
+            None if is_float => TokenKind::Literal(Literal::Float),
+            None => self.integer_literal(start_pos),
         }
     }
 
+    /// Check whether the current character is a digit in the given radix.
+    fn is_digit(&self, radix: u32) -> bool {
+        let mut characters = self.source[self.position].chars();
+
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => character.is_digit(radix),
+            _ => false,
+        }
+    }
+
+    /// Get the kind of an integer literal starting at the given position, which is a
+    /// `BigInt` if its value doesn't fit into an `Int`.
+    fn integer_literal(&self, start_pos: usize) -> TokenKind {
+        let lexeme = self.source[start_pos..self.position].concat();
+
+        match parse_int_literal(&lexeme) {
+            Some(_) => TokenKind::Literal(Literal::Int),
+            None => TokenKind::Literal(Literal::BigInt),
+        }
+    }
+
+    /// Parse a suffix like the `u8` in `255u8` which selects the type of a number literal.
+    ///
+    /// Integer suffixes are only allowed if the literal is an integer. If the characters
+    /// after the number don't form a valid suffix, they're left for the next token.
+    fn parse_number_suffix(&mut self, allow_integer: bool) -> Option<NumericType> {
+        let mut end = self.position;
+        let mut suffix = String::new();
+
+        while end < self.source.len() && self.source[end].chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            suffix.push_str(&self.source[end]);
+            end += 1;
+        }
+
+        let numeric_type = NumericType::from_suffix(&suffix)
+            .filter(|numeric_type| allow_integer || numeric_type.is_float())?;
+
+        self.position = end;
+
+        Some(numeric_type)
+    }
+
     fn parse_string(&mut self) -> TokenKind {
         self.advance();
 
@@ -255,6 +385,12 @@ impl Lexer {
                     break;
                 }
 
+                // Skip over escaped characters like `\"` so they don't end the string.
+                "\\" => {
+                    self.advance();
+                    self.advance();
+                }
+
                 _ => {
                     self.advance();
                 }
@@ -264,6 +400,32 @@ impl Lexer {
         TokenKind::Literal(Literal::String)
     }
 
+    /// Parse a character literal like `'a'`. Whether it contains exactly one character is
+    /// checked by the parser.
+    fn parse_char(&mut self) -> TokenKind {
+        self.advance();
+
+        while !self.eof() {
+            match self.source[self.position].as_str() {
+                "'" => {
+                    self.advance();
+                    break;
+                }
+
+                "\\" => {
+                    self.advance();
+                    self.advance();
+                }
+
+                "\n" => break,
+
+                _ => self.advance(),
+            }
+        }
+
+        TokenKind::Literal(Literal::Char)
+    }
+
     /// Advance the pointer by one if we're not at the end.
     fn advance(&mut self) {
         if !self.eof() {
@@ -311,7 +473,7 @@ impl Lexer {
 
 #[cfg(test)]
 mod tests {
-    use super::{Lexer, Literal, Token, TokenKind};
+    use super::{Lexer, Literal, NumericType, Token, TokenKind};
 
     #[test]
     fn scan_comment() {
@@ -361,6 +523,193 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scan_number_suffixes() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("255u8 1.5f32 0xFFi32 2.0u8".to_string());
+
+        let kinds = lexer
+            .parse()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect::<Vec<TokenKind>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Literal(Literal::Sized(NumericType::UInt8)),
+                TokenKind::Literal(Literal::Sized(NumericType::Float32)),
+                TokenKind::Literal(Literal::Sized(NumericType::Int32)),
+                TokenKind::Literal(Literal::Float),
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_big_integer() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("9223372036854775807 9223372036854775808".to_string());
+
+        let kinds = lexer
+            .parse()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect::<Vec<TokenKind>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Literal(Literal::Int),
+                TokenKind::Literal(Literal::BigInt),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_characters() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("'é' '\\''".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![
+                Token {
+                    kind: TokenKind::Literal(Literal::Char),
+                    start_pos: 0,
+                    end_pos: 3,
+                    line: 1,
+                },
+                Token {
+                    kind: TokenKind::Literal(Literal::Char),
+                    start_pos: 4,
+                    end_pos: 8,
+                    line: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_ellipsis() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("...rest".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![
+                Token {
+                    kind: TokenKind::Ellipsis,
+                    start_pos: 0,
+                    end_pos: 3,
+                    line: 1,
+                },
+                Token {
+                    kind: TokenKind::Identifier,
+                    start_pos: 3,
+                    end_pos: 7,
+                    line: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_ellipsis_at_end_of_input() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("...".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![Token {
+                kind: TokenKind::Ellipsis,
+                start_pos: 0,
+                end_pos: 3,
+                line: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn scan_hexadecimal_integer() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("0x1F".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![Token {
+                kind: TokenKind::Literal(Literal::Int),
+                start_pos: 0,
+                end_pos: 4,
+                line: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn scan_malformed_radix_integers() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("0b12 0x".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![
+                Token {
+                    kind: TokenKind::Literal(Literal::BigInt),
+                    start_pos: 0,
+                    end_pos: 4,
+                    line: 1,
+                },
+                Token {
+                    kind: TokenKind::Literal(Literal::BigInt),
+                    start_pos: 5,
+                    end_pos: 7,
+                    line: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_string_with_escaped_quote() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("\"a\\\"b\"".to_string());
+
+        assert_eq!(
+            lexer.parse(),
+            vec![Token {
+                kind: TokenKind::Literal(Literal::String),
+                start_pos: 0,
+                end_pos: 6,
+                line: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn scan_optional_operators() {
+        let mut lexer = Lexer::new();
+        lexer.add_text("a?.b ?? Int?".to_string());
+
+        let kinds = lexer
+            .parse()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect::<Vec<TokenKind>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Identifier,
+                TokenKind::QuestionDot,
+                TokenKind::Identifier,
+                TokenKind::QuestionQuestion,
+                TokenKind::Type,
+                TokenKind::QuestionMark,
+            ]
+        );
+    }
+
     #[test]
     fn scan_type() {
         let mut lexer = Lexer::new();
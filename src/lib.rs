@@ -10,38 +10,35 @@
 //! A simple infix addition operation like `1 + 2` can be converted to a token sequence like this:
 //!
 //! ```rust
-//! use magc::{Lexer, Parser};
+//! use magc::{Lexer, Literal, Token, TokenKind};
 //!
 //! // Add some text to the lexer's input buffer and parse it into a sequence of tokens.
 //! let tokens = Lexer::new()
-//!     .add_text("1 + 2");
+//!     .add_text("1 + 2".to_string())
 //!     .parse();
 //!
 //! assert_eq!(
 //!     tokens,
-//!     Ok(
-//!        vec![
-//!            Token {
-//!                kind: Literal(
-//!                    Int,
-//!                ),
-//!                start_pos: 0,
-//!                end_pos: 1,
-//!            },
-//!            Token {
-//!                kind: Plus,
-//!                start_pos: 2,
-//!                end_pos: 3,
-//!            },
-//!            Token {
-//!                kind: Literal(
-//!                    Int,
-//!                ),
-//!                start_pos: 4,
-//!                end_pos: 5,
-//!            },
-//!        ]
-//!     ),
+//!     vec![
+//!         Token {
+//!             kind: TokenKind::Literal(Literal::Int),
+//!             start_pos: 0,
+//!             end_pos: 1,
+//!             line: 1,
+//!         },
+//!         Token {
+//!             kind: TokenKind::Plus,
+//!             start_pos: 2,
+//!             end_pos: 3,
+//!             line: 1,
+//!         },
+//!         Token {
+//!             kind: TokenKind::Literal(Literal::Int),
+//!             start_pos: 4,
+//!             end_pos: 5,
+//!             line: 1,
+//!         },
+//!     ],
 //! );
 //! ```
 //!
@@ -50,7 +47,7 @@
 //! * [`Token`]: The smallest possible entity in the language, like a number, an identifier, or a keyword.
 //! * [`Expression`]: A syntactic entity that may be evaluated to determine its value.
 //! * [`Pattern`]: A pattern that can be matched with an [`Expression`] to enable complex flow control
-//! and destructuring.
+//!   and destructuring.
 //!
 //! ## Compilation Pipeline
 //!
@@ -73,8 +70,6 @@
 //!
 //! However, since there are a few syntactical differences to the original Magpie language, the two languages are *source-incompatible* and thus have different names. In particular, Bob's implementation substitutes the dot commonly used for calling methods on objects with a space (usually a meaningless character), which I find rather unintuitive, especially for new programmers.
 
-#![feature(type_ascription)]
-
 pub mod compiler;
 pub mod helpers;
 pub mod lexer;
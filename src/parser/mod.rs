@@ -1,12 +1,15 @@
 //! Assemble a token sequence into a tree of expressions.
 
+use crate::type_system::NumericType;
 use crate::types::{Expression, Keyword, Literal, ParserError, Token, TokenKind};
 use unicode_segmentation::UnicodeSegmentation;
 
 use parselets::{
-    BlockParselet, CallParselet, ConditionalParselet, FieldPatternParselet, InfixOperatorParselet,
-    InfixParselet, ListParselet, LiteralParselet, MemberParselet, MethodParselet, PairParselet,
-    PrefixOperatorParselet, PrefixParselet, TuplePatternParselet, VariablePatternParselet,
+    BlockParselet, CallParselet, ConditionalParselet, EnumParselet, FieldPatternParselet,
+    IndexParselet, InfixOperatorParselet, InfixParselet, InterfaceParselet, ListParselet,
+    LiteralParselet, MemberParselet, MethodParselet, PairParselet, PrefixOperatorParselet,
+    PrefixParselet, RestPatternParselet, SafeMemberParselet, TuplePatternParselet, TypeParselet,
+    VariablePatternParselet,
 };
 
 use std::collections::HashMap;
@@ -28,12 +31,16 @@ pub static PREC_LOGICAL: usize = 30;
 pub static PREC_EQUALITY: usize = 40;
 /// The precedence `50` for comparison operators like `<`, `>`, `<=` and `>=`.
 pub static PREC_COMPARISON: usize = 50;
+/// The precedence `55` for the null-coalescing operator like `name ?? "anonymous"`.
+pub static PREC_COALESCE: usize = 55;
 /// The precedence `60` for term operators like `+`, `-`.
 pub static PREC_TERM: usize = 60;
 /// The precedence `70` for multiplications or divisions, for example `*`, `/` or `%`.
 pub static PREC_PRODUCT: usize = 70;
 /// The precedence `80` for exponents like `2 ^ 16`.
 pub static PREC_EXPONENT: usize = 80;
+/// The precedence `85` for conversions like `n as Float`.
+pub static PREC_CONVERSION: usize = 85;
 /// The precedence `90` for unary operators, like `!isValid`.
 pub static PREC_UNARY: usize = 90;
 /// The precedence `100` for method calls, like `fib(0)`.
@@ -57,6 +64,12 @@ fn infix_operator(precedence: usize) -> Rc<dyn InfixParselet> {
     Rc::new(InfixOperatorParselet { precedence }) as Rc<dyn InfixParselet>
 }
 
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Parser {
     pub fn new() -> Self {
         let mut prefix_parselets = HashMap::new();
@@ -66,6 +79,7 @@ impl Parser {
             TokenKind::Identifier,
             &VariablePatternParselet as &dyn PrefixParselet,
         );
+        prefix_parselets.insert(TokenKind::Type, &TypeParselet as &dyn PrefixParselet);
         //prefix_parselets.insert(TokenKind::LeftParen,  &TuplePatternParselet      as &dyn PrefixParselet);
 
         prefix_parselets.insert(
@@ -84,6 +98,25 @@ impl Parser {
             TokenKind::Literal(Literal::String),
             &LiteralParselet as &dyn PrefixParselet,
         );
+        prefix_parselets.insert(
+            TokenKind::Literal(Literal::Nil),
+            &LiteralParselet as &dyn PrefixParselet,
+        );
+        prefix_parselets.insert(
+            TokenKind::Literal(Literal::BigInt),
+            &LiteralParselet as &dyn PrefixParselet,
+        );
+        prefix_parselets.insert(
+            TokenKind::Literal(Literal::Char),
+            &LiteralParselet as &dyn PrefixParselet,
+        );
+
+        for numeric_type in NumericType::ALL {
+            prefix_parselets.insert(
+                TokenKind::Literal(Literal::Sized(numeric_type)),
+                &LiteralParselet as &dyn PrefixParselet,
+            );
+        }
 
         prefix_parselets.insert(
             TokenKind::Keyword(Keyword::If),
@@ -93,6 +126,19 @@ impl Parser {
             TokenKind::Keyword(Keyword::Def),
             &MethodParselet as &dyn PrefixParselet,
         );
+        prefix_parselets.insert(
+            TokenKind::Keyword(Keyword::Enum),
+            &EnumParselet as &dyn PrefixParselet,
+        );
+        prefix_parselets.insert(
+            TokenKind::Keyword(Keyword::Interface),
+            &InterfaceParselet as &dyn PrefixParselet,
+        );
+        // `this` refers to the receiver of a method like any other parameter.
+        prefix_parselets.insert(
+            TokenKind::Keyword(Keyword::This),
+            &VariablePatternParselet as &dyn PrefixParselet,
+        );
         prefix_parselets.insert(
             TokenKind::Keyword(Keyword::Do),
             &BlockParselet as &dyn PrefixParselet,
@@ -115,6 +161,10 @@ impl Parser {
             &TuplePatternParselet as &dyn PrefixParselet,
         );
         prefix_parselets.insert(TokenKind::LeftBracket, &ListParselet as &dyn PrefixParselet);
+        prefix_parselets.insert(
+            TokenKind::Ellipsis,
+            &RestPatternParselet as &dyn PrefixParselet,
+        );
 
         infix_parselets.insert(TokenKind::Plus, infix_operator(PREC_TERM));
         infix_parselets.insert(TokenKind::Minus, infix_operator(PREC_TERM));
@@ -128,6 +178,16 @@ impl Parser {
         infix_parselets.insert(TokenKind::Smaller, infix_operator(PREC_COMPARISON));
         infix_parselets.insert(TokenKind::SmallerEqual, infix_operator(PREC_COMPARISON));
         infix_parselets.insert(TokenKind::Percent, infix_operator(PREC_PRODUCT));
+        infix_parselets.insert(TokenKind::Caret, infix_operator(PREC_EXPONENT));
+        infix_parselets.insert(TokenKind::QuestionQuestion, infix_operator(PREC_COALESCE));
+        infix_parselets.insert(
+            TokenKind::Keyword(Keyword::Is),
+            infix_operator(PREC_COMPARISON),
+        );
+        infix_parselets.insert(
+            TokenKind::Keyword(Keyword::As),
+            infix_operator(PREC_CONVERSION),
+        );
 
         infix_parselets.insert(
             TokenKind::Comma,
@@ -145,6 +205,14 @@ impl Parser {
             TokenKind::Dot,
             Rc::new(MemberParselet) as Rc<dyn InfixParselet>,
         );
+        infix_parselets.insert(
+            TokenKind::LeftBracket,
+            Rc::new(IndexParselet) as Rc<dyn InfixParselet>,
+        );
+        infix_parselets.insert(
+            TokenKind::QuestionDot,
+            Rc::new(SafeMemberParselet) as Rc<dyn InfixParselet>,
+        );
 
         Self {
             position: 0,
@@ -157,8 +225,7 @@ impl Parser {
 
     // Add tokens and their corresponding graphemes to the buffer.
     pub fn add_tokens(&mut self, source: String, mut tokens: Vec<Token>) {
-        self.source
-            .extend(source.graphemes(true).map(String::from));
+        self.source.extend(source.graphemes(true).map(String::from));
         self.tokens.append(&mut tokens);
     }
 
@@ -185,8 +252,6 @@ impl Parser {
     /// Parse a single expression with the given precedence.
     pub fn parse_expression(&mut self, precedence: usize) -> Result<Expression, ParserError> {
         let token = self.consume();
-        let start_pos = token.start_pos;
-        let mut end_pos = token.end_pos;
 
         // Let's see if we find a prefix parselet for the current token.
         if let Some(prefix) = self.prefix_parselets.get(&token.kind) {
@@ -194,23 +259,11 @@ impl Parser {
             // simple expressions like numbers, strings or variable identifiers.
             let mut left = prefix.parse(self, token.clone())?;
 
-            if self.eof() {
-                end_pos = token.end_pos;
-
-                return Ok(Expression {
-                    kind: left.kind,
-
-                    start_pos,
-                    end_pos,
-                });
-            }
-
             // This is the bit where real magic happens. This conditional check right here
             // is responsible for parsing infix expressions with the right precedence and
             // associativity so we can do math and generally have useful operators.
             while !self.eof() && precedence < self.get_precedence()? {
                 let token = self.peek()?;
-                end_pos = token.end_pos;
 
                 // Hand control over to the infix parselet if there is one, and
                 // insert the previously parsed expression into this structure.
@@ -219,14 +272,9 @@ impl Parser {
                 }
             }
 
-            Ok(Expression {
-                kind: left.kind,
-
-                start_pos,
-                end_pos,
-            })
+            Ok(left)
         } else {
-            return Err(ParserError::MissingPrefixParselet(token.clone().kind));
+            Err(ParserError::MissingPrefixParselet(token.clone().kind))
         }
     }
 
@@ -9,7 +9,7 @@ use std::collections::BTreeMap;
 pub struct BlockParselet;
 
 impl PrefixParselet for BlockParselet {
-    fn parse(&self, parser: &mut Parser, _token: Token) -> ParserResult {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
         let mut children = vec![];
 
         while !parser.eof() {
@@ -28,8 +28,8 @@ impl PrefixParselet for BlockParselet {
                 environment: BTreeMap::new(),
                 children,
             }),
-            start_pos: 0,
-            end_pos: 0,
+            start_pos: token.start_pos,
+            end_pos: token.end_pos,
         })
     }
 }
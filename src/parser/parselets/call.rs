@@ -19,11 +19,12 @@ impl CallParselet {
         }
     }
 
-    fn expect_typeless_variable_pattern(
-        &self,
-        expression: Box<Expression>,
-    ) -> Result<String, ParserError> {
+    /// Get the name of the called method, which is either a variable without a type
+    /// annotation like `fib`, or a type like `Point` whose constructor is called.
+    fn expect_method_name(&self, expression: Box<Expression>) -> Result<String, ParserError> {
         match expression.kind {
+            ExpressionKind::Type(type_id) if type_id.arguments.is_empty() => Ok(type_id.name()),
+
             ExpressionKind::Pattern(Pattern::Variable(pattern)) => {
                 if let VariablePattern {
                     name: Some(name),
@@ -43,7 +44,7 @@ impl CallParselet {
 
 impl InfixParselet for CallParselet {
     fn parse(&self, parser: &mut Parser, left: Box<Expression>, token: Token) -> ParserResult {
-        let name = self.expect_typeless_variable_pattern(left)?;
+        let name = self.expect_method_name(left)?;
         parser.consume_expect(TokenKind::LeftParen)?;
 
         let t = parser.peek()?;
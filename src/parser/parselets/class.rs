@@ -0,0 +1,153 @@
+//! Parse a class definition like `def class Point(x Float, y Float)`.
+
+use crate::parser::parselets::TypeParselet;
+use crate::parser::{Parser, ParserError, ParserResult};
+use crate::types::{
+    Class, Expression, ExpressionKind, Keyword, Pattern, Token, TokenKind, TypeExpression,
+    VariablePattern,
+};
+
+/// Parse a class definition like `def class Point(x Float, y Float)`.
+#[derive(Debug, Clone)]
+pub struct ClassParselet;
+
+impl ClassParselet {
+    /// Parse the name and fields of a class, starting after the `class` identifier
+    /// of a definition whose `def` keyword is the given token.
+    pub fn parse_class(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let name = parser.consume_expect(TokenKind::Type)?;
+        let (fields, mut end) = self.parse_fields(parser)?;
+        let interfaces = self.parse_interfaces(parser, &mut end)?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Class(Class {
+                name: parser.get_lexeme(name.start_pos, name.end_pos)?,
+                fields,
+                interfaces,
+            }),
+            start_pos: token.start_pos,
+            end_pos: end.end_pos,
+        })
+    }
+
+    /// Parse an optional list of implemented interfaces like `with Shape, Printable`,
+    /// updating the given token to the last one which is part of the list.
+    pub fn parse_interfaces(
+        &self,
+        parser: &mut Parser,
+        end: &mut Token,
+    ) -> Result<Vec<TypeExpression>, ParserError> {
+        let mut interfaces = vec![];
+
+        if parser.eof() || parser.peek()?.kind != TokenKind::Keyword(Keyword::With) {
+            return Ok(interfaces);
+        }
+
+        parser.advance();
+
+        loop {
+            *end = parser.consume_expect(TokenKind::Type)?;
+            interfaces.push(TypeParselet.parse_type(parser, end.clone())?);
+
+            if !parser.eof() && parser.peek()?.kind == TokenKind::Comma {
+                parser.advance();
+            } else {
+                break;
+            }
+        }
+
+        Ok(interfaces)
+    }
+
+    /// Parse a parenthesized list of fields like `(x Float, y Float)`, returning them
+    /// along with the closing parenthesis.
+    pub fn parse_fields(
+        &self,
+        parser: &mut Parser,
+    ) -> Result<(Vec<VariablePattern>, Token), ParserError> {
+        parser.consume_expect(TokenKind::LeftParen)?;
+
+        let fields = match parser.peek()?.kind {
+            TokenKind::RightParen => vec![],
+
+            _ => parser
+                .parse_expression(0)?
+                .expect_pattern()?
+                .flatten_pairs()
+                .into_iter()
+                .map(|field| self.expect_named_field(field))
+                .collect::<Result<Vec<VariablePattern>, ParserError>>()?,
+        };
+
+        Ok((fields, parser.consume_expect(TokenKind::RightParen)?))
+    }
+
+    /// Each field needs a name, under which its getter is defined.
+    fn expect_named_field(&self, field: Pattern) -> Result<VariablePattern, ParserError> {
+        match field {
+            Pattern::Variable(variable) if variable.name.is_some() => Ok(variable),
+
+            _ => Err(ParserError::UnexpectedPattern {
+                expected: String::from("a named field like `x Float`"),
+                found: format!("{}", field),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{Class, ExpressionKind, ParserError, TypeExpression, VariablePattern};
+
+    fn parse(source: &str) -> Result<ExpressionKind, ParserError> {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        Ok(parser.parse()?.remove(0).kind)
+    }
+
+    #[test]
+    fn parse_class_with_fields() {
+        assert_eq!(
+            parse("def class Point(x Float, y Float)"),
+            Ok(ExpressionKind::Class(Class {
+                name: "Point".to_string(),
+                fields: vec![
+                    VariablePattern {
+                        name: Some("x".to_string()),
+                        type_id: Some(TypeExpression::named("Float")),
+                    },
+                    VariablePattern {
+                        name: Some("y".to_string()),
+                        type_id: Some(TypeExpression::named("Float")),
+                    },
+                ],
+                interfaces: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_implemented_interfaces() {
+        match parse("def class Circle(radius Float) with Shape, Printable") {
+            Ok(ExpressionKind::Class(class)) => assert_eq!(
+                class.interfaces,
+                vec![
+                    TypeExpression::named("Shape"),
+                    TypeExpression::named("Printable")
+                ]
+            ),
+            result => panic!("expected a class definition, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn fields_need_a_name() {
+        assert!(parse("def class Point(_ Float)").is_err());
+    }
+}
@@ -6,7 +6,7 @@ use crate::types::{Conditional, Expression, ExpressionKind, Keyword, Token, Toke
 pub struct ConditionalParselet;
 
 impl PrefixParselet for ConditionalParselet {
-    fn parse(&self, parser: &mut Parser, _token: Token) -> ParserResult {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
         let condition = Box::new(parser.parse_expression(0)?);
         parser.consume_expect(TokenKind::Keyword(Keyword::Then))?;
         let then_arm = Box::new(parser.parse_expression(0)?);
@@ -24,8 +24,8 @@ impl PrefixParselet for ConditionalParselet {
                         then_arm,
                         else_arm: Some(else_arm),
                     }),
-                    start_pos: 0,
-                    end_pos: 0,
+                    start_pos: token.start_pos,
+                    end_pos: token.end_pos,
                 })
             } else {
                 parser.consume_expect(TokenKind::Keyword(Keyword::End))?;
@@ -36,8 +36,8 @@ impl PrefixParselet for ConditionalParselet {
                         then_arm,
                         else_arm: None,
                     }),
-                    start_pos: 0,
-                    end_pos: 0,
+                    start_pos: token.start_pos,
+                    end_pos: token.end_pos,
                 })
             }
         } else {
@@ -47,8 +47,8 @@ impl PrefixParselet for ConditionalParselet {
                     then_arm,
                     else_arm: None,
                 }),
-                start_pos: 0,
-                end_pos: 0,
+                start_pos: token.start_pos,
+                end_pos: token.end_pos,
             })
         }
     }
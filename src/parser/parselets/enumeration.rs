@@ -0,0 +1,93 @@
+//! Parse an enum definition like `enum Shape case Circle(radius Float) end`.
+
+use crate::parser::parselets::ClassParselet;
+use crate::parser::{Parser, ParserResult, PrefixParselet};
+use crate::types::{Class, Enum, Expression, ExpressionKind, Keyword, Token, TokenKind};
+
+/// Parse an enum definition with a `case` for each variant, which is terminated by `end`.
+///
+/// Variants without any fields may leave out the parentheses, like `case Empty`. Both the
+/// enum and its variants may implement interfaces, like `enum Shape with Printable`.
+#[derive(Debug, Clone)]
+pub struct EnumParselet;
+
+impl PrefixParselet for EnumParselet {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let mut name = parser.consume_expect(TokenKind::Type)?;
+        let type_name = parser.get_lexeme(name.start_pos, name.end_pos)?;
+        let interfaces = ClassParselet.parse_interfaces(parser, &mut name)?;
+        let mut variants = vec![];
+
+        while parser.peek()?.kind == TokenKind::Keyword(Keyword::Case) {
+            parser.advance();
+
+            let mut variant = parser.consume_expect(TokenKind::Type)?;
+            let name = parser.get_lexeme(variant.start_pos, variant.end_pos)?;
+            let fields = if parser.peek()?.kind == TokenKind::LeftParen {
+                ClassParselet.parse_fields(parser)?.0
+            } else {
+                vec![]
+            };
+
+            variants.push(Class {
+                name,
+                fields,
+                interfaces: ClassParselet.parse_interfaces(parser, &mut variant)?,
+            });
+        }
+
+        let end = parser.consume_expect(TokenKind::Keyword(Keyword::End))?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Enum(Enum {
+                name: type_name,
+                variants,
+                interfaces,
+            }),
+            start_pos: token.start_pos,
+            end_pos: end.end_pos,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{Class, Enum, ExpressionKind, TypeExpression, VariablePattern};
+
+    #[test]
+    fn parse_enum_with_variants() {
+        let source = "enum Shape\n  case Circle(radius Float)\n  case Empty\nend";
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        assert_eq!(
+            parser
+                .parse()
+                .map(|mut expressions| expressions.remove(0).kind),
+            Ok(ExpressionKind::Enum(Enum {
+                name: "Shape".to_string(),
+                variants: vec![
+                    Class {
+                        name: "Circle".to_string(),
+                        fields: vec![VariablePattern {
+                            name: Some("radius".to_string()),
+                            type_id: Some(TypeExpression::named("Float")),
+                        }],
+                        interfaces: vec![],
+                    },
+                    Class {
+                        name: "Empty".to_string(),
+                        fields: vec![],
+                        interfaces: vec![],
+                    },
+                ],
+                interfaces: vec![],
+            }))
+        );
+    }
+}
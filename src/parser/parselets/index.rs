@@ -0,0 +1,61 @@
+//! Parse an index like `name[0]` into a call of the built-in multimethod `[]`.
+
+use crate::parser::{InfixParselet, Parser, ParserResult, PREC_CALL};
+
+use crate::types::{Call, Expression, ExpressionKind, PairPattern, Pattern, Token, TokenKind};
+
+/// Parse an index like `name[0]` into a call like `[](name, 0)`, which can't be written
+/// directly since `[]` isn't a valid method name.
+#[derive(Debug, Clone)]
+pub struct IndexParselet;
+
+impl InfixParselet for IndexParselet {
+    fn parse(&self, parser: &mut Parser, left: Box<Expression>, _token: Token) -> ParserResult {
+        parser.advance();
+
+        let index = parser.parse_expression(0)?;
+        let end = parser.consume_expect(TokenKind::RightBracket)?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Call(Call {
+                name: String::from("[]"),
+                signature: Some(Pattern::Pair(PairPattern {
+                    left: Box::new(left.pattern_or_value_pattern()?),
+                    right: Box::new(index.pattern_or_value_pattern()?),
+                })),
+            }),
+
+            start_pos: left.start_pos,
+            end_pos: end.end_pos,
+        })
+    }
+
+    fn get_precedence(&self) -> usize {
+        PREC_CALL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{Call, ExpressionKind, Pattern};
+
+    #[test]
+    fn parses_an_index_into_a_call() {
+        let source = "name[0]".to_string();
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.clone());
+        parser.add_tokens(source, lexer.parse());
+
+        match parser.parse_expression(0).unwrap().kind {
+            ExpressionKind::Call(Call {
+                name,
+                signature: Some(Pattern::Pair(_)),
+            }) => assert_eq!(name, "[]"),
+            kind => panic!("expected a call, found {:?}", kind),
+        }
+    }
+}
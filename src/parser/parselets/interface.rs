@@ -0,0 +1,129 @@
+//! Parse an interface definition like `interface Shape def area(this) end`.
+
+use crate::parser::parselets::ClassParselet;
+use crate::parser::{Parser, ParserError, ParserResult, PrefixParselet};
+use crate::types::{
+    Expression, ExpressionKind, Interface, Keyword, MethodSignature, Token, TokenKind,
+};
+
+/// Parse an interface definition, which lists the signatures of the methods each of its
+/// implementations needs to provide and is terminated by `end`.
+#[derive(Debug, Clone)]
+pub struct InterfaceParselet;
+
+impl InterfaceParselet {
+    /// Parse a method signature without a body, like `def scale(this, factor Float)`.
+    fn parse_method_signature(&self, parser: &mut Parser) -> Result<MethodSignature, ParserError> {
+        parser.consume_expect(TokenKind::Keyword(Keyword::Def))?;
+
+        let name = parser.consume_expect(TokenKind::Identifier)?;
+
+        parser.consume_expect(TokenKind::LeftParen)?;
+
+        let signature = match parser.peek()?.kind {
+            TokenKind::RightParen => None,
+
+            _ => Some(
+                parser
+                    .parse_expression(0)?
+                    .pattern_or_value_pattern()?
+                    .into_signature()?,
+            ),
+        };
+
+        parser.consume_expect(TokenKind::RightParen)?;
+
+        Ok(MethodSignature {
+            name: parser.get_lexeme(name.start_pos, name.end_pos)?,
+            signature,
+        })
+    }
+}
+
+impl PrefixParselet for InterfaceParselet {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let mut name = parser.consume_expect(TokenKind::Type)?;
+        let type_name = parser.get_lexeme(name.start_pos, name.end_pos)?;
+        let interfaces = ClassParselet.parse_interfaces(parser, &mut name)?;
+        let mut methods = vec![];
+
+        while parser.peek()?.kind == TokenKind::Keyword(Keyword::Def) {
+            methods.push(self.parse_method_signature(parser)?);
+        }
+
+        let end = parser.consume_expect(TokenKind::Keyword(Keyword::End))?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Interface(Interface {
+                name: type_name,
+                methods,
+                interfaces,
+            }),
+            start_pos: token.start_pos,
+            end_pos: end.end_pos,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::ExpressionKind;
+
+    #[test]
+    fn parse_interface_with_methods() {
+        let source = "interface Shape\n  def area(this)\n  def scale(this, factor Float)\nend";
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        match parser.parse().unwrap().remove(0).kind {
+            ExpressionKind::Interface(interface) => {
+                assert_eq!(interface.name, "Shape");
+                assert_eq!(
+                    interface
+                        .methods
+                        .iter()
+                        .map(|method| format!(
+                            "{}({})",
+                            method.name,
+                            method.signature.clone().unwrap()
+                        ))
+                        .collect::<Vec<String>>(),
+                    vec!["area(this)", "scale(this, factor: Float)"]
+                );
+            }
+            kind => panic!("expected an interface definition, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn parse_interface_extending_others() {
+        let source = "interface Shape with Printable, Comparable
+  def area(this)
+end";
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        match parser.parse().unwrap().remove(0).kind {
+            ExpressionKind::Interface(interface) => {
+                assert_eq!(
+                    interface
+                        .interfaces
+                        .iter()
+                        .map(|supertype| supertype.name())
+                        .collect::<Vec<String>>(),
+                    vec!["Printable", "Comparable"]
+                );
+                assert_eq!(interface.methods.len(), 1);
+            }
+            kind => panic!("expected an interface definition, found {:?}", kind),
+        }
+    }
+}
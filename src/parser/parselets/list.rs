@@ -9,21 +9,19 @@ use crate::types::{Expression, ExpressionKind, Token, TokenKind};
 pub struct ListParselet;
 
 impl PrefixParselet for ListParselet {
-    fn parse(&self, parser: &mut Parser, _token: Token) -> ParserResult {
-        let kind;
-
-        if parser.peek()?.kind == TokenKind::RightBracket {
-            kind = ExpressionKind::List(None);
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let kind = if parser.peek()?.kind == TokenKind::RightBracket {
+            ExpressionKind::List(None)
         } else {
-            kind = ExpressionKind::List(Some(Box::new(parser.parse_expression(0)?)));
-        }
+            ExpressionKind::List(Some(Box::new(parser.parse_expression(0)?)))
+        };
 
-        parser.consume_expect(TokenKind::RightBracket)?;
+        let end = parser.consume_expect(TokenKind::RightBracket)?;
 
         Ok(Expression {
             kind,
-            start_pos: 0,
-            end_pos: 0,
+            start_pos: token.start_pos,
+            end_pos: end.end_pos,
         })
     }
 }
@@ -1,13 +1,32 @@
-use crate::parser::{Parser, ParserResult, PrefixParselet};
+use crate::helpers::{parse_big_int_literal, parse_char_literal};
+use crate::parser::{Parser, ParserError, ParserResult, PrefixParselet};
 
-use crate::types::{Expression, ExpressionKind, Token, TokenKind};
+use crate::types::{Expression, ExpressionKind, Literal, Token, TokenKind};
 
-/// Parse a literal expression like `"Mike"`, `27`, `3.141`, `true`, or `false`.
+/// Parse a literal expression like `"Mike"`, `'M'`, `27`, `3.141`, `true`, `false` or `nil`.
 pub struct LiteralParselet;
 
 impl PrefixParselet for LiteralParselet {
-    fn parse(&self, _parser: &mut Parser, token: Token) -> ParserResult {
-        let kind = match token.kind {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let kind = match token.kind.clone() {
+            TokenKind::Literal(Literal::Char) => {
+                let lexeme = parser.get_lexeme(token.start_pos, token.end_pos)?;
+
+                if parse_char_literal(&lexeme).is_none() {
+                    return Err(ParserError::InvalidCharacter(token));
+                }
+
+                ExpressionKind::Literal(Literal::Char)
+            }
+            TokenKind::Literal(literal @ (Literal::Int | Literal::BigInt)) => {
+                let lexeme = parser.get_lexeme(token.start_pos, token.end_pos)?;
+
+                if parse_big_int_literal(&lexeme).is_none() {
+                    return Err(ParserError::InvalidInteger(token));
+                }
+
+                ExpressionKind::Literal(literal)
+            }
             TokenKind::Literal(literal) => ExpressionKind::Literal(literal),
             _ => unreachable!(),
         };
@@ -20,3 +39,41 @@ impl PrefixParselet for LiteralParselet {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::{Parser, ParserError};
+    use crate::types::ExpressionKind;
+
+    fn parse(source: &str) -> Result<Vec<ExpressionKind>, ParserError> {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        parser.parse().map(|expressions| {
+            expressions
+                .into_iter()
+                .map(|expression| expression.kind)
+                .collect()
+        })
+    }
+
+    #[test]
+    fn radix_integers_are_literals() {
+        assert_eq!(parse("0x1F 0o17 0b101").map(|kinds| kinds.len()), Ok(3));
+    }
+
+    #[test]
+    fn digits_outside_the_radix_are_rejected() {
+        assert!(matches!(parse("0b12"), Err(ParserError::InvalidInteger(_))));
+        assert!(matches!(parse("0o8"), Err(ParserError::InvalidInteger(_))));
+    }
+
+    #[test]
+    fn radix_prefix_without_digits_is_rejected() {
+        assert!(matches!(parse("0x"), Err(ParserError::InvalidInteger(_))));
+    }
+}
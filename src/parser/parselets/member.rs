@@ -2,6 +2,8 @@
 //!
 //! A member is transformed into a method call similar to `favoriteColor(person)`
 //! while pattern matching ensures the right multimethod is executed for the given type.
+//! With the safe navigation operator like in `person?.favoriteColor`, the method is only
+//! called if the person isn't `nil`.
 
 use crate::parser::{InfixParselet, Parser, ParserError, ParserResult, PREC_CALL};
 
@@ -24,29 +26,66 @@ impl MemberParselet {
             _ => Err(ParserError::ExpectedPattern),
         }
     }
-}
 
-impl InfixParselet for MemberParselet {
-    fn parse(&self, parser: &mut Parser, left: Box<Expression>, token: Token) -> ParserResult {
+    /// Parse the name of the member following the operator into a call on the left side,
+    /// returning it along with the position where the name ends.
+    fn parse_member(
+        &self,
+        parser: &mut Parser,
+        left: Box<Expression>,
+    ) -> Result<(Call, usize), ParserError> {
         parser.advance();
 
         let right = parser.parse_expression(PREC_CALL)?;
+        let end_pos = right.end_pos;
 
         let name_opt = self.expect_typeless_variable_pattern(Box::new(right))?;
 
         let signature = Some(left.expect_pattern()?);
 
         if let Some(name) = name_opt {
-            Ok(Expression {
-                kind: ExpressionKind::Call(Call { name, signature }),
-
-                start_pos: token.start_pos,
-                end_pos: token.end_pos,
-            })
+            Ok((Call { name, signature }, end_pos))
         } else {
             Err(ParserError::ExpectedPattern)
         }
     }
+}
+
+impl InfixParselet for MemberParselet {
+    fn parse(&self, parser: &mut Parser, left: Box<Expression>, _token: Token) -> ParserResult {
+        let start_pos = left.start_pos;
+        let (call, end_pos) = self.parse_member(parser, left)?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Call(call),
+
+            start_pos,
+            end_pos,
+        })
+    }
+
+    fn get_precedence(&self) -> usize {
+        PREC_CALL
+    }
+}
+
+/// Parse a safe navigation like `person?.favoriteColor`, which evaluates to `nil` instead
+/// of calling the method if the person is `nil`.
+#[derive(Debug, Clone)]
+pub struct SafeMemberParselet;
+
+impl InfixParselet for SafeMemberParselet {
+    fn parse(&self, parser: &mut Parser, left: Box<Expression>, _token: Token) -> ParserResult {
+        let start_pos = left.start_pos;
+        let (call, end_pos) = MemberParselet.parse_member(parser, left)?;
+
+        Ok(Expression {
+            kind: ExpressionKind::SafeCall(call),
+
+            start_pos,
+            end_pos,
+        })
+    }
 
     fn get_precedence(&self) -> usize {
         PREC_CALL
@@ -75,7 +114,7 @@ mod tests {
                 }),
 
                 start_pos: 0,
-                end_pos: instance.len() + member.len(),
+                end_pos: format!("{}.{}", instance, member).len(),
             }]),
             {
                 let mut parser = Parser::new();
@@ -90,4 +129,27 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn parses_safe_navigation_into_safe_call() {
+        let mut parser = Parser::new();
+        let mut lexer = Lexer::new();
+        let text = "person?.favoriteColor".to_string();
+
+        lexer.add_text(text.clone());
+        parser.add_tokens(text, lexer.parse());
+
+        let expression = parser.parse().unwrap().remove(0);
+
+        assert_eq!(
+            expression.kind,
+            ExpressionKind::SafeCall(Call {
+                name: "favoriteColor".to_string(),
+                signature: Some(Pattern::Variable(VariablePattern {
+                    name: Some("person".to_string()),
+                    type_id: None,
+                })),
+            })
+        );
+    }
 }
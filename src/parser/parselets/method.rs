@@ -1,5 +1,9 @@
+use crate::parser::parselets::{ClassParselet, TypeParselet};
 use crate::parser::{Parser, ParserError, ParserResult, PrefixParselet};
-use crate::types::{Expression, ExpressionKind, Method, Pattern, Token, TokenKind, ValuePattern};
+use crate::types::{
+    Expression, ExpressionKind, Keyword, Method, MethodQualifier, Pattern, Token, TokenKind,
+    TypeExpression, ValuePattern,
+};
 
 #[derive(Debug, Clone)]
 /// Parse a multimethod definition like `def fib(n Int) fib(n - 1) + fib(n - 2)`
@@ -16,50 +20,178 @@ impl MethodParselet {
             _ => Ok(Pattern::Value(ValuePattern { expression })),
         }
     }
+
+    /// Parse the type parameters of a generic method like `[T, U]` following its name.
+    fn parse_type_parameters(&self, parser: &mut Parser) -> Result<Vec<String>, ParserError> {
+        let mut type_parameters = vec![];
+
+        if parser.peek()?.kind != TokenKind::LeftBracket {
+            return Ok(type_parameters);
+        }
+
+        parser.advance();
+
+        loop {
+            let parameter = parser.consume_expect(TokenKind::Type)?;
+            type_parameters.push(parser.get_lexeme(parameter.start_pos, parameter.end_pos)?);
+
+            if parser.peek()?.kind == TokenKind::Comma {
+                parser.advance();
+            } else {
+                break;
+            }
+        }
+
+        parser.consume_expect(TokenKind::RightBracket)?;
+
+        Ok(type_parameters)
+    }
+
+    /// Parse an optional return type like `T` following the method signature.
+    ///
+    /// A type followed by arguments is a constructor call like `Point(0.0, 0.0)`, which
+    /// is the body of the method rather than its return type.
+    fn parse_return_type(
+        &self,
+        parser: &mut Parser,
+    ) -> Result<Option<TypeExpression>, ParserError> {
+        if parser.eof() || parser.peek()?.kind != TokenKind::Type {
+            return Ok(None);
+        }
+
+        let position = parser.position;
+        let token = parser.consume_expect(TokenKind::Type)?;
+        let return_type = TypeParselet.parse_type(parser, token)?;
+
+        if !parser.eof() && parser.peek()?.kind == TokenKind::LeftParen {
+            parser.position = position;
+
+            Ok(None)
+        } else {
+            Ok(Some(return_type))
+        }
+    }
+
+    /// Parse an optional guard clause like `where n < 0` following the method signature.
+    fn parse_guard(&self, parser: &mut Parser) -> Result<Option<Box<Expression>>, ParserError> {
+        if !parser.eof() && parser.peek()?.kind == TokenKind::Keyword(Keyword::Where) {
+            parser.advance();
+
+            Ok(Some(Box::new(parser.parse_expression(0)?)))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl PrefixParselet for MethodParselet {
-    fn parse(&self, parser: &mut Parser, _token: Token) -> ParserResult {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
         // We'll implement complex signatures with receivers, getters and setters later,
         // so we just parse a simple method signature for now.
-        let method_name = parser.consume_expect(TokenKind::Identifier)?;
-
-        parser.consume_expect(TokenKind::LeftParen)?;
-
-        let kind = match parser.peek()?.kind {
-            // Empty method signature.
-            TokenKind::RightParen => {
-                parser.consume_expect(TokenKind::RightParen)?;
+        let mut method_name = parser.consume_expect(TokenKind::Identifier)?;
+        let lexeme = parser.get_lexeme(method_name.start_pos, method_name.end_pos)?;
 
-                let body = Box::new(parser.parse_expression(0)?);
+        // Class definitions like `def class Point(x Float)` are followed by a type name,
+        // which distinguishes them from a method named `class`.
+        if lexeme == "class" && parser.peek()?.kind == TokenKind::Type {
+            return ClassParselet.parse_class(parser, token);
+        }
 
-                ExpressionKind::Method(Method {
-                    name: parser.get_lexeme(method_name.start_pos, method_name.end_pos)?,
-                    signature: None,
-                    body,
-                })
+        // Auxiliary methods like `def before log(message)` are preceded by a qualifier,
+        // which is only treated as such if it is followed by the method's name.
+        let qualifier = match MethodQualifier::from_name(&lexeme) {
+            Some(qualifier) if parser.peek()?.kind == TokenKind::Identifier => {
+                method_name = parser.consume_expect(TokenKind::Identifier)?;
+                Some(qualifier)
             }
+            _ => None,
+        };
 
-            _ => {
-                let signature =
-                    Some(self.pattern_or_value_pattern(Box::new(parser.parse_expression(0)?))?);
+        let type_parameters = self.parse_type_parameters(parser)?;
 
-                parser.consume_expect(TokenKind::RightParen)?;
+        parser.consume_expect(TokenKind::LeftParen)?;
 
-                let body = Box::new(parser.parse_expression(0)?);
+        let signature = match parser.peek()?.kind {
+            // Empty method signature.
+            TokenKind::RightParen => None,
 
-                ExpressionKind::Method(Method {
-                    name: parser.get_lexeme(method_name.start_pos, method_name.end_pos)?,
-                    signature,
-                    body,
-                })
-            }
+            _ => Some(
+                self.pattern_or_value_pattern(Box::new(parser.parse_expression(0)?))?
+                    .into_signature()?,
+            ),
         };
 
+        parser.consume_expect(TokenKind::RightParen)?;
+
+        let return_type = self.parse_return_type(parser)?;
+        let guard = self.parse_guard(parser)?;
+        let body = Box::new(parser.parse_expression(0)?);
+        let end_pos = body.end_pos;
+
         Ok(Expression {
-            kind,
-            start_pos: 0,
-            end_pos: 0,
+            kind: ExpressionKind::Method(Method {
+                name: parser.get_lexeme(method_name.start_pos, method_name.end_pos)?,
+                qualifier,
+                type_parameters,
+                signature,
+                return_type,
+                guard,
+                body,
+            }),
+            start_pos: token.start_pos,
+            end_pos,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{ExpressionKind, Method, MethodQualifier, TypeExpression};
+
+    fn parse_method(source: &str) -> Method {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        match parser.parse().unwrap().remove(0).kind {
+            ExpressionKind::Method(method) => method,
+            _ => panic!("expected a method definition"),
+        }
+    }
+
+    #[test]
+    fn parse_auxiliary_method() {
+        let method = parse_method("def before log(message) print(message)");
+
+        assert_eq!(method.name, "log");
+        assert_eq!(method.qualifier, Some(MethodQualifier::Before));
+    }
+
+    #[test]
+    fn qualifier_name_is_a_valid_method_name() {
+        let method = parse_method("def after(n) n");
+
+        assert_eq!(method.name, "after");
+        assert_eq!(method.qualifier, None);
+    }
+
+    #[test]
+    fn parse_generic_method() {
+        let method = parse_method("def first[T](list List[T]) T list");
+
+        assert_eq!(method.name, "first");
+        assert_eq!(method.type_parameters, vec![String::from("T")]);
+        assert_eq!(method.return_type, Some(TypeExpression::named("T")));
+    }
+
+    #[test]
+    fn constructor_call_is_not_a_return_type() {
+        let method = parse_method("def origin() Point(0.0, 0.0)");
+
+        assert_eq!(method.return_type, None);
+    }
+}
@@ -4,25 +4,35 @@ use crate::types::{Expression, Token};
 
 mod block;
 mod call;
+mod class;
 mod conditional;
+mod enumeration;
+mod index;
 mod infix;
+mod interface;
 mod list;
 mod literal;
 mod member;
 mod method;
 mod pattern;
 mod prefix;
+mod type_expression;
 
 pub use self::block::*;
 pub use self::call::*;
+pub use self::class::*;
 pub use self::conditional::*;
+pub use self::enumeration::*;
+pub use self::index::*;
 pub use self::infix::*;
+pub use self::interface::*;
 pub use self::list::*;
 pub use self::literal::*;
 pub use self::member::*;
 pub use self::method::*;
 pub use self::pattern::*;
 pub use self::prefix::*;
+pub use self::type_expression::*;
 
 pub trait PrefixParselet {
     fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult;
@@ -1,9 +1,11 @@
 mod field;
 mod pair;
+mod rest;
 mod tuple;
 mod variable;
 
 pub use self::field::*;
 pub use self::pair::*;
+pub use self::rest::*;
 pub use self::tuple::*;
 pub use self::variable::*;
@@ -0,0 +1,34 @@
+//! Parse a rest pattern like `...rest` which collects the remaining elements of a sequence.
+
+use crate::parser::{Parser, ParserResult, PrefixParselet};
+
+use crate::types::{Expression, ExpressionKind, Pattern, RestPattern, Token, TokenKind};
+
+#[derive(Debug, Clone)]
+/// Parse a rest pattern like `...rest` or an anonymous `...`.
+pub struct RestPatternParselet;
+
+impl PrefixParselet for RestPatternParselet {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let mut name = None;
+        let mut end_pos = token.end_pos;
+
+        if !parser.eof() && parser.peek()?.kind == TokenKind::Identifier {
+            let identifier = parser.consume();
+            let lexeme = parser.get_lexeme(identifier.start_pos, identifier.end_pos)?;
+
+            end_pos = identifier.end_pos;
+
+            if lexeme != "_" {
+                name = Some(lexeme);
+            }
+        }
+
+        Ok(Expression {
+            kind: ExpressionKind::Pattern(Pattern::Rest(RestPattern { name })),
+
+            start_pos: token.start_pos,
+            end_pos,
+        })
+    }
+}
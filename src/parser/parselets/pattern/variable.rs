@@ -1,5 +1,6 @@
 //! Parse a variable identifier with an optional type annotation.
 
+use crate::parser::parselets::TypeParselet;
 use crate::parser::{Parser, ParserResult, PrefixParselet};
 
 use crate::types::{Expression, ExpressionKind, Pattern, Token, TokenKind, VariablePattern};
@@ -10,15 +11,9 @@ pub struct VariablePatternParselet;
 
 impl PrefixParselet for VariablePatternParselet {
     fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
-        let name;
-
         let lexeme = parser.get_lexeme(token.start_pos, token.end_pos)?;
 
-        if lexeme == "_".to_string() {
-            name = None;
-        } else {
-            name = Some(lexeme.clone());
-        }
+        let name = if lexeme == "_" { None } else { Some(lexeme) };
 
         if !parser.eof() {
             let next_token = parser.peek()?;
@@ -29,7 +24,7 @@ impl PrefixParselet for VariablePatternParselet {
 
                     Pattern::Variable(VariablePattern {
                         name,
-                        type_id: Some(lexeme),
+                        type_id: Some(TypeParselet.parse_type(parser, next_token)?),
                     })
                 }
 
@@ -59,88 +54,101 @@ impl PrefixParselet for VariablePatternParselet {
     }
 }
 
-/*#[cfg(test)]
+#[cfg(test)]
 mod tests {
-    use super::;
-    use crate::lexer::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::*;
+
+    fn parse_pattern(source: &str) -> Pattern {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        parser
+            .parse_expression(0)
+            .unwrap()
+            .expect_pattern()
+            .unwrap()
+    }
 
     #[test]
     fn type_and_name() {
-        let mut lexer = Lexer::new("name String");
-        let tokens = lexer.parse();
-        let mut parser = Parser::new(tokens);
-
         assert_eq!(
-            Ok(Expression {
-                kind:      ExpressionKind::Pattern(Pattern::Variable {
-                    name: Some("name".to_string()),
-                    type_id: Some("String".to_string()),
-                }),
-
-                start_pos: 0,
-                end_pos:   4,
-            }),
-            parser.parse_expression(0)
+            parse_pattern("name String"),
+            Pattern::Variable(VariablePattern {
+                name: Some("name".to_string()),
+                type_id: Some(TypeExpression::named("String")),
+            })
         );
     }
 
     #[test]
     fn no_type_and_no_name() {
-        let mut lexer = Lexer::new("_");
-        let tokens = lexer.parse();
-        let mut parser = Parser::new(tokens);
-
         assert_eq!(
-            Ok(Expression {
-                kind:      ExpressionKind::Pattern(Pattern::Variable {
-                    name: None,
-                    type_id: None,
-                }),
-
-                start_pos: 0,
-                end_pos:   1,
-            }),
-            parser.parse_expression(0)
+            parse_pattern("_"),
+            Pattern::Variable(VariablePattern {
+                name: None,
+                type_id: None,
+            })
         );
     }
 
     #[test]
     fn type_but_no_name() {
-        let mut lexer = Lexer::new("_ Int");
-        let tokens = lexer.parse();
-        let mut parser = Parser::new(tokens);
-
         assert_eq!(
-            Ok(Expression {
-                kind:      ExpressionKind::Pattern(Pattern::Variable {
-                    name: None,
-                    type_id: Some("Int".to_string()),
-                }),
-
-                start_pos: 0,
-                end_pos:   1,
-            }),
-            parser.parse_expression(0)
+            parse_pattern("_ Int"),
+            Pattern::Variable(VariablePattern {
+                name: None,
+                type_id: Some(TypeExpression::named("Int")),
+            })
         );
     }
 
     #[test]
     fn name_but_no_type() {
-        let mut lexer = Lexer::new("lexer");
-        let tokens = lexer.parse();
-        let mut parser = Parser::new(tokens);
+        assert_eq!(
+            parse_pattern("lexer"),
+            Pattern::Variable(VariablePattern {
+                name: Some("lexer".to_string()),
+                type_id: None,
+            })
+        );
+    }
 
+    #[test]
+    fn generic_type() {
         assert_eq!(
-            Ok(Expression {
-                kind:      ExpressionKind::Pattern(Pattern::Variable {
-                    name: Some("lexer".to_string()),
-                    type_id: None,
+            parse_pattern("counts Map[String, List[Int]]"),
+            Pattern::Variable(VariablePattern {
+                name: Some("counts".to_string()),
+                type_id: Some(TypeExpression {
+                    path: vec!["Map".to_string()],
+                    arguments: vec![
+                        TypeExpression::named("String"),
+                        TypeExpression {
+                            path: vec!["List".to_string()],
+                            arguments: vec![TypeExpression::named("Int")],
+                        },
+                    ],
                 }),
+            })
+        );
+    }
 
-                start_pos: 0,
-                end_pos:   5,
-            }),
-            parser.parse_expression(0)
+    #[test]
+    fn qualified_type() {
+        assert_eq!(
+            parse_pattern("origin Geometry.Point"),
+            Pattern::Variable(VariablePattern {
+                name: Some("origin".to_string()),
+                type_id: Some(TypeExpression {
+                    path: vec!["Geometry".to_string(), "Point".to_string()],
+                    arguments: vec![],
+                }),
+            })
         );
     }
-}*/
+}
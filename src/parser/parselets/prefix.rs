@@ -15,8 +15,8 @@ impl PrefixParselet for PrefixOperatorParselet {
                 operator,
                 operand: Box::new(expr),
             }),
-            start_pos: 0,
-            end_pos: 0,
+            start_pos: token.start_pos,
+            end_pos: token.end_pos,
         })
     }
 }
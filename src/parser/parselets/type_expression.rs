@@ -0,0 +1,75 @@
+//! Parse a type annotation like `Int`, `List[Int]`, `Int?` or `Geometry.Point`.
+
+use crate::parser::{Parser, ParserError, ParserResult, PrefixParselet};
+use crate::types::{Expression, ExpressionKind, Token, TokenKind, TypeExpression};
+
+/// Parse a type annotation like `Int`, `List[Int]`, `Map[String, Int]` or `Geometry.Point`.
+#[derive(Debug, Clone)]
+pub struct TypeParselet;
+
+impl TypeParselet {
+    /// Parse a type expression, starting with the given (already consumed) type token.
+    pub fn parse_type(
+        &self,
+        parser: &mut Parser,
+        token: Token,
+    ) -> Result<TypeExpression, ParserError> {
+        let mut path = vec![parser.get_lexeme(token.start_pos, token.end_pos)?];
+        let mut arguments = vec![];
+
+        // Parse qualified type names like `Geometry.Point`.
+        while self.next_is(parser, TokenKind::Dot)? {
+            parser.advance();
+
+            let segment = parser.consume_expect(TokenKind::Type)?;
+            path.push(parser.get_lexeme(segment.start_pos, segment.end_pos)?);
+        }
+
+        // Parse type arguments like `[String, Int]`.
+        if self.next_is(parser, TokenKind::LeftBracket)? {
+            parser.advance();
+
+            loop {
+                let argument = parser.consume_expect(TokenKind::Type)?;
+                arguments.push(self.parse_type(parser, argument)?);
+
+                if self.next_is(parser, TokenKind::Comma)? {
+                    parser.advance();
+                } else {
+                    break;
+                }
+            }
+
+            parser.consume_expect(TokenKind::RightBracket)?;
+        }
+
+        let type_id = TypeExpression { path, arguments };
+
+        // Parse optional types like `Int?`, whose values may also be `nil`.
+        if self.next_is(parser, TokenKind::QuestionMark)? {
+            parser.advance();
+
+            return Ok(TypeExpression::optional(type_id));
+        }
+
+        Ok(type_id)
+    }
+
+    fn next_is(&self, parser: &Parser, kind: TokenKind) -> Result<bool, ParserError> {
+        Ok(!parser.eof() && parser.peek()?.kind == kind)
+    }
+}
+
+/// A type on its own refers to the type itself, which may be called to construct
+/// an instance like `Point(1.0, 2.0)`.
+impl PrefixParselet for TypeParselet {
+    fn parse(&self, parser: &mut Parser, token: Token) -> ParserResult {
+        let type_id = self.parse_type(parser, token.clone())?;
+
+        Ok(Expression {
+            kind: ExpressionKind::Type(type_id),
+            start_pos: token.start_pos,
+            end_pos: token.end_pos,
+        })
+    }
+}
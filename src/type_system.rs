@@ -1,18 +1,658 @@
 //! A set of utilities which enable static type checking in Mag.
 
-/// An interface which is implemented by anything that has a type.
+use crate::types::TypeExpression;
+
+use std::collections::HashMap;
+
+/// An interface which is implemented by anything whose type may be known before the
+/// program runs.
 pub trait Typed {
-    // / Can we assign a value of the given type to a variable of this type?
-    // fn can_assign_from(&self, other: Box<dyn Typed>) -> bool;
-    /// Get the type of this object as a string.
-    fn get_type(&self) -> Option<String>;
+    /// Get the type of this object, which is [`Type::Unknown`] if it's only known at runtime.
+    fn get_type(&self) -> Type;
+}
+
+/// The type of a value.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Type {
+    Int,
+    Float,
+    /// An integer of arbitrary size, like a literal which doesn't fit into an `Int`.
+    BigInt,
+    String,
+    /// A single grapheme cluster of a string, which is what a reader would consider one
+    /// character, like `'é'`.
+    Char,
+    Boolean,
+    /// The type of `nil`, which stands for the absence of a value.
+    Nil,
+    /// A number of a fixed size like `UInt8` or `Float32`. `Int` and `Float` are the signed
+    /// 64-bit integers and floats, which are never represented by this variant.
+    Sized(NumericType),
+    /// A value of the given type which may also be `nil`, like `Int?`.
+    Optional(Box<Type>),
+    /// A user-defined type like `Point`, or a builtin one without type arguments like `List`.
+    Named(String),
+    /// A generic type applied to type arguments, like `List[Int]`.
+    Generic {
+        name: String,
+        arguments: Vec<Type>,
+    },
+    /// A fixed number of values with individual types, like the arguments of a call.
+    Tuple(Vec<Type>),
+    /// A method taking arguments of the given types and returning a value of another one.
+    Function {
+        parameters: Vec<Type>,
+        result: Box<Type>,
+    },
+    /// A type parameter of a generic method like the `T` in `def first[T](list List[T]) T`,
+    /// which stands for the type it's instantiated with at each call.
+    Parameter(String),
+    /// A type which can only be determined at runtime.
+    Unknown,
+}
+
+impl Type {
+    /// Get the type with the given name, which is one of the primitive types if possible.
+    pub fn named(name: &str) -> Self {
+        match name {
+            "Int" => Type::Int,
+            "Float" => Type::Float,
+            "BigInt" => Type::BigInt,
+            "String" => Type::String,
+            "Char" => Type::Char,
+            "Boolean" => Type::Boolean,
+            "Nil" => Type::Nil,
+            _ => match NumericType::from_name(name) {
+                Some(numeric_type) => Type::numeric(numeric_type),
+                None => Type::Named(String::from(name)),
+            },
+        }
+    }
+
+    /// Get the type of numbers of the given size, where `Int64` and `Float64` are just `Int`
+    /// and `Float`.
+    pub fn numeric(numeric_type: NumericType) -> Self {
+        match numeric_type {
+            NumericType::Int64 => Type::Int,
+            NumericType::Float64 => Type::Float,
+            _ => Type::Sized(numeric_type),
+        }
+    }
+
+    /// Get the type of a value which may be `nil`, unless it already can be or nothing is
+    /// known about it.
+    pub fn optional(value_type: Type) -> Self {
+        match value_type {
+            Type::Nil | Type::Optional(_) | Type::Unknown => value_type,
+            _ => Type::Optional(Box::new(value_type)),
+        }
+    }
+
+    /// Get the type of a list with elements of the given types, which is only known to be
+    /// `List[T]` if all of them share the known type `T`.
+    pub fn list_of(elements: &[Type]) -> Self {
+        match elements.split_first() {
+            Some((first, rest)) if first.is_known() && rest.iter().all(|e| e == first) => {
+                Type::Generic {
+                    name: String::from("List"),
+                    arguments: vec![first.clone()],
+                }
+            }
+            _ => Type::named("List"),
+        }
+    }
+
+    /// Get the name under which values of this type are known at runtime, if they carry one.
+    ///
+    /// Type arguments are erased at runtime, so `List[Int]` is just a `List`.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Type::Int => Some(String::from("Int")),
+            Type::Float => Some(String::from("Float")),
+            Type::BigInt => Some(String::from("BigInt")),
+            Type::String => Some(String::from("String")),
+            Type::Char => Some(String::from("Char")),
+            Type::Boolean => Some(String::from("Boolean")),
+            Type::Nil => Some(String::from("Nil")),
+            Type::Sized(numeric_type) => Some(String::from(numeric_type.name())),
+            Type::Named(name) | Type::Generic { name, .. } => Some(name.clone()),
+            Type::Optional(_)
+            | Type::Tuple(_)
+            | Type::Function { .. }
+            | Type::Parameter(_)
+            | Type::Unknown => None,
+        }
+    }
+
+    /// Check whether this is one of the numeric types, which form a tower from `Int` over
+    /// `BigInt` up to `Float`, or a number of a fixed size.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Type::Int | Type::BigInt | Type::Float | Type::Sized(_)
+        )
+    }
+
+    /// Get the type of the result of an arithmetic operator applied to numbers of the given
+    /// types, where a number is promoted to the type of the other one if it's further up
+    /// the tower, like an integer combined with a float.
+    ///
+    /// Only two integers give an integer, so `7 / 2` is `3` while `7 / 2.0` is `3.5`. Numbers
+    /// of a fixed size are only combined with numbers of the same size, and need to be
+    /// converted with `as` otherwise.
+    pub fn promote(left: &Type, right: &Type) -> Type {
+        match (left, right) {
+            (left, right) if left == right && left.is_numeric() => left.clone(),
+            (Type::Int, Type::BigInt) | (Type::BigInt, Type::Int) => Type::BigInt,
+            (Type::Int | Type::BigInt, Type::Float) | (Type::Float, Type::Int | Type::BigInt) => {
+                Type::Float
+            }
+            _ => Type::Unknown,
+        }
+    }
+
+    /// Check whether anything is known about this type at compile time.
+    ///
+    /// A type parameter may be instantiated with any type, so nothing is known about it.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Type::Unknown | Type::Parameter(_))
+    }
+
+    /// Get the type of an annotation within a generic method, where the names of the given
+    /// type parameters refer to a [`Type::Parameter`].
+    pub fn from_annotation(type_id: &TypeExpression, parameters: &[String]) -> Self {
+        let name = type_id.name();
+
+        if let Some(value_type) = type_id.optional_value() {
+            Type::optional(Type::from_annotation(value_type, parameters))
+        } else if type_id.arguments.is_empty() && parameters.contains(&name) {
+            Type::Parameter(name)
+        } else if type_id.arguments.is_empty() {
+            Type::named(&name)
+        } else {
+            Type::Generic {
+                name,
+                arguments: type_id
+                    .arguments
+                    .iter()
+                    .map(|argument| Type::from_annotation(argument, parameters))
+                    .collect(),
+            }
+        }
+    }
+
+    /// Get the annotation which refers to this type, if it can be written down.
+    pub fn annotation(&self) -> Option<TypeExpression> {
+        match self {
+            Type::Generic { name, arguments } => Some(TypeExpression {
+                path: name.split('.').map(String::from).collect(),
+                arguments: arguments
+                    .iter()
+                    .map(Type::annotation)
+                    .collect::<Option<Vec<TypeExpression>>>()?,
+            }),
+            Type::Optional(value_type) => Some(TypeExpression::optional(value_type.annotation()?)),
+            Type::Parameter(name) => Some(TypeExpression::named(name)),
+            _ => self.name().map(|name| TypeExpression {
+                path: name.split('.').map(String::from).collect(),
+                arguments: vec![],
+            }),
+        }
+    }
+
+    /// Bind the type parameters in this type so that it matches the given one, like `T` to
+    /// `Int` for `List[T]` and `List[Int]`.
+    ///
+    /// Returns `false` if the types can't match, either because they differ in structure or
+    /// because a parameter would be bound to two different types.
+    pub fn unify(&self, given: &Type, bindings: &mut HashMap<String, Type>) -> bool {
+        match (self, given) {
+            (Type::Parameter(name), given) => match bindings.get(name) {
+                Some(bound) if bound.is_known() && given.is_known() => bound == given,
+                Some(bound) if bound.is_known() => true,
+                _ => {
+                    bindings.insert(name.clone(), given.clone());
+                    true
+                }
+            },
+            (_, Type::Unknown | Type::Parameter(_)) | (Type::Unknown, _) => true,
+            (
+                Type::Generic { name, arguments },
+                Type::Generic {
+                    name: other,
+                    arguments: others,
+                },
+            ) => {
+                name == other
+                    && arguments.len() == others.len()
+                    && arguments
+                        .iter()
+                        .zip(others)
+                        .all(|(argument, other)| argument.unify(other, bindings))
+            }
+            (Type::Optional(value_type), Type::Optional(other)) => {
+                value_type.unify(other, bindings)
+            }
+            // Both `nil` and a value of the type itself fit an optional type.
+            (Type::Optional(_), Type::Nil) => true,
+            (Type::Optional(value_type), given) => value_type.unify(given, bindings),
+            // A list whose elements are of unknown type still fits `List[T]`.
+            (Type::Generic { name, .. }, Type::Named(other)) => name == other,
+            (Type::Tuple(elements), Type::Tuple(others)) => {
+                elements.len() == others.len()
+                    && elements
+                        .iter()
+                        .zip(others)
+                        .all(|(element, other)| element.unify(other, bindings))
+            }
+            (
+                Type::Function { parameters, result },
+                Type::Function {
+                    parameters: others,
+                    result: other,
+                },
+            ) => {
+                Type::Tuple(parameters.clone()).unify(&Type::Tuple(others.clone()), bindings)
+                    && result.unify(other, bindings)
+            }
+            _ => self == given,
+        }
+    }
+
+    /// Replace the type parameters in this type by the types they're bound to. Parameters
+    /// without a binding can't be known until runtime.
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::Parameter(name) => bindings.get(name).cloned().unwrap_or(Type::Unknown),
+            Type::Optional(value_type) => Type::optional(value_type.substitute(bindings)),
+            Type::Generic { name, arguments } => Type::Generic {
+                name: name.clone(),
+                arguments: arguments
+                    .iter()
+                    .map(|argument| argument.substitute(bindings))
+                    .collect(),
+            },
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| element.substitute(bindings))
+                    .collect(),
+            ),
+            Type::Function { parameters, result } => Type::Function {
+                parameters: parameters
+                    .iter()
+                    .map(|parameter| parameter.substitute(bindings))
+                    .collect(),
+                result: Box::new(result.substitute(bindings)),
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+/// The numbers of a fixed size, which literals select with a suffix like the `u8` in `255u8`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum NumericType {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+}
+
+impl NumericType {
+    /// All numeric types, from the smallest signed integer to the largest float.
+    pub const ALL: [NumericType; 10] = [
+        NumericType::Int8,
+        NumericType::Int16,
+        NumericType::Int32,
+        NumericType::Int64,
+        NumericType::UInt8,
+        NumericType::UInt16,
+        NumericType::UInt32,
+        NumericType::UInt64,
+        NumericType::Float32,
+        NumericType::Float64,
+    ];
+
+    /// Get the numeric type with the given name, like `UInt8`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|numeric_type| numeric_type.name() == name)
+    }
+
+    /// Get the numeric type selected by a literal's suffix, like `u8` or `f32`.
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|numeric_type| numeric_type.suffix() == suffix)
+    }
+
+    /// Get the name of this type, like `UInt8`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NumericType::Int8 => "Int8",
+            NumericType::Int16 => "Int16",
+            NumericType::Int32 => "Int32",
+            NumericType::Int64 => "Int64",
+            NumericType::UInt8 => "UInt8",
+            NumericType::UInt16 => "UInt16",
+            NumericType::UInt32 => "UInt32",
+            NumericType::UInt64 => "UInt64",
+            NumericType::Float32 => "Float32",
+            NumericType::Float64 => "Float64",
+        }
+    }
+
+    /// Get the suffix which selects this type in a literal, like `u8`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            NumericType::Int8 => "i8",
+            NumericType::Int16 => "i16",
+            NumericType::Int32 => "i32",
+            NumericType::Int64 => "i64",
+            NumericType::UInt8 => "u8",
+            NumericType::UInt16 => "u16",
+            NumericType::UInt32 => "u32",
+            NumericType::UInt64 => "u64",
+            NumericType::Float32 => "f32",
+            NumericType::Float64 => "f64",
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, NumericType::Float32 | NumericType::Float64)
+    }
+
+    /// Get the smallest and largest value of an integer type.
+    pub fn range(&self) -> Option<(i128, i128)> {
+        match self {
+            NumericType::Int8 => Some((i8::MIN.into(), i8::MAX.into())),
+            NumericType::Int16 => Some((i16::MIN.into(), i16::MAX.into())),
+            NumericType::Int32 => Some((i32::MIN.into(), i32::MAX.into())),
+            NumericType::Int64 => Some((i64::MIN.into(), i64::MAX.into())),
+            NumericType::UInt8 => Some((0, u8::MAX.into())),
+            NumericType::UInt16 => Some((0, u16::MAX.into())),
+            NumericType::UInt32 => Some((0, u32::MAX.into())),
+            NumericType::UInt64 => Some((0, u64::MAX.into())),
+            NumericType::Float32 | NumericType::Float64 => None,
+        }
+    }
+
+    /// Check whether an integer can be represented by a value of this type.
+    pub fn contains(&self, value: i128) -> bool {
+        self.range()
+            .is_some_and(|(min, max)| min <= value && value <= max)
+    }
+}
+
+impl From<&TypeExpression> for Type {
+    fn from(type_id: &TypeExpression) -> Self {
+        Type::from_annotation(type_id, &[])
+    }
 }
 
-pub struct Type;
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let join = |types: &[Type]| {
+            types
+                .iter()
+                .map(|t| format!("{}", t))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self {
+            Type::Generic { name, arguments } => write!(f, "{}[{}]", name, join(arguments)),
+            Type::Tuple(elements) => write!(f, "({})", join(elements)),
+            Type::Function { parameters, result } => {
+                write!(f, "({}) -> {}", join(parameters), result)
+            }
+            Type::Optional(value_type) => write!(f, "{}?", value_type),
+            Type::Parameter(name) => write!(f, "{}", name),
+            Type::Unknown => write!(f, "<dynamically typed>"),
+            _ => write!(f, "{}", self.name().unwrap_or_default()),
+        }
+    }
+}
 
+/// A value whose type is known at compile time, but doesn't fit where it's used.
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeError {
+    /// A value of one type was found where another one was expected, like the condition
+    /// of an `if` which isn't a `Boolean`.
     UnexpectedType {
-        expected: String,
-        found: Option<String>,
+        expected: Type,
+        found: Type,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// An operand of a built-in operator has a type the operator isn't defined for,
+    /// like the string in `1 + "two"`.
+    InvalidOperand {
+        operator: String,
+        operand: Type,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// A constant number doesn't fit into the range of its type, like `256u8`, or of the
+    /// type it's converted to, like `-1 as UInt8`.
+    OutOfRange {
+        value: String,
+        target: Type,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// An arithmetic operator combines numbers of different sizes, which need to be
+    /// converted explicitly, like in `1u8 + 1i32`.
+    MixedNumericTypes {
+        operator: String,
+        left: Type,
+        right: Type,
+        start_pos: usize,
+        end_pos: usize,
     },
+    /// A value is converted with `as` to a type it can't be converted to, like the boolean
+    /// in `true as Int`.
+    InvalidConversion {
+        from: Type,
+        to: Type,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// A class, enum or interface has the same name as a type defined before it.
+    DuplicateType {
+        name: String,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// A value which may be `nil` was passed to a method which only accepts the value
+    /// itself, like `person.name` for a person of type `Person?`.
+    OptionalValue {
+        found: Type,
+        start_pos: usize,
+        end_pos: usize,
+    },
+}
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedType {
+                expected, found, ..
+            } => write!(
+                f,
+                "expected a value of type `{}`, found `{}`",
+                expected, found
+            ),
+            Self::InvalidOperand {
+                operator, operand, ..
+            } => write!(
+                f,
+                "the operator `{}` can't be applied to a value of type `{}`",
+                operator, operand
+            ),
+            Self::OutOfRange { value, target, .. } => {
+                write!(f, "the value `{}` is out of range for `{}`", value, target)
+            }
+            Self::MixedNumericTypes {
+                operator,
+                left,
+                right,
+                ..
+            } => write!(
+                f,
+                "the operator `{}` can't combine `{}` and `{}`, one of them needs to be converted with `as`",
+                operator, left, right
+            ),
+            Self::InvalidConversion { from, to, .. } => write!(
+                f,
+                "a value of type `{}` can't be converted to `{}`",
+                from, to
+            ),
+            Self::DuplicateType { name, .. } => {
+                write!(f, "a type named `{}` has already been defined", name)
+            }
+            Self::OptionalValue { found, .. } => write!(
+                f,
+                "a value of type `{}` may be `nil`, which needs to be handled with `??` or `?.` first",
+                found
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NumericType, Type, TypeError, Typed};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::{Expression, TypeExpression};
+
+    use std::collections::HashMap;
+
+    fn parse(source: &str) -> (Parser, Vec<Expression>) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let expressions = parser.parse().unwrap();
+
+        (parser, expressions)
+    }
+
+    #[test]
+    fn converts_type_expressions() {
+        let list = TypeExpression {
+            path: vec![String::from("List")],
+            arguments: vec![TypeExpression::named("Int")],
+        };
+
+        assert_eq!(
+            Type::from(&list),
+            Type::Generic {
+                name: String::from("List"),
+                arguments: vec![Type::Int],
+            }
+        );
+        assert_eq!(Type::from(&list).name(), Some(String::from("List")));
+        assert_eq!(format!("{}", Type::from(&list)), "List[Int]");
+        assert_eq!(
+            Type::from(&TypeExpression::named("Point")),
+            Type::Named(String::from("Point"))
+        );
+    }
+
+    #[test]
+    fn unifies_type_parameters() {
+        let list = TypeExpression {
+            path: vec![String::from("List")],
+            arguments: vec![TypeExpression::named("T")],
+        };
+        let generic = Type::from_annotation(&list, &[String::from("T")]);
+        let mut bindings = HashMap::new();
+
+        assert!(generic.unify(
+            &Type::Generic {
+                name: String::from("List"),
+                arguments: vec![Type::Int],
+            },
+            &mut bindings
+        ));
+        assert_eq!(
+            Type::Parameter(String::from("T")).substitute(&bindings),
+            Type::Int
+        );
+        assert!(!Type::Parameter(String::from("T")).unify(&Type::String, &mut bindings));
+    }
+
+    #[test]
+    fn promotes_integers_to_floats() {
+        assert_eq!(Type::promote(&Type::Int, &Type::Int), Type::Int);
+        assert_eq!(Type::promote(&Type::Int, &Type::Float), Type::Float);
+        assert_eq!(Type::promote(&Type::Float, &Type::Int), Type::Float);
+        assert_eq!(Type::promote(&Type::Int, &Type::Unknown), Type::Unknown);
+        assert_eq!(Type::promote(&Type::BigInt, &Type::Int), Type::BigInt);
+        assert_eq!(Type::promote(&Type::BigInt, &Type::Float), Type::Float);
+    }
+
+    #[test]
+    fn names_sized_numbers() {
+        assert_eq!(Type::named("UInt8"), Type::Sized(NumericType::UInt8));
+        assert_eq!(Type::named("Int64"), Type::Int);
+        assert_eq!(NumericType::from_suffix("f32"), Some(NumericType::Float32));
+        assert!(NumericType::UInt8.contains(255));
+        assert!(!NumericType::UInt8.contains(256));
+        assert_eq!(
+            Type::promote(
+                &Type::Sized(NumericType::UInt8),
+                &Type::Sized(NumericType::Int32)
+            ),
+            Type::Unknown
+        );
+    }
+
+    #[test]
+    fn literals_have_their_type() {
+        let (_, expressions) = parse("[1, 2] 1 2.5 \"a\" true x");
+
+        assert_eq!(
+            expressions
+                .iter()
+                .map(|expression| expression.get_type())
+                .collect::<Vec<Type>>(),
+            vec![
+                Type::list_of(&[Type::Int]),
+                Type::Int,
+                Type::Float,
+                Type::String,
+                Type::Boolean,
+                Type::Unknown,
+            ]
+        );
+    }
+
+    #[test]
+    fn describes_type_errors() {
+        let error = TypeError::UnexpectedType {
+            expected: Type::Int,
+            found: Type::list_of(&[Type::String]),
+            start_pos: 0,
+            end_pos: 0,
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            "expected a value of type `Int`, found `List[String]`"
+        );
+        assert_eq!(format!("{}", Type::Unknown), "<dynamically typed>");
+    }
 }
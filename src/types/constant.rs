@@ -0,0 +1,562 @@
+//! Evaluate expressions whose value is known at compile time.
+//!
+//! Value patterns like `def fib(0) 0` are compared by their value rather than by their
+//! spelling, so that a call like `fib(0x0)` or `fib(1 - 1)` selects the same variant.
+//! Arithmetic follows the same rules as at runtime, so integer operations which overflow
+//! or divide by zero have no value and are reported as an [`ArithmeticError`].
+
+use crate::helpers::{
+    parse_big_int_literal, parse_char_literal, parse_int_literal, parse_string_literal,
+    parse_wide_int_literal, split_number_suffix,
+};
+use crate::parser::Parser;
+use crate::type_system::NumericType;
+use crate::types::{
+    Call, Expression, ExpressionKind, Literal, ParserError, Pattern, Prefix, TokenKind,
+    TuplePattern, ValuePattern,
+};
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{FromPrimitive, ToPrimitive};
+
+/// A canonical value which has been computed at compile time.
+///
+/// Constants are compared structurally, so `1` and `1.0` are different constants. Use
+/// [`Constant::numeric_eq`] to compare numbers of different types by their value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Int(i64),
+    Float(f64),
+    BigInt(BigInt),
+    /// An integer of a fixed size other than `Int`, like `255u8`, whose value is checked
+    /// to be within the range of its type.
+    SizedInt(i128, NumericType),
+    Float32(f32),
+    String(String),
+    /// A single grapheme cluster, like `'é'`.
+    Char(String),
+    Boolean(bool),
+    Nil,
+}
+
+/// The exact value of a number of any type, which is an integer if it has no fractional
+/// part, so that integers and floats can be compared without rounding.
+#[derive(PartialEq)]
+enum ExactNumber {
+    Integer(BigInt),
+    Fraction(f64),
+}
+
+/// The reason why a built-in arithmetic operator has no result for two integers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticError {
+    /// The result is out of range for its type, like in `255u8 + 1u8`.
+    Overflow,
+    /// An integer is divided by zero, either with `/` or with `%`.
+    DivisionByZero,
+    /// An integer is raised to a negative power, whose result isn't an integer.
+    NegativeExponent,
+}
+
+impl std::fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "the result is out of range for its type"),
+            Self::DivisionByZero => write!(f, "an integer can't be divided by zero"),
+            Self::NegativeExponent => {
+                write!(f, "an integer can't be raised to a negative power")
+            }
+        }
+    }
+}
+
+impl Constant {
+    /// Evaluate an expression to a constant value, if it only consists of literals
+    /// and built-in operators.
+    pub fn evaluate(parser: &Parser, expression: &Expression) -> Result<Constant, ParserError> {
+        match &expression.kind {
+            ExpressionKind::Literal(literal) => {
+                let lexeme = parser.get_lexeme(expression.start_pos, expression.end_pos)?;
+
+                let value = match literal {
+                    Literal::Int => parse_int_literal(&lexeme).map(Constant::Int),
+                    Literal::Float => lexeme.parse::<f64>().ok().map(Constant::Float),
+                    Literal::String => Some(Constant::String(parse_string_literal(&lexeme))),
+                    Literal::Char => parse_char_literal(&lexeme).map(Constant::Char),
+                    Literal::Boolean => lexeme.parse::<bool>().ok().map(Constant::Boolean),
+                    Literal::Nil => Some(Constant::Nil),
+                    Literal::BigInt => parse_big_int_literal(&lexeme).map(Constant::BigInt),
+                    Literal::Sized(numeric_type) => {
+                        Constant::sized(split_number_suffix(&lexeme).0, *numeric_type, false)
+                    }
+                };
+
+                value.ok_or_else(|| ParserError::NotConstant(Box::new(expression.clone())))
+            }
+
+            ExpressionKind::Pattern(Pattern::Value(ValuePattern { expression })) => {
+                Constant::evaluate(parser, expression)
+            }
+
+            ExpressionKind::Pattern(Pattern::Tuple(TuplePattern { child })) => {
+                Constant::evaluate(parser, &child.as_ref().clone().into_expression())
+            }
+
+            // The smallest value of a signed type like `-128i8` is only in range with its sign.
+            ExpressionKind::Prefix(Prefix { operator, operand })
+                if operator.kind == TokenKind::Minus
+                    && matches!(operand.kind, ExpressionKind::Literal(Literal::Sized(_))) =>
+            {
+                let lexeme = parser.get_lexeme(operand.start_pos, operand.end_pos)?;
+
+                match split_number_suffix(&lexeme) {
+                    (digits, Some(numeric_type)) => Constant::sized(digits, numeric_type, true),
+                    _ => None,
+                }
+                .ok_or_else(|| ParserError::NotConstant(Box::new(expression.clone())))
+            }
+
+            ExpressionKind::Prefix(Prefix { operator, operand }) => {
+                let operand = Constant::evaluate(parser, operand)?;
+
+                let value = match (&operator.kind, operand) {
+                    (TokenKind::Minus, Constant::Int(n)) => n.checked_neg().map(Constant::Int),
+                    (TokenKind::Minus, Constant::Float(n)) => Some(Constant::Float(-n)),
+                    (TokenKind::Plus, value @ (Constant::Int(_) | Constant::Float(_))) => {
+                        Some(value)
+                    }
+                    (TokenKind::Bang, Constant::Boolean(b)) => Some(Constant::Boolean(!b)),
+                    _ => None,
+                };
+
+                value.ok_or_else(|| ParserError::NotConstant(Box::new(expression.clone())))
+            }
+
+            // Infix expressions are evaluated in their desugared form as built-in method calls.
+            ExpressionKind::Infix(_) => {
+                let mut desugared = expression.clone();
+                desugared.desugar();
+
+                Constant::evaluate(parser, &desugared)
+            }
+
+            ExpressionKind::Call(Call {
+                name,
+                signature: Some(Pattern::Pair(pair)),
+            }) => {
+                let left = Constant::evaluate(parser, &pair.left.clone().into_expression())?;
+                let right = Constant::evaluate(parser, &pair.right.clone().into_expression())?;
+
+                Constant::apply_operator(name, left, right)
+                    .ok_or_else(|| ParserError::NotConstant(Box::new(expression.clone())))
+            }
+
+            _ => Err(ParserError::NotConstant(Box::new(expression.clone()))),
+        }
+    }
+
+    /// Parse the digits of a number literal with a suffix into a value of the given type,
+    /// which may be negated, unless it's out of range.
+    fn sized(digits: &str, numeric_type: NumericType, negative: bool) -> Option<Constant> {
+        let sign = if negative { -1 } else { 1 };
+
+        match numeric_type {
+            NumericType::Float64 => digits
+                .parse::<f64>()
+                .ok()
+                .map(|value| Constant::Float(value * f64::from(sign))),
+            NumericType::Float32 => digits
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(|value| Constant::Float32(value * sign as f32)),
+            NumericType::Int64 => i64::try_from(parse_wide_int_literal(digits)? * i128::from(sign))
+                .ok()
+                .map(Constant::Int),
+            _ => Constant::sized_int(
+                parse_wide_int_literal(digits)? * i128::from(sign),
+                numeric_type,
+            ),
+        }
+    }
+
+    /// Get an integer of the given type, unless it's out of range.
+    fn sized_int(value: i128, numeric_type: NumericType) -> Option<Constant> {
+        if numeric_type.contains(value) {
+            Some(Constant::SizedInt(value, numeric_type))
+        } else {
+            None
+        }
+    }
+
+    /// Fold an expression into a constant value like [`Constant::evaluate`], but report why
+    /// a built-in operator applied to two constant integers has no result.
+    ///
+    /// Returns `Ok(None)` if the expression isn't constant.
+    pub fn fold(
+        parser: &Parser,
+        expression: &Expression,
+    ) -> Result<Option<Constant>, ArithmeticError> {
+        if let Ok(value) = Constant::evaluate(parser, expression) {
+            return Ok(Some(value));
+        }
+
+        if let ExpressionKind::Call(Call {
+            name,
+            signature: Some(Pattern::Pair(pair)),
+        }) = &expression.kind
+        {
+            let left = Constant::fold(parser, &pair.left.clone().into_expression())?;
+            let right = Constant::fold(parser, &pair.right.clone().into_expression())?;
+
+            let divisor: i128 = match (left, right) {
+                (Some(Constant::Int(_)), Some(Constant::Int(divisor))) => divisor.into(),
+                (
+                    Some(Constant::SizedInt(_, left_type)),
+                    Some(Constant::SizedInt(divisor, right_type)),
+                ) if left_type == right_type => divisor,
+                // Only the sign of a `BigInt` matters, since its arithmetic never overflows.
+                (Some(Constant::Int(_) | Constant::BigInt(_)), Some(Constant::BigInt(divisor))) => {
+                    match divisor.sign() {
+                        Sign::Minus => -1,
+                        Sign::NoSign => 0,
+                        Sign::Plus => 1,
+                    }
+                }
+                (Some(Constant::BigInt(_)), Some(Constant::Int(divisor))) => divisor.into(),
+                _ => return Ok(None),
+            };
+
+            // Both operands are constant integers, so the operator itself has no result.
+            return match name.as_str() {
+                "/" | "%" if divisor == 0 => Err(ArithmeticError::DivisionByZero),
+                "^" if divisor < 0 => Err(ArithmeticError::NegativeExponent),
+                "+" | "-" | "*" | "/" | "%" | "^" => Err(ArithmeticError::Overflow),
+                _ => Ok(None),
+            };
+        }
+
+        Ok(None)
+    }
+
+    /// Get the name of the type of this constant, like `Int` or `String`.
+    pub fn type_name(&self) -> String {
+        String::from(match self {
+            Constant::Int(_) => "Int",
+            Constant::Float(_) => "Float",
+            Constant::BigInt(_) => "BigInt",
+            Constant::SizedInt(_, numeric_type) => numeric_type.name(),
+            Constant::Float32(_) => "Float32",
+            Constant::String(_) => "String",
+            Constant::Char(_) => "Char",
+            Constant::Boolean(_) => "Boolean",
+            Constant::Nil => "Nil",
+        })
+    }
+
+    /// Check whether values of the given type are compared numerically with other numbers.
+    pub fn is_numeric_type(type_name: &str) -> bool {
+        matches!(type_name, "Int" | "Float" | "BigInt")
+            || NumericType::from_name(type_name).is_some()
+    }
+
+    /// Check whether two constants have the same value, comparing numbers of different
+    /// types exactly, so `1` is equal to `1.0` and `1u8`, but `2 ^ 53 + 1` isn't equal to
+    /// the float it rounds to.
+    pub fn numeric_eq(&self, other: &Constant) -> bool {
+        match (self.exact_number(), other.exact_number()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self == other,
+            _ => false,
+        }
+    }
+
+    fn exact_number(&self) -> Option<ExactNumber> {
+        let float = match self {
+            Constant::Int(n) => return Some(ExactNumber::Integer(BigInt::from(*n))),
+            Constant::BigInt(n) => return Some(ExactNumber::Integer(n.clone())),
+            Constant::SizedInt(n, _) => return Some(ExactNumber::Integer(BigInt::from(*n))),
+            Constant::Float(n) => *n,
+            Constant::Float32(n) => f64::from(*n),
+            _ => return None,
+        };
+
+        // Every finite float without a fractional part is an integer which can be
+        // represented exactly.
+        match BigInt::from_f64(float) {
+            Some(integer) if float.fract() == 0.0 => Some(ExactNumber::Integer(integer)),
+            _ => Some(ExactNumber::Fraction(float)),
+        }
+    }
+
+    /// Apply a built-in binary operator to two constants.
+    ///
+    /// Arithmetic on two integers stays an integer and fails on overflow, division by zero
+    /// or a negative exponent, while mixing an integer with a float promotes the integer to
+    /// a float first. Dividing two integers truncates the result towards zero.
+    fn apply_operator(operator: &str, left: Constant, right: Constant) -> Option<Constant> {
+        match (left, right) {
+            (Constant::Int(a), Constant::Int(b)) => match operator {
+                "+" => a.checked_add(b).map(Constant::Int),
+                "-" => a.checked_sub(b).map(Constant::Int),
+                "*" => a.checked_mul(b).map(Constant::Int),
+                "/" => a.checked_div(b).map(Constant::Int),
+                "%" => a.checked_rem(b).map(Constant::Int),
+                "^" => u32::try_from(b)
+                    .ok()
+                    .and_then(|b| a.checked_pow(b))
+                    .map(Constant::Int),
+                _ => Constant::compare(operator, a.partial_cmp(&b)),
+            },
+
+            (Constant::Int(a), Constant::Float(b)) => {
+                Constant::apply_operator(operator, Constant::Float(a as f64), Constant::Float(b))
+            }
+
+            (Constant::Float(a), Constant::Int(b)) => {
+                Constant::apply_operator(operator, Constant::Float(a), Constant::Float(b as f64))
+            }
+
+            (Constant::Float(a), Constant::Float(b)) => match operator {
+                "+" => Some(Constant::Float(a + b)),
+                "-" => Some(Constant::Float(a - b)),
+                "*" => Some(Constant::Float(a * b)),
+                "/" => Some(Constant::Float(a / b)),
+                "%" => Some(Constant::Float(a % b)),
+                "^" => Some(Constant::Float(a.powf(b))),
+                _ => Constant::compare(operator, a.partial_cmp(&b)),
+            },
+
+            // An integer combined with a `BigInt` is promoted to one, whose arithmetic never
+            // overflows, and a `BigInt` combined with a float is promoted to a float.
+            (Constant::Int(a), Constant::BigInt(b)) => {
+                Constant::apply_operator(operator, Constant::BigInt(a.into()), Constant::BigInt(b))
+            }
+
+            (Constant::BigInt(a), Constant::Int(b)) => {
+                Constant::apply_operator(operator, Constant::BigInt(a), Constant::BigInt(b.into()))
+            }
+
+            (Constant::BigInt(a), Constant::Float(b)) => {
+                Constant::apply_operator(operator, Constant::Float(a.to_f64()?), Constant::Float(b))
+            }
+
+            (Constant::Float(a), Constant::BigInt(b)) => {
+                Constant::apply_operator(operator, Constant::Float(a), Constant::Float(b.to_f64()?))
+            }
+
+            (Constant::BigInt(a), Constant::BigInt(b)) => {
+                let is_zero = b.sign() == Sign::NoSign;
+
+                match operator {
+                    "+" => Some(Constant::BigInt(a + b)),
+                    "-" => Some(Constant::BigInt(a - b)),
+                    "*" => Some(Constant::BigInt(a * b)),
+                    "/" | "%" if is_zero => None,
+                    "/" => Some(Constant::BigInt(a / b)),
+                    "%" => Some(Constant::BigInt(a % b)),
+                    "^" => u32::try_from(&b).ok().map(|b| Constant::BigInt(a.pow(b))),
+                    _ => Constant::compare(operator, a.partial_cmp(&b)),
+                }
+            }
+
+            // Numbers of a fixed size are only combined with numbers of the same size, and
+            // their results need to stay within its range.
+            (Constant::SizedInt(a, numeric_type), Constant::SizedInt(b, other))
+                if numeric_type == other =>
+            {
+                let value = match operator {
+                    "+" => a.checked_add(b),
+                    "-" => a.checked_sub(b),
+                    "*" => a.checked_mul(b),
+                    "/" => a.checked_div(b),
+                    "%" => a.checked_rem(b),
+                    "^" => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+                    _ => return Constant::compare(operator, a.partial_cmp(&b)),
+                };
+
+                Constant::sized_int(value?, numeric_type)
+            }
+
+            (Constant::Float32(a), Constant::Float32(b)) => match operator {
+                "+" => Some(Constant::Float32(a + b)),
+                "-" => Some(Constant::Float32(a - b)),
+                "*" => Some(Constant::Float32(a * b)),
+                "/" => Some(Constant::Float32(a / b)),
+                "%" => Some(Constant::Float32(a % b)),
+                "^" => Some(Constant::Float32(a.powf(b))),
+                _ => Constant::compare(operator, a.partial_cmp(&b)),
+            },
+
+            (Constant::String(a), Constant::String(b)) => match operator {
+                "+" => Some(Constant::String(a + &b)),
+                _ => Constant::compare(operator, a.partial_cmp(&b)),
+            },
+
+            (Constant::Char(a), Constant::Char(b)) => {
+                Constant::compare(operator, a.partial_cmp(&b))
+            }
+
+            (Constant::Boolean(a), Constant::Boolean(b)) => {
+                Constant::compare(operator, a.partial_cmp(&b))
+            }
+
+            (Constant::Nil, Constant::Nil) => match operator {
+                "==" | "!=" => Constant::compare(operator, Some(std::cmp::Ordering::Equal)),
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+
+    fn compare(operator: &str, ordering: Option<std::cmp::Ordering>) -> Option<Constant> {
+        use std::cmp::Ordering::*;
+
+        let result = match (operator, ordering) {
+            ("==", ordering) => ordering == Some(Equal),
+            ("!=", ordering) => ordering != Some(Equal),
+            ("<", Some(ordering)) => ordering == Less,
+            ("<=", Some(ordering)) => ordering != Greater,
+            (">", Some(ordering)) => ordering == Greater,
+            (">=", Some(ordering)) => ordering != Less,
+            // Comparisons involving `NaN` are always false.
+            ("<", None) | ("<=", None) | (">", None) | (">=", None) => false,
+            _ => return None,
+        };
+
+        Some(Constant::Boolean(result))
+    }
+}
+
+impl std::fmt::Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Constant::Int(n) => write!(f, "{}", n),
+            Constant::Float(n) => write!(f, "{}", n),
+            Constant::BigInt(n) => write!(f, "{}", n),
+            Constant::SizedInt(n, numeric_type) => write!(f, "{}{}", n, numeric_type.suffix()),
+            Constant::Float32(n) => write!(f, "{}f32", n),
+            Constant::String(s) => write!(f, "{:?}", s),
+            Constant::Char(c) => write!(f, "'{}'", c),
+            Constant::Boolean(b) => write!(f, "{}", b),
+            Constant::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArithmeticError, Constant};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::type_system::NumericType;
+
+    fn evaluate(source: &str) -> Option<Constant> {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let expression = parser.parse_expression(0).unwrap();
+
+        Constant::evaluate(&parser, &expression).ok()
+    }
+
+    #[test]
+    fn integers_equal_floats_with_the_same_value() {
+        let numeric_eq = |a: &str, b: &str| evaluate(a).unwrap().numeric_eq(&evaluate(b).unwrap());
+
+        assert!(numeric_eq("1", "1.0"));
+        assert!(numeric_eq("1", "0x1"));
+        assert!(numeric_eq("1", "1u8"));
+        assert!(!numeric_eq("1", "1.5"));
+        assert!(!numeric_eq("9007199254740993", "9007199254740993.0"));
+        assert_ne!(evaluate("1"), evaluate("1.0"));
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(evaluate("2 - 1"), Some(Constant::Int(1)));
+        assert_eq!(evaluate("1 + 0.5"), Some(Constant::Float(1.5)));
+        assert_eq!(evaluate("-3"), Some(Constant::Int(-3)));
+        assert_eq!(evaluate("7 / 2"), Some(Constant::Int(3)));
+        assert_eq!(evaluate("7 / 2.0"), Some(Constant::Float(3.5)));
+        assert_eq!(evaluate("2 ^ 10"), Some(Constant::Int(1024)));
+    }
+
+    #[test]
+    fn checks_the_range_of_sized_numbers() {
+        assert_eq!(
+            evaluate("255u8"),
+            Some(Constant::SizedInt(255, NumericType::UInt8))
+        );
+        assert_eq!(evaluate("256u8"), None);
+        assert_eq!(
+            evaluate("-128i8"),
+            Some(Constant::SizedInt(-128, NumericType::Int8))
+        );
+        assert_eq!(evaluate("200u8 + 55u8"), evaluate("255u8"));
+        assert_eq!(evaluate("200u8 + 56u8"), None);
+        assert_eq!(evaluate("1u8 + 1i32"), None);
+        assert_eq!(evaluate("1.5f32"), Some(Constant::Float32(1.5)));
+    }
+
+    #[test]
+    fn evaluates_big_integers() {
+        let big = |digits: &str| Some(Constant::BigInt(digits.parse().unwrap()));
+
+        assert_eq!(
+            evaluate("9223372036854775807 + 9223372036854775808"),
+            big("18446744073709551615")
+        );
+        assert_eq!(
+            evaluate("18446744073709551616 / 2"),
+            big("9223372036854775808")
+        );
+        assert_eq!(evaluate("18446744073709551616 / 0"), None);
+        assert_eq!(
+            evaluate("18446744073709551616 * 0.5"),
+            evaluate("9223372036854775808.0")
+        );
+    }
+
+    #[test]
+    fn reports_invalid_integer_arithmetic() {
+        let fold = |source: &str| {
+            let mut lexer = Lexer::new();
+            let mut parser = Parser::new();
+
+            lexer.add_text(source.to_string());
+            parser.add_tokens(source.to_string(), lexer.parse());
+
+            let mut expression = parser.parse_expression(0).unwrap();
+            expression.desugar();
+
+            Constant::fold(&parser, &expression)
+        };
+
+        assert_eq!(
+            fold("9223372036854775807 + 1"),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(fold("1 / 0"), Err(ArithmeticError::DivisionByZero));
+        assert_eq!(fold("2 ^ -1"), Err(ArithmeticError::NegativeExponent));
+        assert_eq!(fold("1.0 / 0"), Ok(Some(Constant::Float(f64::INFINITY))));
+        assert_eq!(fold("n / 0"), Ok(None));
+    }
+
+    #[test]
+    fn resolves_string_escapes() {
+        assert_eq!(
+            evaluate("\"a\\\"b\""),
+            Some(Constant::String("a\"b".to_string()))
+        );
+    }
+
+    #[test]
+    fn variables_are_not_constant() {
+        assert_eq!(evaluate("n + 1"), None);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::types::*;
+
+/// A definition of a record type with named fields, like `def class Point(x Float, y Float)`.
+///
+/// Defining a class registers its type and generates a constructor multimethod named after
+/// the type, as well as a getter multimethod for each field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Class {
+    /// The name of the defined type.
+    pub name: String,
+    /// The fields of each instance, in the order the constructor takes them.
+    pub fields: Vec<VariablePattern>,
+    /// The interfaces implemented by the class, like `Shape` in `def class Circle(r Float) with Shape`.
+    pub interfaces: Vec<TypeExpression>,
+}
@@ -0,0 +1,15 @@
+use crate::types::*;
+
+/// A definition of a tagged union, like `enum Shape case Circle(radius Float) end`.
+///
+/// Each variant is a type of its own with a constructor and field getters, just like a
+/// class, and every value of the enum is an instance of exactly one of its variants.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Enum {
+    /// The name of the type which contains all variants.
+    pub name: String,
+    /// The variants declared with `case`, in the order they're defined.
+    pub variants: Vec<Class>,
+    /// The interfaces implemented by all variants, declared like `enum Shape with Printable`.
+    pub interfaces: Vec<TypeExpression>,
+}
@@ -0,0 +1,26 @@
+use crate::types::*;
+
+/// A definition of an interface, like `interface Shape def area(this) end`.
+///
+/// Types implement an interface by listing it after `with` in their definition, and need
+/// to provide a method variant for each of its signatures, where `this` stands in for the
+/// implementing type. An interface may extend other interfaces in the same way, so that its
+/// implementations also implement those.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Interface {
+    /// The name of the interface, which may be used as a type.
+    pub name: String,
+    /// The methods each implementation needs to provide.
+    pub methods: Vec<MethodSignature>,
+    /// The interfaces this one extends, listed after `with`.
+    pub interfaces: Vec<TypeExpression>,
+}
+
+/// The signature of a method without a body, like `def area(this)`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MethodSignature {
+    /// The name of the multimethod.
+    pub name: String,
+    /// The parameters of the method.
+    pub signature: Option<Pattern>,
+}
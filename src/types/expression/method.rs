@@ -1,5 +1,8 @@
+use crate::type_system::Type;
 use crate::types::*;
 
+use std::collections::HashMap;
+
 /// An expression which defines a multimethod.
 ///
 /// A method can be registered to the same name multiple times if the signature is not already
@@ -9,12 +12,172 @@ use crate::types::*;
 pub struct Method {
     /// The name by which this multimethod is referenced.
     pub name: String,
+    /// Turns this method into an auxiliary method which is combined with the primary
+    /// variants, like `def before log(message)`.
+    pub qualifier: Option<MethodQualifier>,
+    /// The type parameters of a generic method, like `T` in `def first[T](list List[T]) T`.
+    pub type_parameters: Vec<String>,
     /// The method signature which defines the arguments.
     pub signature: Option<Pattern>,
+    /// The declared type of the returned value, like `T` in `def first[T](list List[T]) T`.
+    pub return_type: Option<TypeExpression>,
+    /// An optional condition like `where n < 0`, which is evaluated after the signature
+    /// has matched. The method is only selected if the guard evaluates to `true`.
+    pub guard: Option<Box<Expression>>,
     /// A value or piece of code which is evaluated when the method is called.
     pub body: Box<Expression>,
 }
 
+impl Method {
+    /// Remove the annotations which are only checked statically from the signature, so the
+    /// parameters accept any value at runtime.
+    ///
+    /// These are annotations naming a type parameter, since type arguments aren't known at
+    /// runtime, and optional types like `Int?`, since `nil` carries no type of its own.
+    /// Annotations like `List[T]` keep their type, which is all that's checked at runtime,
+    /// and the aliases `Int64` and `Float64` are replaced by the `Int` and `Float` values
+    /// carry at runtime.
+    pub fn erase_static_types(&mut self) {
+        if let Some(signature) = &mut self.signature {
+            Self::erase(signature, &self.type_parameters);
+        }
+    }
+
+    fn erase(pattern: &mut Pattern, parameters: &[String]) {
+        match pattern {
+            Pattern::Variable(variable) => {
+                let is_static = variable.type_id.as_ref().is_some_and(|type_id| {
+                    (type_id.arguments.is_empty() && parameters.contains(&type_id.name()))
+                        || type_id.optional_value().is_some()
+                });
+
+                if is_static {
+                    variable.type_id = None;
+                } else if let Some(type_id) = &mut variable.type_id {
+                    *type_id = type_id.canonical();
+                }
+            }
+            Pattern::Pair(pair) => {
+                Self::erase(&mut pair.left, parameters);
+                Self::erase(&mut pair.right, parameters);
+            }
+            Pattern::List(list) => {
+                for element in list.elements.iter_mut() {
+                    Self::erase(element, parameters);
+                }
+            }
+            Pattern::Tuple(tuple) => Self::erase(&mut tuple.child, parameters),
+            Pattern::Field(field) => Self::erase(&mut field.value, parameters),
+            Pattern::Value(_) | Pattern::Rest(_) => {}
+        }
+    }
+
+    /// Instantiate a generic method for arguments of the given types, replacing each type
+    /// parameter that can be inferred from them in the signature and the return type.
+    ///
+    /// For example, `def first[T](list List[T]) T` becomes `def first(list List[Int]) Int`
+    /// for the argument type `List[Int]`. Parameters which can't be inferred are kept.
+    pub fn instantiate(&self, arguments: &[Type]) -> Method {
+        let mut bindings = HashMap::new();
+        let parameters = self
+            .signature
+            .clone()
+            .map(Pattern::flatten_pairs)
+            .unwrap_or_default();
+
+        for (parameter, argument) in parameters.iter().zip(arguments) {
+            if let Pattern::Variable(VariablePattern {
+                type_id: Some(type_id),
+                ..
+            }) = parameter
+            {
+                let mut candidate = bindings.clone();
+
+                if Type::from_annotation(type_id, &self.type_parameters)
+                    .unify(argument, &mut candidate)
+                {
+                    bindings = candidate;
+                }
+            }
+        }
+
+        let bindings = bindings
+            .into_iter()
+            .filter_map(|(name, bound)| Some((name, bound.annotation()?)))
+            .collect::<HashMap<String, TypeExpression>>();
+
+        let mut method = self.clone();
+
+        method
+            .type_parameters
+            .retain(|parameter| !bindings.contains_key(parameter));
+
+        if let Some(signature) = &mut method.signature {
+            Self::substitute(signature, &bindings);
+        }
+
+        if let Some(return_type) = &mut method.return_type {
+            *return_type = return_type.substitute(&bindings);
+        }
+
+        method
+    }
+
+    fn substitute(pattern: &mut Pattern, bindings: &HashMap<String, TypeExpression>) {
+        match pattern {
+            Pattern::Variable(VariablePattern {
+                type_id: Some(type_id),
+                ..
+            }) => *type_id = type_id.substitute(bindings),
+            Pattern::Pair(pair) => {
+                Self::substitute(&mut pair.left, bindings);
+                Self::substitute(&mut pair.right, bindings);
+            }
+            Pattern::List(list) => {
+                for element in list.elements.iter_mut() {
+                    Self::substitute(element, bindings);
+                }
+            }
+            Pattern::Tuple(tuple) => Self::substitute(&mut tuple.child, bindings),
+            Pattern::Field(field) => Self::substitute(&mut field.value, bindings),
+            Pattern::Variable(_) | Pattern::Value(_) | Pattern::Rest(_) => {}
+        }
+    }
+}
+
+/// The kind of an auxiliary method, which runs in addition to the primary variants.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MethodQualifier {
+    /// Runs before the primary variant, like `def before log(message)`.
+    Before,
+    /// Runs after the primary variant, like `def after log(message)`.
+    After,
+    /// Runs instead of the primary variant, which it may call with `next_method()`.
+    Around,
+}
+
+impl MethodQualifier {
+    /// Get the qualifier with the given name, if any.
+    pub fn from_name(name: &str) -> Option<MethodQualifier> {
+        match name {
+            "before" => Some(MethodQualifier::Before),
+            "after" => Some(MethodQualifier::After),
+            "around" => Some(MethodQualifier::Around),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MethodQualifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MethodQualifier::Before => write!(f, "before"),
+            MethodQualifier::After => write!(f, "after"),
+            MethodQualifier::Around => write!(f, "around"),
+        }
+    }
+}
+
 /// A call of a method with a given set of arguments.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Call {
@@ -1,17 +1,25 @@
-use crate::type_system::Typed;
+use crate::type_system::{Type, Typed};
 use crate::types::ParserError;
-use crate::types::{Literal, PairPattern, Pattern, TokenKind, ValuePattern};
+use crate::types::{
+    Keyword, Literal, PairPattern, Pattern, TokenKind, TypeExpression, ValuePattern,
+};
 
 mod block;
+mod class;
 mod conditional;
+mod enumeration;
 mod infix;
+mod interface;
 mod method;
 mod prefix;
 
 pub use self::block::Block;
+pub use self::class::Class;
 pub use self::conditional::Conditional;
+pub use self::enumeration::Enum;
 pub use self::infix::Infix;
-pub use self::method::{Call, Method};
+pub use self::interface::{Interface, MethodSignature};
+pub use self::method::{Call, Method, MethodQualifier};
 pub use self::prefix::Prefix;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -34,16 +42,25 @@ pub enum ExpressionKind {
     Literal(Literal),
     /// A value, tuple, field or variable pattern.
     Pattern(Pattern),
-    /// A reference to a type, like `Int32`.
-    Type(String),
+    /// A reference to a type, like `Int32` or `List[Int]`.
+    Type(TypeExpression),
     /// An expression with a prefix operator.
     Prefix(Prefix),
     /// Two expressions with an infix operator in between.
     Infix(Infix),
     /// An invocation of a method, like `print("Hello, World!")`
     Call(Call),
+    /// An invocation of a method on a value which may be `nil`, like `person?.name`.
+    /// It evaluates to `nil` without calling the method if the value is `nil`.
+    SafeCall(Call),
     /// A definition of a method with a given name, signature and body.
     Method(Method),
+    /// A definition of a record type with named fields.
+    Class(Class),
+    /// A definition of a tagged union with several variants.
+    Enum(Enum),
+    /// A definition of an interface which lists the methods its implementations provide.
+    Interface(Interface),
     /// A first-class chunk of code that can be passed around as a value.
     Block(Block),
     Identifier,
@@ -65,12 +82,16 @@ impl ExpressionKind {
                     TokenKind::Star => "*".to_string(),
                     TokenKind::Slash => "/".to_string(),
                     TokenKind::Percent => "%".to_string(),
+                    TokenKind::Caret => "^".to_string(),
                     TokenKind::EqualEqual => "==".to_string(),
                     TokenKind::BangEqual => "!=".to_string(),
                     TokenKind::Greater => ">".to_string(),
                     TokenKind::GreaterEqual => ">=".to_string(),
                     TokenKind::Smaller => "<".to_string(),
                     TokenKind::SmallerEqual => "<=".to_string(),
+                    TokenKind::QuestionQuestion => "??".to_string(),
+                    TokenKind::Keyword(Keyword::Is) => "is".to_string(),
+                    TokenKind::Keyword(Keyword::As) => "as".to_string(),
                     _ => unimplemented!(),
                 };
                 ExpressionKind::Call(Call {
@@ -86,29 +107,35 @@ impl ExpressionKind {
                 })
             }
 
-            ExpressionKind::Call(Call {
-                ref name,
-                ref signature,
-            }) => {
-                if let Some(pattern) = signature {
-                    match pattern {
-                        Pattern::Value(ValuePattern { expression }) => {
-                            let mut expr = expression.clone();
-                            expr.desugar();
-                            ExpressionKind::Call(Call {
-                                name: name.to_string(),
-                                signature: Some(Pattern::Value(ValuePattern { expression: expr })),
-                            })
-                        }
-                        _ => self,
-                    }
-                } else {
-                    self
+            // Desugar any expressions contained in the call's arguments.
+            ExpressionKind::Call(Call { name, signature }) => ExpressionKind::Call(Call {
+                name,
+                signature: signature.map(|mut pattern| pattern.desugar()),
+            }),
+
+            ExpressionKind::SafeCall(Call { name, signature }) => ExpressionKind::SafeCall(Call {
+                name,
+                signature: signature.map(|mut pattern| pattern.desugar()),
+            }),
+
+            ExpressionKind::Conditional(mut conditional) => {
+                conditional.condition.desugar();
+                conditional.then_arm.desugar();
+
+                if let Some(else_arm) = &mut conditional.else_arm {
+                    else_arm.desugar();
                 }
+
+                ExpressionKind::Conditional(conditional)
             }
 
             ExpressionKind::Method(mut method) => {
                 method.body.desugar();
+
+                if let Some(guard) = &mut method.guard {
+                    guard.desugar();
+                }
+
                 ExpressionKind::Method(method)
             }
             // Desugar other expression kinds if necessary
@@ -143,20 +170,42 @@ impl Expression {
     }
 }
 
+/// Only literals and lists of them carry a type we can know without looking at the rest
+/// of the program, the types of any other expressions are left to be checked at runtime.
 impl Typed for Expression {
-    fn get_type(&self) -> Option<String> {
+    fn get_type(&self) -> Type {
         match &self.kind {
-            ExpressionKind::Conditional(_) => Some(String::from("ConditionalExpression")),
-            ExpressionKind::List(_) => Some(String::from("ListExpression")),
             ExpressionKind::Literal(literal) => literal.get_type(),
-            ExpressionKind::Pattern(pattern) => pattern.get_type(),
-            ExpressionKind::Type(type_id) => Some(type_id.clone()),
-            ExpressionKind::Prefix(_) => Some(String::from("PrefixExpression")),
-            ExpressionKind::Infix(_) => Some(String::from("InfixExpression")),
-            ExpressionKind::Call(_) => Some(String::from("CallExpression")),
-            ExpressionKind::Method(_) => Some(String::from("MethodExpression")),
-            ExpressionKind::Block(_) => Some(String::from("BlockExpression")),
-            ExpressionKind::Identifier => Some(String::from("Identifier")),
+            ExpressionKind::List(Some(child)) => {
+                let elements = child
+                    .pattern_or_value_pattern()
+                    .map(Pattern::flatten_pairs)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|element| match element {
+                        Pattern::Value(ValuePattern { expression }) => expression.get_type(),
+                        _ => Type::Unknown,
+                    })
+                    .collect::<Vec<Type>>();
+
+                Type::list_of(&elements)
+            }
+            ExpressionKind::List(None) => Type::named("List"),
+            // The built-in operators follow the numeric tower.
+            ExpressionKind::Call(Call {
+                name,
+                signature: Some(Pattern::Pair(pair)),
+            }) => {
+                let left = pair.left.clone().into_expression().get_type();
+                let right = pair.right.clone().into_expression().get_type();
+
+                match name.as_str() {
+                    "+" | "-" | "*" | "/" | "^" | "%" => Type::promote(&left, &right),
+                    "==" | "!=" | "<" | "<=" | ">" | ">=" => Type::Boolean,
+                    _ => Type::Unknown,
+                }
+            }
+            _ => Type::Unknown,
         }
     }
 }
@@ -1,15 +1,21 @@
 //! Data structures common to the entire crate, such as [`Token`], [`Expression`] or [`Pattern`].
 
+pub mod constant;
 pub mod expression;
+pub mod node_kind;
 pub mod pattern;
 pub mod result;
 pub mod token;
+pub mod type_expression;
 
 use std::collections::HashMap;
 
 pub type Environment = HashMap<String, Box<Expression>>;
 
+pub use self::constant::*;
 pub use self::expression::*;
+pub use self::node_kind::*;
 pub use self::pattern::*;
 pub use self::result::*;
 pub use self::token::*;
+pub use self::type_expression::*;
@@ -0,0 +1,140 @@
+//! Identify the kind of syntax tree node an expression or pattern is.
+//!
+//! Unlike the [`Type`](crate::type_system::Type) of a value, the kind of a node only
+//! depends on its syntax, and is used to look up the compilelet which compiles it.
+
+use crate::types::{Expression, ExpressionKind, Pattern};
+
+/// The different kinds of nodes in the syntax tree.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum NodeKind {
+    Conditional,
+    List,
+    Literal,
+    Type,
+    Prefix,
+    Infix,
+    Call,
+    SafeCall,
+    Method,
+    Class,
+    Enum,
+    Interface,
+    Block,
+    Identifier,
+    FieldPattern,
+    TuplePattern,
+    ValuePattern,
+    VariablePattern,
+    PairPattern,
+    RestPattern,
+    ListPattern,
+}
+
+impl Expression {
+    /// Get the kind of node this expression is, looking through expressions which only
+    /// wrap a pattern.
+    pub fn node_kind(&self) -> NodeKind {
+        match &self.kind {
+            ExpressionKind::Conditional(_) => NodeKind::Conditional,
+            ExpressionKind::List(_) => NodeKind::List,
+            ExpressionKind::Literal(_) => NodeKind::Literal,
+            ExpressionKind::Pattern(pattern) => pattern.node_kind(),
+            ExpressionKind::Type(_) => NodeKind::Type,
+            ExpressionKind::Prefix(_) => NodeKind::Prefix,
+            ExpressionKind::Infix(_) => NodeKind::Infix,
+            ExpressionKind::Call(_) => NodeKind::Call,
+            ExpressionKind::SafeCall(_) => NodeKind::SafeCall,
+            ExpressionKind::Method(_) => NodeKind::Method,
+            ExpressionKind::Class(_) => NodeKind::Class,
+            ExpressionKind::Enum(_) => NodeKind::Enum,
+            ExpressionKind::Interface(_) => NodeKind::Interface,
+            ExpressionKind::Block(_) => NodeKind::Block,
+            ExpressionKind::Identifier => NodeKind::Identifier,
+        }
+    }
+}
+
+impl Pattern {
+    /// Get the kind of node this pattern is.
+    pub fn node_kind(&self) -> NodeKind {
+        match self {
+            Pattern::Field(_) => NodeKind::FieldPattern,
+            Pattern::Tuple(_) => NodeKind::TuplePattern,
+            Pattern::Value(_) => NodeKind::ValuePattern,
+            Pattern::Variable(_) => NodeKind::VariablePattern,
+            Pattern::Pair(_) => NodeKind::PairPattern,
+            Pattern::Rest(_) => NodeKind::RestPattern,
+            Pattern::List(_) => NodeKind::ListPattern,
+        }
+    }
+}
+
+impl std::fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            NodeKind::Conditional => "ConditionalExpression",
+            NodeKind::List => "ListExpression",
+            NodeKind::Literal => "LiteralExpression",
+            NodeKind::Type => "TypeExpression",
+            NodeKind::Prefix => "PrefixExpression",
+            NodeKind::Infix => "InfixExpression",
+            NodeKind::Call => "CallExpression",
+            NodeKind::SafeCall => "SafeCallExpression",
+            NodeKind::Method => "MethodExpression",
+            NodeKind::Class => "ClassExpression",
+            NodeKind::Enum => "EnumExpression",
+            NodeKind::Interface => "InterfaceExpression",
+            NodeKind::Block => "BlockExpression",
+            NodeKind::Identifier => "Identifier",
+            NodeKind::FieldPattern => "FieldPattern",
+            NodeKind::TuplePattern => "TuplePattern",
+            NodeKind::ValuePattern => "ValuePattern",
+            NodeKind::VariablePattern => "VariablePattern",
+            NodeKind::PairPattern => "PairPattern",
+            NodeKind::RestPattern => "RestPattern",
+            NodeKind::ListPattern => "ListPattern",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeKind;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::Expression;
+
+    fn parse(source: &str) -> (Parser, Vec<Expression>) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let expressions = parser.parse().unwrap();
+
+        (parser, expressions)
+    }
+
+    #[test]
+    fn expressions_wrapping_patterns_have_the_pattern_kind() {
+        let (_, expressions) = parse("[1, 2] 1 f(1) x def f(n) n");
+
+        assert_eq!(
+            expressions
+                .iter()
+                .map(|expression| expression.node_kind())
+                .collect::<Vec<NodeKind>>(),
+            vec![
+                NodeKind::List,
+                NodeKind::Literal,
+                NodeKind::Call,
+                NodeKind::VariablePattern,
+                NodeKind::Method,
+            ]
+        );
+    }
+}
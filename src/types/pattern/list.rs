@@ -0,0 +1,33 @@
+//! A pattern which destructures the elements of a list.
+
+use crate::types::Pattern;
+
+/// A pattern which destructures the elements of a list, like `[head, ...tail]`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ListPattern {
+    pub elements: Vec<Pattern>,
+}
+
+impl ListPattern {
+    pub fn desugar(self) -> ListPattern {
+        ListPattern {
+            elements: self
+                .elements
+                .into_iter()
+                .map(|mut element| element.desugar())
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for ListPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| format!("{}", element))
+            .collect::<Vec<String>>();
+
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
@@ -2,18 +2,23 @@ use crate::types::{Expression, ExpressionKind};
 
 use crate::parser::Parser;
 
-use crate::type_system::Typed;
-use crate::types::ParserError;
+use crate::type_system::{Type, Typed};
+use crate::types::{Constant, ParserError};
 use std::collections::HashMap;
 
 mod field;
+mod list;
 mod pair;
+mod rest;
+mod specificity;
 mod tuple;
 mod value;
 mod variable;
 
 pub use self::field::*;
+pub use self::list::*;
 pub use self::pair::*;
+pub use self::rest::*;
 pub use self::tuple::*;
 pub use self::value::*;
 pub use self::variable::*;
@@ -35,6 +40,10 @@ pub enum Pattern {
     Variable(VariablePattern),
     /// A pair of patterns separated by a comma.
     Pair(PairPattern),
+    /// A pattern which collects all remaining elements of a sequence, like `...rest`.
+    Rest(RestPattern),
+    /// A pattern which destructures the elements of a list, like `[head, ...tail]`.
+    List(ListPattern),
 }
 
 impl Pattern {
@@ -55,9 +64,7 @@ impl Pattern {
             Pattern::Field(pattern) => Ok(pattern),
             _ => Err(ParserError::UnexpectedPattern {
                 expected: String::from("FieldPattern"),
-                found: self
-                    .get_type()
-                    .unwrap_or(String::from("<dynamically typed>")),
+                found: self.node_kind().to_string(),
             }),
         }
     }
@@ -67,9 +74,7 @@ impl Pattern {
             Pattern::Tuple(pattern) => Ok(pattern),
             _ => Err(ParserError::UnexpectedPattern {
                 expected: String::from("TuplePattern"),
-                found: self
-                    .get_type()
-                    .unwrap_or(String::from("<dynamically typed>")),
+                found: self.node_kind().to_string(),
             }),
         }
     }
@@ -79,9 +84,7 @@ impl Pattern {
             Pattern::Value(pattern) => Ok(*pattern.expression),
             _ => Err(ParserError::UnexpectedPattern {
                 expected: String::from("ValuePattern"),
-                found: self
-                    .get_type()
-                    .unwrap_or(String::from("<dynamically typed>")),
+                found: self.node_kind().to_string(),
             }),
         }
     }
@@ -91,9 +94,7 @@ impl Pattern {
             Pattern::Variable(pattern) => Ok(pattern),
             _ => Err(ParserError::UnexpectedPattern {
                 expected: String::from("VariablePattern"),
-                found: self
-                    .get_type()
-                    .unwrap_or(String::from("<dynamically typed>")),
+                found: self.node_kind().to_string(),
             }),
         }
     }
@@ -103,22 +104,137 @@ impl Pattern {
             Pattern::Pair(pattern) => Ok(pattern),
             _ => Err(ParserError::UnexpectedPattern {
                 expected: String::from("PairPattern"),
-                found: self
-                    .get_type()
-                    .unwrap_or(String::from("<dynamically typed>")),
+                found: self.node_kind().to_string(),
+            }),
+        }
+    }
+
+    pub fn expect_rest(self) -> Result<RestPattern, ParserError> {
+        match self {
+            Pattern::Rest(pattern) => Ok(pattern),
+            _ => Err(ParserError::UnexpectedPattern {
+                expected: String::from("RestPattern"),
+                found: self.node_kind().to_string(),
+            }),
+        }
+    }
+
+    pub fn expect_list(self) -> Result<ListPattern, ParserError> {
+        match self {
+            Pattern::List(pattern) => Ok(pattern),
+            _ => Err(ParserError::UnexpectedPattern {
+                expected: String::from("ListPattern"),
+                found: self.node_kind().to_string(),
             }),
         }
     }
-}
 
-impl Typed for Pattern {
-    fn get_type(&self) -> Option<String> {
+    /// Split a chain of pair patterns into its individual elements.
+    ///
+    /// Since the pair parselet is left-associative, `a, b, c` is stored as `((a, b), c)`,
+    /// which this function turns into `[a, b, c]`. Any other pattern is returned as a
+    /// single element.
+    pub fn flatten_pairs(self) -> Vec<Pattern> {
         match self {
-            Pattern::Field(_) => Some(String::from("FieldPattern")),
-            Pattern::Tuple(_) => Some(String::from("TuplePattern")),
-            Pattern::Value(_) => Some(String::from("ValuePattern")),
-            Pattern::Variable(_) => Some(String::from("VariablePattern")),
-            Pattern::Pair(_) => Some(String::from("PairPattern")),
+            Pattern::Pair(PairPattern { left, right }) => {
+                let mut elements = left.flatten_pairs();
+                elements.extend(right.flatten_pairs());
+                elements
+            }
+
+            _ => vec![self],
+        }
+    }
+
+    /// Join a list of patterns into a left-associative chain of pairs, the inverse of
+    /// [`Pattern::flatten_pairs`]. Returns `None` if there are no elements.
+    pub fn from_elements(elements: Vec<Pattern>) -> Option<Pattern> {
+        elements
+            .into_iter()
+            .fold(None, |chain, element| match chain {
+                Some(left) => Some(Pattern::Pair(PairPattern {
+                    left: Box::new(left),
+                    right: Box::new(element),
+                })),
+                None => Some(element),
+            })
+    }
+
+    /// Convert this pattern back into an expression which can be compiled or bound to a variable.
+    pub fn into_expression(self) -> Expression {
+        match self {
+            Pattern::Value(ValuePattern { expression }) => *expression,
+            _ => Expression {
+                kind: ExpressionKind::Pattern(self),
+                start_pos: 0,
+                end_pos: 0,
+            },
+        }
+    }
+
+    /// Prepare a pattern for use in a method signature.
+    ///
+    /// List literals are parsed as expressions, so a signature like `def f([head, ...tail])`
+    /// initially contains a value pattern wrapping a list. This function turns those into
+    /// list patterns and ensures that rest patterns only appear at the end of a sequence.
+    pub fn into_signature(self) -> Result<Pattern, ParserError> {
+        let signature = self.convert_lists()?;
+
+        Pattern::expect_trailing_rest(&signature.clone().flatten_pairs())?;
+
+        Ok(signature)
+    }
+
+    fn convert_lists(self) -> Result<Pattern, ParserError> {
+        match self {
+            Pattern::Value(ValuePattern { expression }) => {
+                if let ExpressionKind::List(child) = &expression.kind {
+                    let elements = match child {
+                        Some(child) => child
+                            .pattern_or_value_pattern()?
+                            .convert_lists()?
+                            .flatten_pairs(),
+                        None => vec![],
+                    };
+
+                    Pattern::expect_trailing_rest(&elements)?;
+
+                    Ok(Pattern::List(ListPattern { elements }))
+                } else {
+                    Ok(Pattern::Value(ValuePattern { expression }))
+                }
+            }
+
+            Pattern::Pair(PairPattern { left, right }) => Ok(Pattern::Pair(PairPattern {
+                left: Box::new(left.convert_lists()?),
+                right: Box::new(right.convert_lists()?),
+            })),
+
+            Pattern::Tuple(TuplePattern { child }) => Ok(Pattern::Tuple(TuplePattern {
+                child: Box::new(child.convert_lists()?),
+            })),
+
+            Pattern::Field(FieldPattern { name, value }) => Ok(Pattern::Field(FieldPattern {
+                name,
+                value: Box::new(value.convert_lists()?),
+            })),
+
+            _ => Ok(self),
+        }
+    }
+
+    /// Ensure that a rest pattern, if present, is the last element of the given sequence.
+    fn expect_trailing_rest(elements: &[Pattern]) -> Result<(), ParserError> {
+        let misplaced = elements
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|element| matches!(element, Pattern::Rest(_)));
+
+        if misplaced {
+            Err(ParserError::MisplacedRestPattern)
+        } else {
+            Ok(())
         }
     }
 }
@@ -146,20 +262,34 @@ impl Pattern {
             Pattern::Value(reference) => self.linearize_value(parser, reference.clone(), other),
             Pattern::Variable(reference) => self.linearize_variable(reference.clone(), other),
             Pattern::Pair(reference) => self.linearize_pair(parser, reference.clone(), other),
+            Pattern::Rest(reference) => {
+                self.linearize_rest(reference.clone(), other.flatten_pairs())
+            }
+            Pattern::List(reference) => self.linearize_list(parser, reference.clone(), other),
         }
     }
 
     pub fn matches_with(&self, parser: &Parser, other: Pattern) -> bool {
-        match self.linearize(parser, other) {
-            Ok(_) => true,
-            Err(_) => false,
-        }
+        self.linearize(parser, other).is_ok()
     }
 
-    pub fn get_precedence(&self) -> usize {
+    /// Ensure that all value patterns within this pattern evaluate to constants, since
+    /// they can't be compared to the arguments of a call otherwise.
+    pub fn expect_constant_values(&self, parser: &Parser) -> Result<(), ParserError> {
         match self {
-            Pattern::Value(_) => 2,
-            _ => 1,
+            Pattern::Value(ValuePattern { expression }) => {
+                Constant::evaluate(parser, expression).map(|_| ())
+            }
+            Pattern::Field(FieldPattern { value, .. }) => value.expect_constant_values(parser),
+            Pattern::Tuple(TuplePattern { child }) => child.expect_constant_values(parser),
+            Pattern::Pair(PairPattern { left, right }) => {
+                left.expect_constant_values(parser)?;
+                right.expect_constant_values(parser)
+            }
+            Pattern::List(ListPattern { elements }) => elements
+                .iter()
+                .try_for_each(|element| element.expect_constant_values(parser)),
+            Pattern::Variable(_) | Pattern::Rest(_) => Ok(()),
         }
     }
 
@@ -170,6 +300,8 @@ impl Pattern {
             Pattern::Value(pattern) => Pattern::Value(pattern.clone().desugar()),
             Pattern::Variable(pattern) => Pattern::Variable(pattern.clone().desugar()),
             Pattern::Pair(pattern) => Pattern::Pair(pattern.clone().desugar()),
+            Pattern::Rest(pattern) => Pattern::Rest(pattern.clone().desugar()),
+            Pattern::List(pattern) => Pattern::List(pattern.clone().desugar()),
         }
     }
 
@@ -212,16 +344,14 @@ impl Pattern {
         reference: ValuePattern,
         other: Pattern,
     ) -> LinearizeResult {
-        let reference_lexeme =
-            parser.get_lexeme(reference.expression.start_pos, reference.expression.end_pos)?;
+        // Value patterns in signatures are checked to be constant when the method is defined.
+        let expected = Constant::evaluate(parser, &reference.expression)?;
 
         if let Pattern::Value(ValuePattern { expression }) = other {
-            let given_lexeme = parser.get_lexeme(expression.start_pos, expression.end_pos)?;
-
-            if reference.expression.kind == expression.kind && reference_lexeme == given_lexeme {
-                Ok(HashMap::new())
-            } else {
-                Err(ParserError::NoMatch)
+            // Arguments which are only known at runtime can never match a value pattern statically.
+            match Constant::evaluate(parser, &expression) {
+                Ok(given) if given.numeric_eq(&expected) => Ok(HashMap::new()),
+                _ => Err(ParserError::NoMatch),
             }
         } else {
             Err(ParserError::NoMatch)
@@ -231,8 +361,23 @@ impl Pattern {
     fn linearize_variable(&self, reference: VariablePattern, other: Pattern) -> LinearizeResult {
         let mut variables = HashMap::new();
 
+        // Check the type annotation against the value's type if it is known at compile time.
+        // Values which only get their type at runtime are checked by the dispatcher instead.
+        if let (Some(expected), Pattern::Value(ValuePattern { expression })) =
+            (&reference.type_id, &other)
+        {
+            let found = expression.get_type();
+
+            if found.name().is_some_and(|name| name != expected.name()) {
+                return Err(ParserError::UnexpectedType {
+                    expected: Type::from(expected),
+                    found,
+                });
+            }
+        }
+
         if let Some(name) = reference.name {
-            // Extract value into environment and skip type checking for now.
+            // Extract value into environment.
             if let Pattern::Value(ValuePattern { expression }) = other {
                 variables.insert(
                     VariablePattern {
@@ -256,6 +401,14 @@ impl Pattern {
         reference: PairPattern,
         other: Pattern,
     ) -> LinearizeResult {
+        let elements = Pattern::Pair(reference.clone()).flatten_pairs();
+
+        // Variadic signatures like `first, ...rest` match any number of trailing arguments,
+        // so we need to compare them element by element instead of structurally.
+        if let Some(Pattern::Rest(_)) = elements.last() {
+            return self.linearize_sequence(parser, elements, other.flatten_pairs());
+        }
+
         if let Pattern::Pair(PairPattern { left, right }) = other {
             let mut left_map = reference.left.linearize(parser, *left)?;
             let right_map = reference.right.linearize(parser, *right)?;
@@ -267,6 +420,86 @@ impl Pattern {
             Err(ParserError::NoMatch)
         }
     }
+
+    fn linearize_rest(&self, reference: RestPattern, given: Vec<Pattern>) -> LinearizeResult {
+        let mut variables = HashMap::new();
+
+        if let Some(name) = reference.name {
+            let child =
+                Pattern::from_elements(given).map(|chain| Box::new(chain.into_expression()));
+
+            variables.insert(
+                VariablePattern {
+                    name: Some(name),
+                    type_id: None,
+                },
+                Box::new(Expression {
+                    kind: ExpressionKind::List(child),
+                    start_pos: 0,
+                    end_pos: 0,
+                }),
+            );
+        }
+
+        Ok(variables)
+    }
+
+    fn linearize_list(
+        &self,
+        parser: &Parser,
+        reference: ListPattern,
+        other: Pattern,
+    ) -> LinearizeResult {
+        let given = match other {
+            Pattern::List(ListPattern { elements }) => elements,
+
+            Pattern::Value(ValuePattern { expression }) => match expression.kind {
+                ExpressionKind::List(Some(child)) => {
+                    child.pattern_or_value_pattern()?.flatten_pairs()
+                }
+                ExpressionKind::List(None) => vec![],
+                _ => return Err(ParserError::NoMatch),
+            },
+
+            _ => return Err(ParserError::NoMatch),
+        };
+
+        self.linearize_sequence(parser, reference.elements, given)
+    }
+
+    /// Match a sequence of patterns element by element, allowing a trailing rest pattern
+    /// to collect any remaining elements.
+    fn linearize_sequence(
+        &self,
+        parser: &Parser,
+        reference: Vec<Pattern>,
+        given: Vec<Pattern>,
+    ) -> LinearizeResult {
+        Pattern::expect_trailing_rest(&reference)?;
+
+        let mut variables = HashMap::new();
+        let mut given = given.into_iter();
+
+        for pattern in reference {
+            if let Pattern::Rest(rest) = pattern {
+                variables.extend(self.linearize_rest(rest, given.collect())?);
+
+                return Ok(variables);
+            }
+
+            match given.next() {
+                Some(element) => variables.extend(pattern.linearize(parser, element)?),
+                None => return Err(ParserError::NoMatch),
+            }
+        }
+
+        // Without a rest pattern, both sequences need to have the same length.
+        if given.next().is_some() {
+            Err(ParserError::NoMatch)
+        } else {
+            Ok(variables)
+        }
+    }
 }
 
 impl std::fmt::Display for Pattern {
@@ -277,6 +510,113 @@ impl std::fmt::Display for Pattern {
             Pattern::Value(pattern) => write!(f, "{}", pattern),
             Pattern::Variable(pattern) => write!(f, "{}", pattern),
             Pattern::Pair(pattern) => write!(f, "{}", pattern),
+            Pattern::Rest(pattern) => write!(f, "{}", pattern),
+            Pattern::List(pattern) => write!(f, "{}", pattern),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::*;
+
+    /// Parse a method definition followed by a call, returning their signatures.
+    fn parse(source: &str) -> (Parser, Pattern, Pattern) {
+        let mut lexer = Lexer::new();
+        let mut parser = Parser::new();
+
+        lexer.add_text(source.to_string());
+        parser.add_tokens(source.to_string(), lexer.parse());
+
+        let expressions = parser.parse().unwrap();
+
+        let signature = match &expressions[0].kind {
+            ExpressionKind::Method(method) => method.signature.clone().unwrap(),
+            _ => panic!("expected a method definition"),
+        };
+
+        let arguments = match &expressions[1].kind {
+            ExpressionKind::Call(call) => call.signature.clone().unwrap(),
+            _ => panic!("expected a call"),
+        };
+
+        (parser, signature, arguments)
+    }
+
+    fn variable(name: &str) -> VariablePattern {
+        VariablePattern {
+            name: Some(name.to_string()),
+            type_id: None,
+        }
+    }
+
+    #[test]
+    fn rest_pattern_collects_remaining_arguments() {
+        let (parser, signature, arguments) = parse("def sum(first, ...rest) first sum(1, 2, 3)");
+        let variables = signature.linearize(&parser, arguments).unwrap();
+
+        assert_eq!(
+            variables[&variable("first")].kind,
+            ExpressionKind::Literal(Literal::Int)
+        );
+        assert!(matches!(
+            variables[&variable("rest")].kind,
+            ExpressionKind::List(Some(_))
+        ));
+    }
+
+    #[test]
+    fn rest_pattern_matches_no_remaining_arguments() {
+        let (parser, signature, arguments) = parse("def sum(first, ...rest) first sum(1)");
+        let variables = signature.linearize(&parser, arguments).unwrap();
+
+        assert_eq!(
+            variables[&variable("rest")].kind,
+            ExpressionKind::List(None)
+        );
+    }
+
+    #[test]
+    fn list_pattern_destructures_list_literal() {
+        let (parser, signature, arguments) = parse("def head([h, ...t]) h head([1, 2])");
+
+        assert!(matches!(signature, Pattern::List(_)));
+
+        let variables = signature.linearize(&parser, arguments).unwrap();
+
+        assert_eq!(
+            variables[&variable("h")].kind,
+            ExpressionKind::Literal(Literal::Int)
+        );
+        assert!(matches!(
+            variables[&variable("t")].kind,
+            ExpressionKind::List(Some(_))
+        ));
+    }
+
+    #[test]
+    fn typed_variable_pattern_checks_literal_type() {
+        let (parser, _, arguments) = parse("def f(n) n f(\"hello\")");
+
+        let int = Pattern::Variable(VariablePattern {
+            name: Some("n".to_string()),
+            type_id: Some(TypeExpression::named("Int")),
+        });
+        let string = Pattern::Variable(VariablePattern {
+            name: Some("n".to_string()),
+            type_id: Some(TypeExpression::named("String")),
+        });
+
+        assert!(!int.matches_with(&parser, arguments.clone()));
+        assert!(string.matches_with(&parser, arguments));
+    }
+
+    #[test]
+    fn list_pattern_requires_matching_length() {
+        let (parser, signature, arguments) = parse("def pair([a, b]) a pair([1, 2, 3])");
+
+        assert!(!signature.matches_with(&parser, arguments));
+    }
+}
@@ -11,12 +11,9 @@ pub struct PairPattern {
 
 impl PairPattern {
     pub fn desugar(mut self) -> PairPattern {
-        self.left.desugar();
-        self.right.desugar();
-
         PairPattern {
-            left: self.left,
-            right: self.right,
+            left: Box::new(self.left.desugar()),
+            right: Box::new(self.right.desugar()),
         }
     }
 }
@@ -0,0 +1,27 @@
+//! A pattern which collects all remaining elements of a sequence.
+
+/// A pattern which collects all remaining elements of a sequence, like `...rest`.
+///
+/// Rest patterns may only appear as the last element of a method signature or a list
+/// pattern. If the name is omitted (`...`), the remaining elements are matched but
+/// not bound to a variable.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RestPattern {
+    pub name: Option<String>,
+}
+
+impl RestPattern {
+    pub fn desugar(self) -> RestPattern {
+        self
+    }
+}
+
+impl std::fmt::Display for RestPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "...{}", name)
+        } else {
+            write!(f, "...")
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Compare patterns by how specific they are, and whether they accept the same values.
+//!
+//! Specificity forms a partial order: value patterns are more specific than typed variables,
+//! which in turn are more specific than untyped variables, and rest patterns are the least
+//! specific of all. Among typed variables, those of a subtype are more specific than those
+//! of its supertypes. Compound patterns are compared element by element, so `0, n` is more
+//! specific than `a, b`, while `0, b` and `a, 0` can't be compared at all.
+
+use crate::compiler::TypeSystem;
+use crate::parser::Parser;
+use crate::types::{Pattern, TypeExpression, VariablePattern};
+
+use std::cmp::Ordering;
+
+impl Pattern {
+    /// Compare the specificity of this pattern with another.
+    ///
+    /// Returns [`Ordering::Greater`] if this pattern is more specific than the other one,
+    /// and `None` if the two patterns are incomparable, like `n Int` and `s String`.
+    pub fn compare_specificity(&self, types: &TypeSystem, other: &Pattern) -> Option<Ordering> {
+        match (self, other) {
+            (Pattern::Value(_), Pattern::Value(_)) => Some(Ordering::Equal),
+            (Pattern::Value(_), _) => Some(Ordering::Greater),
+            (_, Pattern::Value(_)) => Some(Ordering::Less),
+
+            (Pattern::Variable(reference), Pattern::Variable(given)) => {
+                match (&reference.type_id, &given.type_id) {
+                    (Some(reference), Some(given)) => {
+                        let (reference, given) =
+                            (Self::runtime_name(reference), Self::runtime_name(given));
+
+                        if reference == given {
+                            Some(Ordering::Equal)
+                        } else if types.is_subtype(&reference, &given) {
+                            Some(Ordering::Greater)
+                        } else if types.is_subtype(&given, &reference) {
+                            Some(Ordering::Less)
+                        } else {
+                            None
+                        }
+                    }
+                    (Some(_), None) => Some(Ordering::Greater),
+                    (None, Some(_)) => Some(Ordering::Less),
+                    (None, None) => Some(Ordering::Equal),
+                }
+            }
+
+            (Pattern::Rest(_), Pattern::Rest(_)) => Some(Ordering::Equal),
+            (Pattern::Rest(_), _) => Some(Ordering::Less),
+            (_, Pattern::Rest(_)) => Some(Ordering::Greater),
+
+            // An untyped variable accepts anything, so any destructuring pattern beats it.
+            (Pattern::Variable(VariablePattern { type_id: None, .. }), _) => Some(Ordering::Less),
+            (_, Pattern::Variable(VariablePattern { type_id: None, .. })) => {
+                Some(Ordering::Greater)
+            }
+
+            (Pattern::Pair(_), Pattern::Pair(_)) => Pattern::compare_sequences(
+                types,
+                &self.clone().flatten_pairs(),
+                &other.clone().flatten_pairs(),
+            ),
+            (Pattern::List(reference), Pattern::List(given)) => {
+                Pattern::compare_sequences(types, &reference.elements, &given.elements)
+            }
+            (Pattern::Tuple(reference), Pattern::Tuple(given)) => {
+                reference.child.compare_specificity(types, &given.child)
+            }
+            (Pattern::Field(reference), Pattern::Field(given)) if reference.name == given.name => {
+                reference.value.compare_specificity(types, &given.value)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Compare the specificity of two sequences of patterns element by element.
+    ///
+    /// The sequences are more specific than one another if at least one element is, while
+    /// none of the others are less specific. A trailing rest pattern stands in for any
+    /// number of elements, which makes a fixed-length sequence more specific.
+    pub fn compare_sequences(
+        types: &TypeSystem,
+        reference: &[Pattern],
+        given: &[Pattern],
+    ) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+
+        for index in 0..reference.len().max(given.len()) {
+            let next = match (
+                Pattern::element_at(reference, index),
+                Pattern::element_at(given, index),
+            ) {
+                (Some(reference), Some(given)) => reference.compare_specificity(types, given)?,
+                (None, Some(Pattern::Rest(_))) => Ordering::Greater,
+                (Some(Pattern::Rest(_)), None) => Ordering::Less,
+                // Sequences of different lengths never match the same arguments.
+                _ => return None,
+            };
+
+            ordering = match (ordering, next) {
+                (Ordering::Equal, next) => next,
+                (ordering, Ordering::Equal) => ordering,
+                (ordering, next) if ordering == next => ordering,
+                _ => return None,
+            };
+        }
+
+        Some(ordering)
+    }
+
+    /// Check whether this pattern accepts exactly the same values as another one, regardless
+    /// of the names of any variables it binds.
+    pub fn is_equivalent(&self, parser: &Parser, other: &Pattern) -> bool {
+        match (self, other) {
+            (Pattern::Value(reference), Pattern::Value(_)) => self
+                .linearize_value(parser, reference.clone(), other.clone())
+                .is_ok(),
+            (Pattern::Variable(reference), Pattern::Variable(given)) => {
+                reference.type_id.as_ref().map(Self::runtime_name)
+                    == given.type_id.as_ref().map(Self::runtime_name)
+            }
+            (Pattern::Rest(_), Pattern::Rest(_)) => true,
+            (Pattern::Pair(_), Pattern::Pair(_)) => Pattern::are_equivalent_sequences(
+                parser,
+                &self.clone().flatten_pairs(),
+                &other.clone().flatten_pairs(),
+            ),
+            (Pattern::List(reference), Pattern::List(given)) => {
+                Pattern::are_equivalent_sequences(parser, &reference.elements, &given.elements)
+            }
+            (Pattern::Tuple(reference), Pattern::Tuple(given)) => {
+                reference.child.is_equivalent(parser, &given.child)
+            }
+            (Pattern::Field(reference), Pattern::Field(given)) => {
+                reference.name == given.name && reference.value.is_equivalent(parser, &given.value)
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Check whether two sequences of patterns accept exactly the same values.
+    pub fn are_equivalent_sequences(
+        parser: &Parser,
+        reference: &[Pattern],
+        given: &[Pattern],
+    ) -> bool {
+        reference.len() == given.len()
+            && reference
+                .iter()
+                .zip(given)
+                .all(|(reference, given)| reference.is_equivalent(parser, given))
+    }
+
+    /// Get the name of the type a typed variable checks at runtime. Type arguments are
+    /// erased, so `List[T]` is the same as `List[Int]`, and the aliases `Int64` and
+    /// `Float64` are the same as `Int` and `Float`.
+    fn runtime_name(type_id: &TypeExpression) -> String {
+        type_id.canonical().name()
+    }
+
+    /// Get the element at the given position of a sequence, where a trailing rest pattern
+    /// stands in for any elements beyond the end.
+    fn element_at(sequence: &[Pattern], index: usize) -> Option<&Pattern> {
+        match sequence.get(index) {
+            Some(element) => Some(element),
+            None => match sequence.last() {
+                Some(rest @ Pattern::Rest(_)) => Some(rest),
+                _ => None,
+            },
+        }
+    }
+}
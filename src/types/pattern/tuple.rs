@@ -8,9 +8,9 @@ pub struct TuplePattern {
 
 impl TuplePattern {
     pub fn desugar(mut self) -> TuplePattern {
-        self.child.desugar();
-
-        TuplePattern { child: self.child }
+        TuplePattern {
+            child: Box::new(self.child.desugar()),
+        }
     }
 }
 
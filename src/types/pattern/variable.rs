@@ -1,3 +1,5 @@
+use crate::types::TypeExpression;
+
 /// A variable identifier with an optional type annotation.
 ///
 /// If the name of the identifier happens to be a single underscore character,
@@ -5,7 +7,7 @@
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct VariablePattern {
     pub name: Option<String>,
-    pub type_id: Option<String>,
+    pub type_id: Option<TypeExpression>,
 }
 
 impl VariablePattern {
@@ -1,4 +1,5 @@
-use super::{Expression, ExpressionKind, Pattern, Token, TokenKind};
+use super::{ArithmeticError, Expression, ExpressionKind, Pattern, Token, TokenKind};
+use crate::type_system::{Type, TypeError};
 
 pub type CompilerResult<T> = Result<T, CompilerError>;
 
@@ -11,11 +12,55 @@ pub enum CompilerError {
         signature: Option<Pattern>,
     },
     MethodNotFound(String),
+    /// A type with the given name has already been defined.
+    DuplicateType(String),
+    /// A type lists something other than an interface after `with`.
+    UnknownInterface {
+        type_name: String,
+        interface_name: String,
+    },
+    /// The supertypes of a type can't be put into a consistent order, for example because
+    /// they refer to each other.
+    InconsistentTypeHierarchy(String),
+    /// A type doesn't provide a method required by one of its interfaces.
+    MissingInterfaceMethod {
+        type_name: String,
+        interface_name: String,
+        method_name: String,
+        signature: Option<Box<Pattern>>,
+    },
+    /// Several methods match a call equally well, and none of them is more specific.
+    AmbiguousMethod {
+        method_name: String,
+        signatures: Vec<Option<Pattern>>,
+    },
     MethodSignatureNotFound {
         method_name: String,
         pattern: Option<Pattern>,
     },
+    /// A method variant or guard is called, but was never compiled, so the call can't be
+    /// linked to its address.
+    UnlinkedMethod(String),
+    /// `next_method` was called in the least specific variant of a multimethod.
+    NoNextMethod {
+        method_name: String,
+        arguments: Option<Pattern>,
+    },
+    /// A value pattern in the method's signature isn't known at compile time.
+    NonConstantPattern {
+        method_name: String,
+        expression: Box<Expression>,
+    },
+    /// A built-in operator is applied to constant integers for which it has no result,
+    /// like in `1 / 0`.
+    InvalidArithmetic {
+        error: ArithmeticError,
+        start_pos: usize,
+        end_pos: usize,
+    },
     ParserError(ParserError),
+    /// The types of some expressions don't fit where they're used.
+    TypeErrors(Vec<TypeError>),
 }
 
 impl std::fmt::Display for CompilerError {
@@ -23,14 +68,78 @@ impl std::fmt::Display for CompilerError {
         let error_description: String = match self {
             Self::Generic(string) => string.clone(),
             Self::ParserError(error) => format!("{}", error),
+            Self::TypeErrors(errors) => errors
+                .iter()
+                .map(|error| format!("{}", error))
+                .collect::<Vec<String>>()
+                .join("\n"),
             Self::DuplicateMethodSignature { method_name, .. } => format!(
                 "this method signature has already been defined for the `{}` multimethod",
-                format!("{}", method_name)
+                method_name
+            ),
+            Self::MethodNotFound(method_name) => {
+                format!("cannot find multimethod named `{}`", method_name)
+            }
+            Self::DuplicateType(type_name) => {
+                format!("a type named `{}` has already been defined", type_name)
+            }
+            Self::UnknownInterface {
+                type_name,
+                interface_name,
+            } => format!(
+                "`{}` can't implement `{}`, which is not an interface",
+                type_name, interface_name
+            ),
+            Self::InconsistentTypeHierarchy(type_name) => format!(
+                "the supertypes of `{}` can't be ordered consistently",
+                type_name
             ),
-            Self::MethodNotFound(method_name) => format!(
-                "cannot find multimethod named `{}`",
-                format!("{}", method_name)
+            Self::MissingInterfaceMethod {
+                type_name,
+                interface_name,
+                method_name,
+                signature,
+            } => format!(
+                "`{}` doesn't implement `{}({})` required by the interface `{}`",
+                type_name,
+                method_name,
+                signature
+                    .as_ref()
+                    .map(|pattern| format!("{}", pattern))
+                    .unwrap_or_default(),
+                interface_name
+            ),
+            Self::AmbiguousMethod {
+                method_name,
+                signatures,
+            } => format!(
+                "ambiguous call to `{}`, the signatures {} are equally specific",
+                method_name,
+                signatures
+                    .iter()
+                    .map(|signature| match signature {
+                        Some(pattern) => format!("`({})`", pattern),
+                        None => String::from("`()`"),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" and ")
+            ),
+            Self::UnlinkedMethod(method_id) => format!(
+                "the method variant `{}` is called, but has not been compiled",
+                method_id
             ),
+            Self::NoNextMethod { method_name, .. } => format!(
+                "there is no less specific variant of `{}` to call with `next_method`",
+                method_name
+            ),
+            Self::NonConstantPattern {
+                method_name,
+                expression,
+            } => format!(
+                "the value pattern `{:?}` in a signature of `{}` is not a constant",
+                expression, method_name
+            ),
+            Self::InvalidArithmetic { error, .. } => format!("{}", error),
             Self::MethodSignatureNotFound {
                 method_name,
                 pattern,
@@ -50,6 +159,82 @@ impl From<ParserError> for CompilerError {
     }
 }
 
+/// A problem in the source code which doesn't prevent it from being compiled.
+#[derive(Debug, Clone)]
+pub enum CompilerWarning {
+    /// No variant of the multimethod accepts the arguments of this call.
+    NonExhaustiveCall {
+        method_name: String,
+        arguments: Option<Pattern>,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// This variant is never selected, since the variants tried before it already accept
+    /// all of its arguments.
+    UnreachableMethod {
+        method_name: String,
+        signature: Option<Pattern>,
+        start_pos: usize,
+        end_pos: usize,
+    },
+    /// The multimethod handles some variants of an enum, but not all of them.
+    MissingVariants {
+        method_name: String,
+        type_name: String,
+        variants: Vec<String>,
+        start_pos: usize,
+        end_pos: usize,
+    },
+}
+
+impl std::fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let warning_description = match self {
+            Self::NonExhaustiveCall {
+                method_name,
+                arguments,
+                ..
+            } => format!(
+                "no variant of `{}` accepts the arguments `({})`",
+                method_name,
+                arguments
+                    .as_ref()
+                    .map(|pattern| format!("{}", pattern))
+                    .unwrap_or_default()
+            ),
+            Self::UnreachableMethod {
+                method_name,
+                signature,
+                ..
+            } => format!(
+                "the variant `{}({})` is unreachable, since other variants accept all of its arguments",
+                method_name,
+                signature
+                    .as_ref()
+                    .map(|pattern| format!("{}", pattern))
+                    .unwrap_or_default()
+            ),
+            Self::MissingVariants {
+                method_name,
+                type_name,
+                variants,
+                ..
+            } => format!(
+                "`{}` doesn't handle the variants {} of `{}`",
+                method_name,
+                variants
+                    .iter()
+                    .map(|variant| format!("`{}`", variant))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                type_name
+            ),
+        };
+
+        write!(f, "{}", warning_description)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParserError {
     MissingPrefixParselet(TokenKind),
@@ -59,18 +244,27 @@ pub enum ParserError {
     },
     UnexpectedEOF,
     UnexpectedExpression {
-        expected: ExpressionKind,
-        found: Expression,
+        expected: Box<ExpressionKind>,
+        found: Box<Expression>,
     },
     UnexpectedType {
-        expected: String,
-        found: Option<String>,
+        expected: Type,
+        found: Type,
     },
     UnexpectedPattern {
         expected: String,
         found: String,
     },
     ExpectedPattern,
+    /// A rest pattern like `...rest` was found somewhere other than the end of a sequence.
+    MisplacedRestPattern,
+    /// The expression's value can not be computed at compile time.
+    NotConstant(Box<Expression>),
+    /// A character literal like `'ab'` doesn't contain exactly one character.
+    InvalidCharacter(Token),
+    /// An integer literal like `0b12` or `0x` contains no digits, or digits which aren't
+    /// valid for its radix.
+    InvalidInteger(Token),
     /// The linearization of the two given patterns failed.
     NoMatch,
 }
@@ -84,18 +278,30 @@ impl std::fmt::Display for ParserError {
             Self::UnexpectedToken { expected, found } => {
                 format!("expected token {:?}, found {:?}", expected, found)
             }
-            Self::UnexpectedEOF => format!("expected expression, found end of input"),
+            Self::UnexpectedEOF => "expected expression, found end of input".to_string(),
             Self::UnexpectedExpression { expected, found } => {
                 format!("expected expression {:?}, found {:?}", expected, found)
             }
             Self::UnexpectedType { expected, found } => {
-                format!("expected type {:?}, found {:?}", expected, found)
+                format!("expected type `{}`, found `{}`", expected, found)
             }
             Self::UnexpectedPattern { expected, found } => {
                 format!("expected pattern {:?}, found {:?}", expected, found)
             }
-            Self::ExpectedPattern => format!("expected to find a pattern"),
-            Self::NoMatch => format!("the given patterns do not match"),
+            Self::ExpectedPattern => "expected to find a pattern".to_string(),
+            Self::MisplacedRestPattern => {
+                "a rest pattern may only appear at the end of a sequence".to_string()
+            }
+            Self::NotConstant(expression) => {
+                format!("expected a constant expression, found {:?}", expression)
+            }
+            Self::InvalidCharacter(_) => {
+                "a character literal needs to contain exactly one character".to_string()
+            }
+            Self::InvalidInteger(_) => {
+                "an integer literal needs digits which are valid for its radix".to_string()
+            }
+            Self::NoMatch => "the given patterns do not match".to_string(),
         };
 
         write!(f, "{}", error_description)
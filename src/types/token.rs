@@ -1,4 +1,4 @@
-use crate::type_system::Typed;
+use crate::type_system::{NumericType, Type, Typed};
 
 /// A single textual entity of a program like `(` or `if`.
 ///
@@ -18,9 +18,12 @@ pub struct Token {
 pub enum TokenKind {
     Bang,
     BangEqual,
+    /// The exponentiation operator in `2 ^ 10`.
+    Caret,
     Colon,
     Comma,
     Dot,
+    Ellipsis,
     Equal,
     EqualEqual,
     Greater,
@@ -36,6 +39,10 @@ pub enum TokenKind {
     Plus,
     PlusEqual,
     QuestionMark,
+    /// The safe navigation operator in `person?.name`.
+    QuestionDot,
+    /// The null-coalescing operator in `name ?? "anonymous"`.
+    QuestionQuestion,
     RightBracket,
     RightParen,
     Slash,
@@ -64,6 +71,7 @@ pub enum Keyword {
     If,
     Import,
     Interface,
+    Is,
     It,
     For,
     Match,
@@ -72,6 +80,7 @@ pub enum Keyword {
     Then,
     This,
     Var,
+    Where,
     With,
     While,
 }
@@ -82,15 +91,27 @@ pub enum Literal {
     Float,
     String,
     Boolean,
+    /// The absence of a value, `nil`.
+    Nil,
+    /// A single character like `'a'` or `'é'`, which is a grapheme cluster.
+    Char,
+    /// An integer which doesn't fit into an `Int`, like `18446744073709551616`.
+    BigInt,
+    /// A number with a suffix which selects its type, like `255u8` or `1.5f32`.
+    Sized(NumericType),
 }
 
 impl Typed for Literal {
-    fn get_type(&self) -> Option<String> {
-        Some(match self {
-            Literal::Int => String::from("Int"),
-            Literal::Float => String::from("Float"),
-            Literal::String => String::from("String"),
-            Literal::Boolean => String::from("Boolean"),
-        })
+    fn get_type(&self) -> Type {
+        match self {
+            Literal::Int => Type::Int,
+            Literal::Float => Type::Float,
+            Literal::String => Type::String,
+            Literal::Boolean => Type::Boolean,
+            Literal::Nil => Type::Nil,
+            Literal::Char => Type::Char,
+            Literal::BigInt => Type::BigInt,
+            Literal::Sized(numeric_type) => Type::numeric(*numeric_type),
+        }
     }
 }
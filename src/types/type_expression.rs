@@ -0,0 +1,98 @@
+//! A structured representation of type annotations.
+
+use crate::type_system::{NumericType, Type};
+
+use std::collections::HashMap;
+
+/// A type annotation like `Int`, `List[Int]`, `Map[String, Int]` or `Geometry.Point`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TypeExpression {
+    /// The segments of a possibly qualified type name, like `["Geometry", "Point"]`.
+    pub path: Vec<String>,
+    /// The type arguments of a generic type, like `Int` in `List[Int]`.
+    pub arguments: Vec<TypeExpression>,
+}
+
+impl TypeExpression {
+    /// Create a type expression for a simple type name without any type arguments.
+    pub fn named(name: &str) -> Self {
+        Self {
+            path: vec![String::from(name)],
+            arguments: vec![],
+        }
+    }
+
+    /// Create a type expression for a value of the given type which may also be `nil`,
+    /// written as `Int?`.
+    pub fn optional(value_type: TypeExpression) -> Self {
+        Self {
+            path: vec![String::from("Option")],
+            arguments: vec![value_type],
+        }
+    }
+
+    /// Get the type of the value if this is an optional type like `Int?`.
+    pub fn optional_value(&self) -> Option<&TypeExpression> {
+        match self.arguments.as_slice() {
+            [value_type] if self.path == ["Option"] => Some(value_type),
+            _ => None,
+        }
+    }
+
+    /// Get the fully qualified name of the type without its type arguments.
+    pub fn name(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// Get this annotation with the aliases `Int64` and `Float64` replaced by `Int` and
+    /// `Float`, which are the names their values have at runtime.
+    pub fn canonical(&self) -> TypeExpression {
+        let path = match NumericType::from_name(&self.name()).map(Type::numeric) {
+            Some(numeric @ (Type::Int | Type::Float)) => vec![numeric.to_string()],
+            _ => self.path.clone(),
+        };
+
+        TypeExpression {
+            path,
+            arguments: self.arguments.iter().map(Self::canonical).collect(),
+        }
+    }
+
+    /// Replace the type parameters named in this annotation by the given types, like `T`
+    /// in `List[T]`.
+    pub fn substitute(&self, bindings: &HashMap<String, TypeExpression>) -> TypeExpression {
+        match bindings.get(&self.name()) {
+            Some(bound) if self.arguments.is_empty() => bound.clone(),
+            _ => TypeExpression {
+                path: self.path.clone(),
+                arguments: self
+                    .arguments
+                    .iter()
+                    .map(|argument| argument.substitute(bindings))
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for TypeExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(value_type) = self.optional_value() {
+            return write!(f, "{}?", value_type);
+        }
+
+        write!(f, "{}", self.name())?;
+
+        if !self.arguments.is_empty() {
+            let arguments = self
+                .arguments
+                .iter()
+                .map(|argument| format!("{}", argument))
+                .collect::<Vec<String>>();
+
+            write!(f, "[{}]", arguments.join(", "))?;
+        }
+
+        Ok(())
+    }
+}